@@ -0,0 +1,52 @@
+#[macro_use]
+extern crate criterion;
+extern crate prev_iter;
+
+use criterion::{black_box, Criterion};
+use prev_iter::{PrevBytes, PrevPeekable};
+
+/// Counts the lines in `bytes` by skipping to each newline.
+fn lines_prev_peekable(bytes: &[u8]) -> usize {
+    let mut it = PrevPeekable::new(bytes.iter().copied());
+    let mut count = 0;
+    loop {
+        it.skip_until_byte(b'\n');
+        if !it.eat_byte(b'\n') {
+            return count;
+        }
+        count += 1;
+    }
+}
+
+fn lines_prev_bytes(bytes: &[u8]) -> usize {
+    let mut it = PrevBytes::new(bytes);
+    let mut count = 0;
+    loop {
+        it.skip_until_byte(b'\n');
+        if !it.eat_byte(b'\n') {
+            return count;
+        }
+        count += 1;
+    }
+}
+
+fn bench_skip_until_byte(c: &mut Criterion) {
+    // 4 MiB of printable bytes, with a newline roughly every 4 KiB
+    let bytes: Vec<u8> = (0..4 * 1024 * 1024u32)
+        .map(|i| match i.wrapping_mul(2_654_435_761) >> 20 {
+            0 => b'\n',
+            x => b' ' + (x % 94) as u8,
+        })
+        .collect();
+    assert_eq!(lines_prev_peekable(&bytes), lines_prev_bytes(&bytes));
+
+    let mut group = c.benchmark_group("skip_until_byte");
+    group.bench_function("PrevPeekable", |b| {
+        b.iter(|| lines_prev_peekable(black_box(&bytes)))
+    });
+    group.bench_function("PrevBytes", |b| b.iter(|| lines_prev_bytes(black_box(&bytes))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_skip_until_byte);
+criterion_main!(benches);
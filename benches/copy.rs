@@ -0,0 +1,60 @@
+#[macro_use]
+extern crate criterion;
+extern crate prev_iter;
+
+use criterion::{black_box, Criterion};
+use prev_iter::{PrevCopyPeekable, PrevPeekable};
+
+/// Counts the positions where a byte differs from the one before it.
+fn transitions_loop(bytes: &[u8]) -> usize {
+    let mut count = 0;
+    for i in 1..bytes.len() {
+        if bytes[i] != bytes[i - 1] {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn transitions_clone(bytes: &[u8]) -> usize {
+    let mut it = PrevPeekable::new(bytes.iter().copied());
+    let mut count = 0;
+    while let Some(cur) = it.next() {
+        if it.prev().is_some_and(|prev| prev != cur) {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn transitions_copy(bytes: &[u8]) -> usize {
+    let mut it = PrevCopyPeekable::new(bytes.iter().copied());
+    let mut count = 0;
+    while let Some(cur) = it.next() {
+        if it.prev().is_some_and(|prev| prev != cur) {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn bench_transitions(c: &mut Criterion) {
+    let bytes: Vec<u8> = (0..64 * 1024u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 29) as u8)
+        .collect();
+
+    let mut group = c.benchmark_group("byte transitions");
+    group.bench_function("hand-written loop", |b| {
+        b.iter(|| transitions_loop(black_box(&bytes)))
+    });
+    group.bench_function("PrevPeekable", |b| {
+        b.iter(|| transitions_clone(black_box(&bytes)))
+    });
+    group.bench_function("PrevCopyPeekable", |b| {
+        b.iter(|| transitions_copy(black_box(&bytes)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_transitions);
+criterion_main!(benches);
@@ -0,0 +1,177 @@
+//! `prev-iter-derive` provides `#[derive(PrevIterator)]`, which gives a custom iterator the
+//! lookbehind and peeking of `prev_iter::PrevPeekable` without wrapping it.
+//!
+//! It's re-exported by `prev-iter` with the `derive` feature, and documented there.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use syn::{Data, DeriveInput, Error, Field, GenericArgument, Ident, LitStr, PathArguments, Type};
+
+/// Derives `Iterator` and `prev()`, `peek_prev()`, `current()` and `peek()` methods for a struct.
+/// The struct names the method that produces its next element with
+/// `#[prev_iterator(next = "step")]`, where `step` takes `&mut self` and returns `Option<Item>`, and
+/// needs one field of type `prev_iter::PrevState<Item>` marked with `#[prev_state]`. The derived
+/// `next()` calls `step` and records what it returned, so every way of advancing the iterator is
+/// tracked.
+#[proc_macro_derive(PrevIterator, attributes(prev_iterator, prev_state))]
+pub fn derive_prev_iterator(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    expand(&input).unwrap_or_else(compile_error).into()
+}
+
+/// Turns `error` into a `compile_error!` invocation. This doesn't use `Error::into_compile_error`,
+/// since its `::core` path doesn't resolve in crates on the 2015 edition.
+fn compile_error(error: Error) -> proc_macro2::TokenStream {
+    let message = error.to_string();
+
+    quote_spanned!(error.span()=> compile_error!(#message);)
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "#[derive(PrevIterator)] can only be used on structs",
+            ))
+        }
+    };
+
+    let mut marked = fields
+        .iter()
+        .enumerate()
+        .filter(|&(_, field)| field.attrs.iter().any(|attr| attr.path().is_ident("prev_state")));
+    let (index, field) = marked.next().ok_or_else(|| {
+        Error::new(
+            Span::call_site(),
+            "#[derive(PrevIterator)] needs a field of type `PrevState<Item>` marked with #[prev_state]",
+        )
+    })?;
+    if let Some((_, extra)) = marked.next() {
+        return Err(Error::new_spanned(
+            extra,
+            "only one field can be marked with #[prev_state]",
+        ));
+    }
+
+    let state = match field.ident {
+        Some(ref ident) => quote!(#ident),
+        None => {
+            let index = syn::Index::from(index);
+            quote!(#index)
+        }
+    };
+    let item = item_type(field)?;
+    let step = step_fn(input)?;
+    let name = &input.ident;
+    // Requiring `Clone` up front reports a non-`Clone` item once, rather than once for every method
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(syn::parse_quote!(#item: ::std::clone::Clone));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Returns the previous element, or `None` if there isn't one yet. When the end is
+            /// reached, it returns the last element.
+            pub fn prev(&self) -> ::std::option::Option<#item> {
+                self.#state.prev()
+            }
+
+            /// Returns a reference to the previous element, or `None` if there isn't one yet. When
+            /// the end is reached, it returns the last element.
+            pub fn peek_prev(&self) -> ::std::option::Option<&#item> {
+                self.#state.peek_prev()
+            }
+
+            /// Returns a reference to the element returned last by `next()`, or `None` before the
+            /// first element and once the end has been reached.
+            pub fn current(&self) -> ::std::option::Option<&#item> {
+                self.#state.current()
+            }
+
+            /// Returns a reference to the next element without advancing the iterator.
+            pub fn peek(&mut self) -> ::std::option::Option<&#item> {
+                if !self.#state.is_peeked() {
+                    let next = self.#step();
+                    self.#state.set_peeked(next);
+                }
+                self.#state.peeked()
+            }
+        }
+
+        impl #impl_generics ::std::iter::Iterator for #name #ty_generics #where_clause {
+            type Item = #item;
+
+            /// Returns the element peeked at by `peek()` if there is one, and otherwise the next
+            /// element from the step function, and keeps track of it.
+            fn next(&mut self) -> ::std::option::Option<#item> {
+                let next = match self.#state.take_peeked() {
+                    ::std::option::Option::Some(next) => next,
+                    ::std::option::Option::None => self.#step(),
+                };
+                self.#state.record(next)
+            }
+        }
+    })
+}
+
+/// Returns the name of the step function given by `#[prev_iterator(next = "...")]`.
+fn step_fn(input: &DeriveInput) -> syn::Result<Ident> {
+    let mut step = None;
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("prev_iterator"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("next") {
+                let name: LitStr = meta.value()?.parse()?;
+                step = Some(name.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported #[prev_iterator] option, expected `next`"))
+            }
+        })?;
+    }
+
+    step.ok_or_else(|| {
+        Error::new(
+            Span::call_site(),
+            "#[derive(PrevIterator)] needs the method that produces the next element, named with \
+             #[prev_iterator(next = \"...\")]",
+        )
+    })
+}
+
+/// Returns `Item` from the type of a field declared as `PrevState<Item>`.
+fn item_type(field: &Field) -> syn::Result<&Type> {
+    let error = || Error::new_spanned(&field.ty, "the #[prev_state] field must be a `PrevState<Item>`");
+
+    let segment = match field.ty {
+        Type::Path(ref path) => path.path.segments.last().ok_or_else(error)?,
+        _ => return Err(error()),
+    };
+    if segment.ident != "PrevState" {
+        return Err(error());
+    }
+
+    match segment.arguments {
+        PathArguments::AngleBracketed(ref args) if args.args.len() == 1 => match args.args[0] {
+            GenericArgument::Type(ref item) => Ok(item),
+            _ => Err(error()),
+        },
+        _ => Err(error()),
+    }
+}
@@ -0,0 +1,149 @@
+extern crate prev_iter;
+extern crate prev_iter_derive;
+
+use prev_iter::PrevState;
+use prev_iter_derive::PrevIterator;
+
+/// Splits a string into words, the way a hand-written tokenizer would.
+#[derive(PrevIterator)]
+#[prev_iterator(next = "next_word")]
+struct Words<'a> {
+    rest: &'a str,
+    #[prev_state]
+    state: PrevState<&'a str>,
+}
+
+impl<'a> Words<'a> {
+    fn new(s: &'a str) -> Self {
+        Words {
+            rest: s,
+            state: PrevState::new(),
+        }
+    }
+
+    fn next_word(&mut self) -> Option<&'a str> {
+        let trimmed = self.rest.trim_start();
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        let end = trimmed.find(' ').unwrap_or(trimmed.len());
+        self.rest = &trimmed[end..];
+        Some(&trimmed[..end])
+    }
+}
+
+/// A tuple struct that's generic over what it counts.
+#[derive(PrevIterator)]
+#[prev_iterator(next = "step")]
+struct Repeat<T: Clone>(T, usize, #[prev_state] PrevState<T>);
+
+impl<T: Clone> Repeat<T> {
+    fn step(&mut self) -> Option<T> {
+        self.1 = self.1.checked_sub(1)?;
+        Some(self.0.clone())
+    }
+}
+
+#[test]
+fn test_next() {
+    let mut it = Words::new("a b c");
+
+    assert_eq!(Some("a"), it.next());
+    assert_eq!(None, it.prev());
+    assert_eq!(Some("b"), it.next());
+    assert_eq!(Some("a"), it.prev());
+    assert_eq!(Some("c"), it.next());
+    assert_eq!(Some("b"), it.prev());
+    assert_eq!(None, it.next());
+    assert_eq!(None, it.next());
+}
+
+#[test]
+fn test_peek() {
+    let mut it = Words::new("a b");
+
+    assert_eq!(Some(&"a"), it.peek());
+    assert_eq!(Some(&"a"), it.peek());
+    assert_eq!(Some("a"), it.next());
+    assert_eq!(Some(&"b"), it.peek());
+    assert_eq!(Some("b"), it.next());
+    assert_eq!(None, it.peek());
+    assert_eq!(None, it.next());
+}
+
+#[test]
+fn test_prev() {
+    let mut it = Words::new("a b");
+
+    assert_eq!(None, it.prev());
+    assert_eq!(Some("a"), it.next());
+    assert_eq!(None, it.prev());
+    assert_eq!(Some("b"), it.next());
+    assert_eq!(Some("a"), it.prev());
+    assert_eq!(None, it.next());
+    assert_eq!(Some("b"), it.prev());
+    assert_eq!(None, it.next());
+    assert_eq!(Some("b"), it.prev());
+}
+
+#[test]
+fn test_peek_prev_and_current() {
+    let mut it = Words::new("a b");
+
+    assert_eq!(None, it.peek_prev());
+    assert_eq!(None, it.current());
+    assert_eq!(Some("a"), it.next());
+    assert_eq!(Some(&"a"), it.current());
+    assert_eq!(Some("b"), it.next());
+    assert_eq!(Some(&"a"), it.peek_prev());
+    assert_eq!(Some(&"b"), it.current());
+    assert_eq!(None, it.next());
+    assert_eq!(None, it.current());
+    assert_eq!(Some(&"b"), it.peek_prev());
+}
+
+#[test]
+fn test_empty() {
+    let mut it = Words::new("  ");
+
+    assert_eq!(None, it.peek());
+    assert_eq!(None, it.next());
+    assert_eq!(None, it.prev());
+}
+
+#[test]
+fn test_generic_tuple_struct() {
+    let mut it = Repeat('x', 2, PrevState::new());
+
+    assert_eq!(Some('x'), it.next());
+    assert_eq!(Some(&'x'), it.peek());
+    assert_eq!(Some('x'), it.next());
+    assert_eq!(Some('x'), it.prev());
+    assert_eq!(None, it.next());
+}
+
+#[test]
+fn test_peek_then_next() {
+    let mut it = Words::new("a b c");
+
+    assert_eq!(Some(&"a"), it.peek());
+    assert_eq!(Some("a"), it.next());
+    assert_eq!(Some(&"b"), it.peek());
+    assert_eq!(vec!["b", "c"], it.by_ref().collect::<Vec<_>>());
+    assert_eq!(Some("c"), it.prev());
+}
+
+#[test]
+fn test_peek_then_for_loop() {
+    let mut it = Words::new("a b c");
+    let mut words = Vec::new();
+
+    assert_eq!(Some(&"a"), it.peek());
+    for word in &mut it {
+        words.push(word);
+    }
+
+    assert_eq!(vec!["a", "b", "c"], words);
+    assert_eq!(Some("c"), it.prev());
+}
@@ -0,0 +1,10 @@
+extern crate trybuild;
+
+/// The expected errors pin the exact diagnostics of the stable compiler, so this only runs when
+/// asked for with `--ignored`, which CI does on stable.
+#[test]
+#[ignore = "compares stable rustc diagnostics; run with --ignored on stable"]
+fn test_misuse() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,21 @@
+extern crate prev_iter;
+extern crate prev_iter_derive;
+
+use prev_iter::PrevState;
+use prev_iter_derive::PrevIterator;
+
+#[derive(PrevIterator)]
+struct Counter {
+    count: u32,
+    #[prev_state]
+    state: PrevState<u32>,
+}
+
+impl Counter {
+    fn step(&mut self) -> Option<u32> {
+        self.count += 1;
+        Some(self.count)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+extern crate prev_iter;
+extern crate prev_iter_derive;
+
+use prev_iter::PrevState;
+use prev_iter_derive::PrevIterator;
+
+struct Token;
+
+#[derive(PrevIterator)]
+#[prev_iterator(next = "step")]
+struct Tokens {
+    #[prev_state]
+    state: PrevState<Token>,
+}
+
+impl Tokens {
+    fn step(&mut self) -> Option<Token> {
+        None
+    }
+}
+
+fn main() {}
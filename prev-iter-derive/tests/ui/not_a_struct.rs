@@ -0,0 +1,11 @@
+extern crate prev_iter_derive;
+
+use prev_iter_derive::PrevIterator;
+
+#[derive(PrevIterator)]
+enum Token {
+    Word,
+    Number,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+extern crate prev_iter_derive;
+
+use prev_iter_derive::PrevIterator;
+
+#[derive(PrevIterator)]
+#[prev_iterator(next = "step")]
+struct Counter {
+    count: u32,
+    #[prev_state]
+    last: Option<u32>,
+}
+
+impl Counter {
+    fn step(&mut self) -> Option<u32> {
+        self.count += 1;
+        Some(self.count)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,304 @@
+//! A `PrevPeekable` over a byte slice that can skip ahead in bulk.
+
+#[cfg(feature = "memchr")]
+use memchr::memchr;
+
+/// An iterator over the bytes of a slice with `prev()`, `peek_prev()`, and `peek()` functions, like
+/// a [`PrevPeekable`] over `bytes.iter().cloned()`.
+///
+/// Since the bytes are all in memory, [`skip_until_byte`], [`take_until_byte`] and [`eat_slice`]
+/// search or compare the rest of the slice directly instead of going through the bytes one at a
+/// time, and then update the position and the previous and current bytes in one step. With the
+/// `memchr` feature enabled, searching uses [`memchr`], which is much faster on long inputs. Either
+/// way, they return the same results and leave the same previous and current bytes as the
+/// `PrevPeekable` methods of the same name.
+///
+/// This `struct` is created by the [`new`] function.
+///
+/// [`PrevPeekable`]: type.PrevPeekable.html
+/// [`skip_until_byte`]: #method.skip_until_byte
+/// [`take_until_byte`]: #method.take_until_byte
+/// [`eat_slice`]: #method.eat_slice
+/// [`memchr`]: https://docs.rs/memchr/2/memchr/fn.memchr.html
+/// [`new`]: #method.new
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use prev_iter::PrevBytes;
+///
+/// let mut it = PrevBytes::new(b"Host: example.com\r\n");
+///
+/// assert_eq!(b"Host", it.take_until_byte(b':'));
+/// assert!(it.eat_slice(b": "));
+/// assert_eq!(11, it.skip_until_byte(b'\r'));
+/// assert_eq!(Some(b'o'), it.prev());
+/// assert_eq!(Some(&b'm'), it.current());
+/// assert_eq!(17, it.position());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrevBytes<'a> {
+    /// The whole input, including the bytes that have been consumed
+    bytes: &'a [u8],
+    /// How many bytes have been consumed
+    position: usize,
+    /// The byte before the one we just returned. Initially it's `None`.
+    prev: Option<u8>,
+    /// The current byte we just returned.
+    current: Option<u8>,
+    /// Keeps track of whether the end has been reached or not
+    finished: bool,
+}
+
+impl<'a> PrevBytes<'a> {
+    /// Creates a new `PrevBytes` that starts at the beginning of `bytes`.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        PrevBytes {
+            bytes,
+            position: 0,
+            prev: None,
+            current: None,
+            finished: false,
+        }
+    }
+
+    /// Returns a reference to the next byte without advancing. Like `next()`, it returns `None` if
+    /// there are no bytes left.
+    #[inline]
+    pub fn peek(&self) -> Option<&u8> {
+        self.bytes.get(self.position)
+    }
+
+    /// Returns the previous byte, or `None` if there isn't one yet.
+    #[inline]
+    pub fn prev(&self) -> Option<u8> {
+        self.prev
+    }
+
+    /// Returns a reference to the previous byte, or `None` if there isn't one yet. This is [`prev`]
+    /// by reference, like the method of the same name on [`PrevPeekable`].
+    ///
+    /// [`prev`]: #method.prev
+    /// [`PrevPeekable`]: type.PrevPeekable.html
+    #[inline]
+    pub fn peek_prev(&self) -> Option<&u8> {
+        self.prev.as_ref()
+    }
+
+    /// Returns a reference to the byte returned by the last call to `next()`, or `None` before the
+    /// first call and after the end has been reached.
+    #[inline]
+    pub fn current(&self) -> Option<&u8> {
+        self.current.as_ref()
+    }
+
+    /// Returns how many bytes have been consumed, which is also the offset of the next byte.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the bytes that haven't been consumed yet.
+    #[inline]
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.bytes[self.position..]
+    }
+
+    /// Consumes the next byte if it's `b`, and returns whether it did.
+    pub fn eat_byte(&mut self, b: u8) -> bool {
+        if self.peek() == Some(&b) {
+            self.advance(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes the next bytes if they're `bytes`, and returns whether it did. If only some of them
+    /// match, nothing is consumed.
+    pub fn eat_slice(&mut self, bytes: &[u8]) -> bool {
+        if !self.remaining().starts_with(bytes) {
+            return false;
+        }
+
+        self.advance(bytes.len());
+        true
+    }
+
+    /// Consumes bytes up to the next `b` and returns how many there were. `b` itself isn't
+    /// consumed, so it will be returned by the next call to `next()`. If there is no `b`, the rest
+    /// of the input is consumed.
+    pub fn skip_until_byte(&mut self, b: u8) -> usize {
+        self.take_until_byte(b).len()
+    }
+
+    /// Consumes bytes up to the next `b` and returns them. `b` itself isn't consumed, so it will be
+    /// returned by the next call to `next()`. If there is no `b`, the rest of the input is returned.
+    pub fn take_until_byte(&mut self, b: u8) -> &'a [u8] {
+        let remaining = self.remaining();
+        let taken = &remaining[..find(b, remaining).unwrap_or(remaining.len())];
+
+        self.advance(taken.len());
+        taken
+    }
+
+    /// Consumes the next `n` bytes, which must all exist, as if `next()` had been called `n` times.
+    fn advance(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+
+        let end = self.position + n;
+        if n >= 2 {
+            self.prev = Some(self.bytes[end - 2]);
+        } else if let Some(old) = self.current {
+            self.prev = Some(old);
+        }
+        self.current = Some(self.bytes[end - 1]);
+        self.position = end;
+    }
+}
+
+impl<'a> Iterator for PrevBytes<'a> {
+    type Item = u8;
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        match self.peek() {
+            Some(&next) => {
+                self.advance(1);
+                Some(next)
+            }
+            // The first time we reach the end, the last byte becomes the previous one
+            None => {
+                if !self.finished {
+                    if let Some(last) = self.current.take() {
+                        self.prev = Some(last);
+                    }
+                    self.finished = true;
+                }
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bytes.len() - self.position;
+        (len, Some(len))
+    }
+}
+
+/// Returns the index of the first `b` in `haystack`.
+#[cfg(feature = "memchr")]
+#[inline]
+fn find(b: u8, haystack: &[u8]) -> Option<usize> {
+    memchr(b, haystack)
+}
+
+/// Returns the index of the first `b` in `haystack`.
+#[cfg(not(feature = "memchr"))]
+#[inline]
+fn find(b: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&x| x == b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PrevPeekable;
+
+    /// A small xorshift generator, so the comparison tests are random but reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    #[test]
+    fn test_skip_and_take() {
+        let mut it = PrevBytes::new(b"key=value;rest");
+
+        assert_eq!(b"key", it.take_until_byte(b'='));
+        assert_eq!(Some(b'e'), it.prev());
+        assert_eq!(Some(&b'y'), it.current());
+        assert_eq!(0, it.skip_until_byte(b'='));
+        assert!(it.eat_byte(b'='));
+        assert_eq!(Some(b'y'), it.prev());
+        assert_eq!(5, it.skip_until_byte(b';'));
+        assert_eq!(9, it.position());
+        assert_eq!(b";rest", it.remaining());
+
+        // Without a match, the rest is consumed, but the end isn't reached until next()
+        assert_eq!(b";rest", it.take_until_byte(b'='));
+        assert_eq!(Some(&b't'), it.current());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(b't'), it.prev());
+        assert_eq!(None, it.current());
+    }
+
+    #[test]
+    fn test_eat_slice() {
+        let mut it = PrevBytes::new(b"abcd");
+
+        assert!(!it.eat_slice(b"abx"));
+        assert!(!it.eat_slice(b"abcde"));
+        assert_eq!(0, it.position());
+        assert!(it.eat_slice(b""));
+        assert_eq!(None, it.current());
+        assert!(it.eat_slice(b"a"));
+        assert_eq!(None, it.prev());
+        assert!(it.eat_slice(b"bcd"));
+        assert_eq!(Some(b'c'), it.prev());
+        assert_eq!((0, Some(0)), it.size_hint());
+    }
+
+    #[test]
+    fn test_matches_prev_peekable() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..500 {
+            // A small alphabet so searches and comparisons often succeed
+            let len = rng.below(40);
+            let input: Vec<u8> = (0..len).map(|_| b"abc"[rng.below(3)]).collect();
+            let mut fast = PrevBytes::new(&input);
+            let mut slow = PrevPeekable::new(input.iter().cloned());
+
+            for _ in 0..20 {
+                let b = b"abcd"[rng.below(4)];
+                match rng.below(5) {
+                    0 => assert_eq!(slow.next(), fast.next()),
+                    1 => assert_eq!(slow.eat_byte(b), fast.eat_byte(b)),
+                    2 => assert_eq!(slow.skip_until_byte(b), fast.skip_until_byte(b)),
+                    3 => assert_eq!(slow.take_until_byte(b), fast.take_until_byte(b).to_vec()),
+                    _ => {
+                        // Usually a prefix of the rest of the input, sometimes with the last byte changed
+                        let mut prefix = fast.remaining()[..rng.below(fast.remaining().len() + 1)].to_vec();
+                        if rng.below(3) == 0 {
+                            if let Some(last) = prefix.last_mut() {
+                                *last = b;
+                            }
+                        }
+                        assert_eq!(slow.eat_slice(&prefix), fast.eat_slice(&prefix));
+                    }
+                }
+
+                assert_eq!(slow.prev(), fast.prev());
+                assert_eq!(slow.peek_prev(), fast.peek_prev());
+                assert_eq!(slow.current(), fast.current());
+                assert_eq!(slow.peek(), fast.peek());
+                assert_eq!(input.len() - fast.remaining().len(), fast.position());
+            }
+        }
+    }
+}
@@ -0,0 +1,562 @@
+//! Helpers for parsing binary formats with a `PrevPeekable` over bytes.
+
+use super::PrevPeekable;
+use std::error::Error;
+use std::fmt;
+
+/// The error returned by [`expect_magic`] when the input doesn't start with the expected bytes.
+///
+/// [`expect_magic`]: struct.PrevPeekableWith.html#method.expect_magic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MagicError {
+    /// The offset into the magic bytes of the first byte that didn't match.
+    pub offset: usize,
+    /// The byte that was expected at `offset`.
+    pub expected: u8,
+    /// The byte found instead, or `None` if the input ended.
+    pub found: Option<u8>,
+    /// The byte before the one that didn't match, or `None` if there wasn't one.
+    pub after: Option<u8>,
+}
+
+impl fmt::Display for MagicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected {:#04x} at offset {} of the magic bytes, found ",
+            self.expected, self.offset
+        )?;
+        match self.found {
+            Some(found) => write!(f, "{:#04x}", found)?,
+            None => write!(f, "end of input")?,
+        }
+        match self.after {
+            Some(after) => write!(f, " after {:#04x}", after),
+            None => write!(f, " at the start of input"),
+        }
+    }
+}
+
+impl Error for MagicError {}
+
+/// The error returned by the `read_*` methods when there aren't enough bytes left for the value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnexpectedEof {
+    /// How many bytes the value needed.
+    pub needed: usize,
+    /// How many bytes were left.
+    pub available: usize,
+}
+
+impl fmt::Display for UnexpectedEof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unexpected end of input: needed {} bytes, but only {} were left",
+            self.needed, self.available
+        )
+    }
+}
+
+impl Error for UnexpectedEof {}
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator<Item = u8>,
+{
+    /// Consumes the next byte if it's `b`, and returns whether it did.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new(b"\x01\x02".iter().cloned());
+    ///
+    /// assert!(!it.eat_byte(0x02));
+    /// assert!(it.eat_byte(0x01));
+    /// assert!(it.eat_byte(0x02));
+    /// ```
+    pub fn eat_byte(&mut self, b: u8) -> bool {
+        if self.peek() == Some(&b) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes the next bytes if they're `bytes`, and returns whether it did. If only some of them
+    /// match, nothing is consumed.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new(b"GET /".iter().cloned());
+    ///
+    /// assert!(!it.eat_slice(b"GEM"));
+    /// assert!(it.eat_slice(b"GET "));
+    /// assert_eq!(Some(b'/'), it.next());
+    /// ```
+    pub fn eat_slice(&mut self, bytes: &[u8]) -> bool {
+        if self.mismatch(bytes).is_some() {
+            return false;
+        }
+
+        for _ in bytes {
+            self.next();
+        }
+        true
+    }
+
+    /// Consumes bytes up to the next `b` and returns them. `b` itself isn't consumed, so it will be
+    /// returned by the next call to `next()`. If there is no `b`, the rest of the input is returned.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new(b"name\0rest".iter().cloned());
+    ///
+    /// assert_eq!(b"name".to_vec(), it.take_until_byte(0));
+    /// assert!(it.eat_byte(0));
+    /// ```
+    pub fn take_until_byte(&mut self, b: u8) -> Vec<u8> {
+        let mut taken = Vec::new();
+
+        while let Some(&next) = self.peek() {
+            if next == b {
+                break;
+            }
+
+            taken.push(next);
+            self.next();
+        }
+
+        taken
+    }
+
+    /// Consumes bytes up to the next `b` and returns how many there were, like
+    /// [`take_until_byte`] without collecting them. `b` itself isn't consumed. If there is no `b`,
+    /// the rest of the input is consumed.
+    ///
+    /// This goes through the bytes one at a time. For input that's already in a slice,
+    /// [`PrevBytes::skip_until_byte`] is much faster.
+    ///
+    /// [`take_until_byte`]: #method.take_until_byte
+    /// [`PrevBytes::skip_until_byte`]: struct.PrevBytes.html#method.skip_until_byte
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new(b"# comment\nline".iter().cloned());
+    ///
+    /// assert_eq!(9, it.skip_until_byte(b'\n'));
+    /// assert_eq!(Some(b't'), it.current().cloned());
+    /// assert!(it.eat_byte(b'\n'));
+    /// ```
+    pub fn skip_until_byte(&mut self, b: u8) -> usize {
+        let mut skipped = 0;
+
+        while let Some(&next) = self.peek() {
+            if next == b {
+                break;
+            }
+
+            skipped += 1;
+            self.next();
+        }
+
+        skipped
+    }
+
+    /// Returns the previous byte, like [`prev`].
+    ///
+    /// [`prev`]: struct.PrevPeekableWith.html#method.prev
+    pub fn prev_byte(&self) -> Option<u8> {
+        self.prev()
+    }
+
+    /// Consumes the magic bytes a file format starts with. If the input doesn't start with
+    /// `magic`, nothing is consumed and the returned [`MagicError`] describes the first byte that
+    /// didn't match.
+    ///
+    /// [`MagicError`]: struct.MagicError.html
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{MagicError, PrevPeekable};
+    ///
+    /// let mut it = PrevPeekable::new(b"\x89PNX".iter().cloned());
+    ///
+    /// assert_eq!(
+    ///     Err(MagicError { offset: 3, expected: b'G', found: Some(b'X'), after: Some(b'N') }),
+    ///     it.expect_magic(b"\x89PNG")
+    /// );
+    /// // Nothing was consumed
+    /// assert_eq!(Some(0x89), it.next());
+    /// ```
+    pub fn expect_magic(&mut self, magic: &[u8]) -> Result<(), MagicError> {
+        if let Some(offset) = self.mismatch(magic) {
+            let after = match offset {
+                0 => self.last_returned().cloned(),
+                _ => Some(magic[offset - 1]),
+            };
+
+            return Err(MagicError {
+                offset,
+                expected: magic[offset],
+                found: self.peek_nth(offset).cloned(),
+                after,
+            });
+        }
+
+        for _ in magic {
+            self.next();
+        }
+        Ok(())
+    }
+
+    /// Reads a `u16` stored in little-endian byte order. If there aren't enough bytes left,
+    /// nothing is consumed.
+    ///
+    /// After a successful read, [`prev`] is the last byte of the value, so errors about what comes
+    /// next can say what it came after. It's also what [`current`] returns, until the iterator is
+    /// advanced again.
+    ///
+    /// [`prev`]: struct.PrevPeekableWith.html#method.prev
+    /// [`current`]: struct.PrevPeekableWith.html#method.current
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{PrevPeekable, UnexpectedEof};
+    ///
+    /// let mut it = PrevPeekable::new(vec![0x34, 0x12, 0xff].into_iter());
+    ///
+    /// assert_eq!(Ok(0x1234), it.read_u16_le());
+    /// assert_eq!(Some(0x12), it.prev());
+    /// assert_eq!(Some(&0x12), it.current());
+    /// assert_eq!(Err(UnexpectedEof { needed: 2, available: 1 }), it.read_u16_le());
+    /// assert_eq!(Some(0xff), it.next());
+    /// ```
+    pub fn read_u16_le(&mut self) -> Result<u16, UnexpectedEof> {
+        self.read_bytes().map(u16::from_le_bytes)
+    }
+
+    /// Reads a `u16` stored in big-endian byte order, like [`read_u16_le`].
+    ///
+    /// [`read_u16_le`]: #method.read_u16_le
+    pub fn read_u16_be(&mut self) -> Result<u16, UnexpectedEof> {
+        self.read_bytes().map(u16::from_be_bytes)
+    }
+
+    /// Reads an `i16` stored in little-endian byte order, like [`read_u16_le`].
+    ///
+    /// [`read_u16_le`]: #method.read_u16_le
+    pub fn read_i16_le(&mut self) -> Result<i16, UnexpectedEof> {
+        self.read_bytes().map(i16::from_le_bytes)
+    }
+
+    /// Reads an `i16` stored in big-endian byte order, like [`read_u16_le`].
+    ///
+    /// [`read_u16_le`]: #method.read_u16_le
+    pub fn read_i16_be(&mut self) -> Result<i16, UnexpectedEof> {
+        self.read_bytes().map(i16::from_be_bytes)
+    }
+
+    /// Reads a `u32` stored in little-endian byte order, like [`read_u16_le`].
+    ///
+    /// [`read_u16_le`]: #method.read_u16_le
+    pub fn read_u32_le(&mut self) -> Result<u32, UnexpectedEof> {
+        self.read_bytes().map(u32::from_le_bytes)
+    }
+
+    /// Reads a `u32` stored in big-endian byte order, like [`read_u16_le`].
+    ///
+    /// [`read_u16_le`]: #method.read_u16_le
+    pub fn read_u32_be(&mut self) -> Result<u32, UnexpectedEof> {
+        self.read_bytes().map(u32::from_be_bytes)
+    }
+
+    /// Reads an `i32` stored in little-endian byte order, like [`read_u16_le`].
+    ///
+    /// [`read_u16_le`]: #method.read_u16_le
+    pub fn read_i32_le(&mut self) -> Result<i32, UnexpectedEof> {
+        self.read_bytes().map(i32::from_le_bytes)
+    }
+
+    /// Reads an `i32` stored in big-endian byte order, like [`read_u16_le`].
+    ///
+    /// [`read_u16_le`]: #method.read_u16_le
+    pub fn read_i32_be(&mut self) -> Result<i32, UnexpectedEof> {
+        self.read_bytes().map(i32::from_be_bytes)
+    }
+
+    /// Reads a `u64` stored in little-endian byte order, like [`read_u16_le`].
+    ///
+    /// [`read_u16_le`]: #method.read_u16_le
+    pub fn read_u64_le(&mut self) -> Result<u64, UnexpectedEof> {
+        self.read_bytes().map(u64::from_le_bytes)
+    }
+
+    /// Reads a `u64` stored in big-endian byte order, like [`read_u16_le`].
+    ///
+    /// [`read_u16_le`]: #method.read_u16_le
+    pub fn read_u64_be(&mut self) -> Result<u64, UnexpectedEof> {
+        self.read_bytes().map(u64::from_be_bytes)
+    }
+
+    /// Reads an `i64` stored in little-endian byte order, like [`read_u16_le`].
+    ///
+    /// [`read_u16_le`]: #method.read_u16_le
+    pub fn read_i64_le(&mut self) -> Result<i64, UnexpectedEof> {
+        self.read_bytes().map(i64::from_le_bytes)
+    }
+
+    /// Reads an `i64` stored in big-endian byte order, like [`read_u16_le`].
+    ///
+    /// [`read_u16_le`]: #method.read_u16_le
+    pub fn read_i64_be(&mut self) -> Result<i64, UnexpectedEof> {
+        self.read_bytes().map(i64::from_be_bytes)
+    }
+
+    /// Consumes the next `N` bytes if there are that many left, or nothing if there aren't, and
+    /// makes the last of them the previous byte.
+    fn read_bytes<const N: usize>(&mut self) -> Result<[u8; N], UnexpectedEof> {
+        if !self.has_lookahead(N) {
+            let available = (0..N).take_while(|&i| self.peek_nth(i).is_some()).count();
+            return Err(UnexpectedEof { needed: N, available });
+        }
+
+        let mut bytes = [0; N];
+        for byte in bytes.iter_mut() {
+            *byte = self
+                .next()
+                .expect("has_lookahead() checked there are enough bytes");
+        }
+        if let Some(&last) = bytes.last() {
+            self.set_prev(Some(last));
+        }
+
+        Ok(bytes)
+    }
+
+    /// Returns the offset of the first of the upcoming bytes that doesn't match `bytes`, buffering
+    /// them without consuming anything.
+    fn mismatch(&mut self, bytes: &[u8]) -> Option<usize> {
+        bytes
+            .iter()
+            .enumerate()
+            .position(|(i, b)| self.peek_nth(i) != Some(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(v: &'static [u8]) -> PrevPeekable<::std::iter::Cloned<::std::slice::Iter<'static, u8>>> {
+        PrevPeekable::new(v.iter().cloned())
+    }
+
+    #[test]
+    fn test_eat_byte() {
+        let mut it = bytes(b"ab");
+
+        assert!(!it.eat_byte(b'b'));
+        assert!(it.eat_byte(b'a'));
+        assert!(it.eat_byte(b'b'));
+        assert_eq!(Some(b'a'), it.prev_byte());
+        assert!(!it.eat_byte(b'b'));
+    }
+
+    #[test]
+    fn test_eat_slice_partial_match() {
+        let mut it = bytes(b"abcd");
+
+        assert!(!it.eat_slice(b"abx"));
+        assert!(!it.eat_slice(b"abcde"));
+        // Nothing was consumed
+        assert_eq!(None, it.prev_byte());
+        assert_eq!(Some(&b'a'), it.peek());
+
+        assert!(it.eat_slice(b"abc"));
+        assert_eq!(Some(b'b'), it.prev_byte());
+        assert!(it.eat_slice(b""));
+        assert_eq!(Some(b'd'), it.next());
+    }
+
+    #[test]
+    fn test_take_until_byte() {
+        let mut it = bytes(b"key=value");
+
+        assert_eq!(b"key".to_vec(), it.take_until_byte(b'='));
+        assert_eq!(Vec::<u8>::new(), it.take_until_byte(b'='));
+        assert!(it.eat_byte(b'='));
+        assert_eq!(b"value".to_vec(), it.take_until_byte(b'='));
+        assert_eq!(None, it.next());
+    }
+
+    #[test]
+    fn test_skip_until_byte() {
+        let mut it = bytes(b"ab;c");
+
+        assert_eq!(2, it.skip_until_byte(b';'));
+        assert_eq!(Some(b'a'), it.prev());
+        assert_eq!(0, it.skip_until_byte(b';'));
+        assert!(it.eat_byte(b';'));
+        assert_eq!(1, it.skip_until_byte(b';'));
+        assert_eq!(None, it.peek());
+        assert_eq!(Some(b';'), it.prev());
+    }
+
+    #[test]
+    fn test_expect_magic() {
+        let mut it = bytes(b"%PDF-1.7");
+
+        assert_eq!(Ok(()), it.expect_magic(b"%PDF-"));
+        assert_eq!(Some(b'F'), it.prev_byte());
+        assert_eq!(Some(b'1'), it.next());
+    }
+
+    #[test]
+    fn test_expect_magic_mismatch() {
+        let mut it = bytes(b"\x7fELX");
+
+        assert_eq!(
+            Err(MagicError {
+                offset: 0,
+                expected: b'E',
+                found: Some(0x7f),
+                after: None,
+            }),
+            it.expect_magic(b"ELF")
+        );
+        assert!(it.eat_byte(0x7f));
+
+        let err = it.expect_magic(b"ELF").unwrap_err();
+        assert_eq!(
+            MagicError {
+                offset: 2,
+                expected: b'F',
+                found: Some(b'X'),
+                after: Some(b'L'),
+            },
+            err
+        );
+        assert_eq!(
+            "expected 0x46 at offset 2 of the magic bytes, found 0x58 after 0x4c",
+            err.to_string()
+        );
+        // Nothing was consumed
+        assert_eq!(Some(b'E'), it.next());
+        assert_eq!(Some(0x7f), it.prev_byte());
+    }
+
+    #[test]
+    fn test_expect_magic_end_of_input() {
+        let mut it = bytes(b"\x7fE");
+
+        assert!(it.eat_byte(0x7f));
+        assert_eq!(
+            "expected 0x4c at offset 1 of the magic bytes, found end of input after 0x45",
+            it.expect_magic(b"EL").unwrap_err().to_string()
+        );
+        assert_eq!(Some(b'E'), it.next());
+    }
+
+    #[test]
+    fn test_read_round_trip() {
+        macro_rules! round_trip {
+            ($value:expr, $to:ident, $read:ident) => {{
+                let value = $value;
+                let mut it = PrevPeekable::new(value.$to().to_vec().into_iter());
+                assert_eq!(Ok(value), it.$read());
+                assert_eq!(None, it.next());
+            }};
+        }
+
+        round_trip!(0xbeefu16, to_le_bytes, read_u16_le);
+        round_trip!(0xbeefu16, to_be_bytes, read_u16_be);
+        round_trip!(-12_345i16, to_le_bytes, read_i16_le);
+        round_trip!(-12_345i16, to_be_bytes, read_i16_be);
+        round_trip!(0xdead_beefu32, to_le_bytes, read_u32_le);
+        round_trip!(0xdead_beefu32, to_be_bytes, read_u32_be);
+        round_trip!(i32::MIN, to_le_bytes, read_i32_le);
+        round_trip!(i32::MIN, to_be_bytes, read_i32_be);
+        round_trip!(0x0123_4567_89ab_cdefu64, to_le_bytes, read_u64_le);
+        round_trip!(0x0123_4567_89ab_cdefu64, to_be_bytes, read_u64_be);
+        round_trip!(-2i64, to_le_bytes, read_i64_le);
+        round_trip!(-2i64, to_be_bytes, read_i64_be);
+    }
+
+    #[test]
+    fn test_read_sequence() {
+        let mut it = bytes(b"\x00\x01\x02\x03\x04\x05");
+
+        assert_eq!(Ok(0x0001), it.read_u16_be());
+        assert_eq!(Ok(0x0504_0302), it.read_u32_le());
+        assert_eq!(Some(&0x05), it.current());
+        assert_eq!(Some(0x05), it.prev_byte());
+        // The next byte comes after the last one of the value
+        assert_eq!(None, it.next());
+        assert_eq!(Some(0x05), it.prev());
+    }
+
+    #[test]
+    fn test_read_prev() {
+        let mut it = bytes(b"\x00\x01\x02\x03\xff");
+
+        assert_eq!(Ok(0x0001_0203), it.read_u32_be());
+        assert_eq!(Some(0x03), it.prev());
+        assert_eq!(Some(0xff), it.next());
+        assert_eq!(Some(0x03), it.prev());
+    }
+
+    #[test]
+    fn test_read_short_input() {
+        let mut it = bytes(b"\x01\x02\x03");
+
+        assert!(it.eat_byte(0x01));
+        assert_eq!(
+            Err(UnexpectedEof {
+                needed: 4,
+                available: 2
+            }),
+            it.read_u32_le()
+        );
+        assert_eq!(
+            "unexpected end of input: needed 8 bytes, but only 2 were left",
+            it.read_i64_be().unwrap_err().to_string()
+        );
+        // Nothing was consumed
+        assert_eq!(Some(&0x01), it.current());
+        assert_eq!(Ok(0x0302), it.read_u16_le());
+        assert_eq!(
+            Err(UnexpectedEof {
+                needed: 2,
+                available: 0
+            }),
+            it.read_u16_le()
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Constructors for reading from an [`mpsc`] channel with lookbehind and peeking.
+//!
+//! [`mpsc`]: https://doc.rust-lang.org/nightly/std/sync/mpsc/index.html
+
+use super::PrevPeekable;
+use std::sync::mpsc::{IntoIter, Receiver, TryRecvError};
+
+impl<T> PrevPeekable<IntoIter<T>>
+where
+    T: Clone,
+{
+    /// Creates a `PrevPeekable` that reads messages from `rx`. `next()` blocks until a message
+    /// arrives, and the iterator ends once every sender has disconnected. `peek()` blocks too, and
+    /// holds on to the message it received until `next()` returns it.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    ///
+    /// let (tx, rx) = mpsc::channel();
+    /// let producer = thread::spawn(move || {
+    ///     for i in 0..3 {
+    ///         tx.send(i).unwrap();
+    ///     }
+    /// });
+    ///
+    /// let mut it = PrevPeekable::from_receiver(rx);
+    /// assert_eq!(Some(&0), it.peek());
+    /// assert_eq!(vec![0, 1, 2], it.by_ref().collect::<Vec<_>>());
+    /// assert_eq!(Some(2), it.prev());
+    ///
+    /// producer.join().unwrap();
+    /// ```
+    pub fn from_receiver(rx: Receiver<T>) -> Self {
+        PrevPeekable::new(rx.into_iter())
+    }
+}
+
+impl<T> PrevPeekable<TryRecvIter<T>>
+where
+    T: Clone,
+{
+    /// Creates a `PrevPeekable` that reads messages from `rx` without blocking, for use in polling
+    /// loops. `next()` returns `Some(Some(message))` when a message was waiting, `Some(None)` when
+    /// there was none yet, and `None` once every sender has disconnected and the channel is empty.
+    ///
+    /// "No message yet" is an element like any other, so it becomes the previous element after it's
+    /// returned, and if `peek()` sees it, `next()` will return it even if a message arrived since,
+    /// unless [`invalidate_peek`] drops it first.
+    ///
+    /// [`invalidate_peek`]: struct.PrevPeekableWith.html#method.invalidate_peek
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    /// use std::sync::mpsc;
+    ///
+    /// let (tx, rx) = mpsc::channel();
+    /// let mut it = PrevPeekable::try_from_receiver(rx);
+    ///
+    /// assert_eq!(Some(None), it.next());
+    ///
+    /// tx.send(1).unwrap();
+    /// assert_eq!(Some(Some(1)), it.next());
+    /// assert_eq!(Some(None), it.prev());
+    ///
+    /// drop(tx);
+    /// assert_eq!(None, it.next());
+    /// ```
+    pub fn try_from_receiver(rx: Receiver<T>) -> Self {
+        PrevPeekable::new(TryRecvIter { rx })
+    }
+}
+
+/// An iterator that polls a [`Receiver`] with [`try_recv`], yielding `None` when no message is
+/// waiting and ending once the channel is disconnected.
+///
+/// This `struct` is created by [`PrevPeekable::try_from_receiver`].
+///
+/// [`Receiver`]: https://doc.rust-lang.org/nightly/std/sync/mpsc/struct.Receiver.html
+/// [`try_recv`]: https://doc.rust-lang.org/nightly/std/sync/mpsc/struct.Receiver.html#method.try_recv
+/// [`PrevPeekable::try_from_receiver`]: struct.PrevPeekableWith.html#method.try_from_receiver
+#[derive(Debug)]
+pub struct TryRecvIter<T> {
+    rx: Receiver<T>,
+}
+
+impl<T> Iterator for TryRecvIter<T> {
+    type Item = Option<T>;
+
+    fn next(&mut self) -> Option<Option<T>> {
+        match self.rx.try_recv() {
+            Ok(message) => Some(Some(message)),
+            Err(TryRecvError::Empty) => Some(None),
+            Err(TryRecvError::Disconnected) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn test_from_receiver() {
+        let (tx, rx) = mpsc::channel();
+        let producer = thread::spawn(move || {
+            for i in 1..4 {
+                tx.send(i).unwrap();
+            }
+        });
+
+        let mut it = PrevPeekable::from_receiver(rx);
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Some(&2), it.peek());
+        assert_eq!(Some(2), it.next());
+        assert_eq!(Some(1), it.prev());
+        assert_eq!(Some(3), it.next());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(3), it.prev());
+
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn test_from_receiver_disconnect_while_peeked() {
+        let (tx, rx) = mpsc::channel();
+        let (peeked_tx, peeked_rx) = mpsc::channel();
+        let producer = thread::spawn(move || {
+            tx.send(1).unwrap();
+            tx.send(2).unwrap();
+            // Disconnect only once the consumer is holding a peeked message
+            peeked_rx.recv().unwrap();
+        });
+
+        let mut it = PrevPeekable::from_receiver(rx);
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Some(&2), it.peek());
+        peeked_tx.send(()).unwrap();
+        producer.join().unwrap();
+
+        assert_eq!(Some(2), it.next());
+        assert_eq!(Some(1), it.prev());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(2), it.prev());
+    }
+
+    #[test]
+    fn test_try_from_receiver() {
+        let (tx, rx) = mpsc::channel();
+        let mut it = PrevPeekable::try_from_receiver(rx);
+
+        assert_eq!(Some(&None), it.peek());
+        tx.send(1).unwrap();
+        // The peeked "no message yet" is returned first
+        assert_eq!(Some(None), it.next());
+        assert_eq!(Some(Some(1)), it.next());
+        assert_eq!(Some(None), it.next());
+        assert_eq!(Some(Some(1)), it.prev());
+    }
+
+    #[test]
+    fn test_try_from_receiver_disconnect_while_peeked() {
+        let (tx, rx) = mpsc::channel();
+        let producer = thread::spawn(move || tx.send(1).unwrap());
+        producer.join().unwrap();
+
+        let mut it = PrevPeekable::try_from_receiver(rx);
+        assert_eq!(Some(&Some(1)), it.peek());
+        assert_eq!(Some(Some(1)), it.next());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(Some(1)), it.prev());
+    }
+}
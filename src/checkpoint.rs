@@ -0,0 +1,260 @@
+//! Checkpoints for backtracking to an earlier position.
+
+use super::PrevPeekableWith;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use storage::PrevStorage;
+
+/// Hands out a different id to every journal, so checkpoints can't be used with another one.
+static NEXT_JOURNAL_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A position of a `PrevPeekable` that it can be rewound to with [`rewind_to`].
+///
+/// This `struct` is created by [`checkpoint`], and can only be used with the iterator that created
+/// it.
+///
+/// [`rewind_to`]: struct.PrevPeekableWith.html#method.rewind_to
+/// [`checkpoint`]: struct.PrevPeekableWith.html#method.checkpoint
+#[derive(Debug)]
+pub struct Checkpoint {
+    journal: usize,
+    index: usize,
+}
+
+/// The error returned by [`rewind_to`] when the checkpoint can't be rewound to.
+///
+/// [`rewind_to`]: struct.PrevPeekableWith.html#method.rewind_to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleCheckpoint;
+
+impl fmt::Display for StaleCheckpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the checkpoint is stale or was created by another iterator")
+    }
+}
+
+impl Error for StaleCheckpoint {}
+
+/// Everything needed to rewind to the checkpoints of an iterator.
+#[derive(Debug)]
+pub(crate) struct Journal<T, S> {
+    id: usize,
+    /// Every element returned since the first checkpoint
+    returned: Vec<T>,
+    snapshots: Vec<Snapshot<T, S>>,
+}
+
+impl<T, S> Journal<T, S> {
+    /// Remembers that `item` was returned.
+    pub(crate) fn record(&mut self, item: T) {
+        self.returned.push(item);
+    }
+
+    /// Returns how many of the checkpoints created since the journal was started haven't been
+    /// passed to `rewind_to()` yet.
+    pub(crate) fn checkpoints(&self) -> usize {
+        self.snapshots.iter().filter(|snapshot| !snapshot.rewound).count()
+    }
+}
+
+/// The state of an iterator when a checkpoint was created.
+#[derive(Debug, Clone)]
+struct Snapshot<T, S> {
+    /// How many elements had been returned since the first checkpoint
+    offset: usize,
+    current: Option<T>,
+    storage: S,
+    finished: bool,
+    /// Whether its checkpoint has been passed to `rewind_to()`, which takes it
+    rewound: bool,
+}
+
+impl<I, S> PrevPeekableWith<I, S>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+    S: PrevStorage<I::Item> + Clone,
+{
+    /// Returns a [`Checkpoint`] of the current position, which the iterator can be rewound to with
+    /// [`rewind_to`] to try something else, such as another branch of a parser.
+    ///
+    /// From the first checkpoint on, a clone of every element returned is kept so they can be
+    /// returned again, until [`clear_checkpoints`] is called or the source is replaced.
+    ///
+    /// [`Checkpoint`]: struct.Checkpoint.html
+    /// [`rewind_to`]: #method.rewind_to
+    /// [`clear_checkpoints`]: #method.clear_checkpoints
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new("abc".chars());
+    ///
+    /// assert_eq!(Some('a'), it.next());
+    /// let start = it.checkpoint();
+    /// assert_eq!(Some('b'), it.next());
+    /// assert_eq!(Some('c'), it.next());
+    ///
+    /// it.rewind_to(start).unwrap();
+    /// assert_eq!(Some(&'a'), it.current());
+    /// assert_eq!(Some('b'), it.next());
+    /// assert_eq!(Some('a'), it.prev());
+    /// ```
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        let journal = self.journal.get_or_insert_with(|| Journal {
+            id: NEXT_JOURNAL_ID.fetch_add(1, Ordering::Relaxed),
+            returned: Vec::new(),
+            snapshots: Vec::new(),
+        });
+
+        // An element swapped into a caller's buffer by next_reuse() isn't ours to restore
+        let current = if self.lent { None } else { self.current.clone() };
+        journal.snapshots.push(Snapshot {
+            offset: journal.returned.len(),
+            current,
+            storage: self.storage.clone(),
+            finished: self.finished,
+            rewound: false,
+        });
+
+        Checkpoint {
+            journal: journal.id,
+            index: journal.snapshots.len() - 1,
+        }
+    }
+
+    /// Rewinds the iterator to `checkpoint`, so the elements returned since are returned again,
+    /// and [`prev`] and [`current`] are what they were when it was created.
+    ///
+    /// Returns [`StaleCheckpoint`] if `checkpoint` was created by another iterator, before
+    /// [`clear_checkpoints`] was called or the source was replaced, or after the position the
+    /// iterator was rewound to. Rewinding never changes a buffer passed to [`next_reuse`].
+    ///
+    /// [`prev`]: #method.prev
+    /// [`current`]: #method.current
+    /// [`StaleCheckpoint`]: struct.StaleCheckpoint.html
+    /// [`clear_checkpoints`]: #method.clear_checkpoints
+    /// [`next_reuse`]: #method.next_reuse
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{PrevPeekable, StaleCheckpoint};
+    ///
+    /// let mut it = PrevPeekable::new(1..10);
+    /// let mut other = PrevPeekable::new(1..10);
+    ///
+    /// let start = it.checkpoint();
+    /// assert_eq!(Some(1), it.next());
+    /// assert_eq!(Err(StaleCheckpoint), other.rewind_to(start));
+    /// ```
+    pub fn rewind_to(&mut self, checkpoint: Checkpoint) -> Result<(), StaleCheckpoint> {
+        let journal = match self.journal {
+            Some(ref mut journal) if journal.id == checkpoint.journal => journal,
+            _ => return Err(StaleCheckpoint),
+        };
+
+        let snapshot = &mut journal.snapshots[checkpoint.index];
+        snapshot.rewound = true;
+        let snapshot = snapshot.clone();
+        if snapshot.offset > journal.returned.len() {
+            return Err(StaleCheckpoint);
+        }
+
+        self.source.replay(journal.returned.drain(snapshot.offset..));
+        self.current = snapshot.current;
+        self.storage = snapshot.storage;
+        self.finished = snapshot.finished;
+        self.lent = false;
+
+        Ok(())
+    }
+
+    /// Makes every checkpoint stale, and drops the elements kept to rewind to them.
+    pub fn clear_checkpoints(&mut self) {
+        self.journal = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StaleCheckpoint;
+    use PrevPeekable;
+
+    #[test]
+    fn test_rewind_to() {
+        let v = [1, 2, 3, 4];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(Some(&2), it.next());
+        let cp = it.checkpoint();
+        assert_eq!(Some(&3), it.next());
+        assert_eq!(Some(&&4), it.peek());
+        assert_eq!(Some(&4), it.next());
+        assert_eq!(None, it.next());
+
+        assert_eq!(Ok(()), it.rewind_to(cp));
+        assert_eq!(Some(&1), it.prev());
+        assert_eq!(Some(&&2), it.current());
+        assert_eq!(Some(&&3), it.peek());
+        assert_eq!(vec![&3, &4], it.by_ref().collect::<Vec<_>>());
+        assert_eq!(Some(&4), it.prev());
+    }
+
+    #[test]
+    fn test_rewind_to_nested() {
+        let mut it = PrevPeekable::new("abcd".chars());
+
+        let outer = it.checkpoint();
+        assert_eq!(Some('a'), it.next());
+        let inner = it.checkpoint();
+        assert_eq!(Some('b'), it.next());
+        assert_eq!(Some('c'), it.next());
+
+        assert_eq!(Ok(()), it.rewind_to(inner));
+        assert_eq!(Some('b'), it.next());
+        assert_eq!(Ok(()), it.rewind_to(outer));
+        assert_eq!(None, it.current());
+        assert_eq!(None, it.prev());
+        assert_eq!(Some('a'), it.next());
+    }
+
+    #[test]
+    fn test_rewind_to_keeps_lookahead() {
+        let mut it = PrevPeekable::new(1..6);
+
+        let cp = it.checkpoint();
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Some(&4), it.peek_nth(2));
+        assert_eq!(Ok(()), it.rewind_to(cp));
+        assert_eq!(vec![1, 2, 3, 4, 5], it.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_rewind_to_stale() {
+        let mut it = PrevPeekable::new(1..10);
+
+        // A checkpoint ahead of the position that was rewound to
+        let start = it.checkpoint();
+        it.next();
+        let ahead = it.checkpoint();
+        assert_eq!(Ok(()), it.rewind_to(start));
+        assert_eq!(Err(StaleCheckpoint), it.rewind_to(ahead));
+
+        let cp = it.checkpoint();
+        it.clear_checkpoints();
+        assert_eq!(Err(StaleCheckpoint), it.rewind_to(cp));
+
+        let cp = it.checkpoint();
+        let _ = it.replace_source(20..30);
+        assert_eq!(Err(StaleCheckpoint), it.rewind_to(cp));
+    }
+}
@@ -0,0 +1,787 @@
+//! Adapters that produce or operate on collections.
+
+use super::PrevPeekable;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasher, Hash};
+
+impl<I, T, H> PrevPeekable<I>
+where
+    I: Iterator<Item = HashSet<T, H>>,
+    T: Eq + Hash + Clone,
+    H: BuildHasher + Clone,
+{
+    /// Tracks how a set evolves over time by yielding `(added, removed)` for each consecutive pair
+    /// of sets: the elements of the current set missing from the previous one, and the elements of
+    /// the previous set missing from the current one.
+    ///
+    /// Nothing is yielded for the first set. The order of the elements in `added` and `removed` is
+    /// the iteration order of the sets, so it is unspecified.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    /// use std::collections::HashSet;
+    ///
+    /// let sets: Vec<HashSet<_>> = vec![[1, 2].iter().cloned().collect(), [2, 3].iter().cloned().collect()];
+    /// let deltas: Vec<_> = PrevPeekable::new(sets.into_iter()).set_deltas().collect();
+    ///
+    /// assert_eq!(vec![(vec![3], vec![1])], deltas);
+    /// ```
+    pub fn set_deltas(mut self) -> impl Iterator<Item = (Vec<T>, Vec<T>)> {
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+
+            if let Some(prev) = self.peek_prev() {
+                let added = cur.difference(prev).cloned().collect();
+                let removed = prev.difference(&cur).cloned().collect();
+
+                return Some((added, removed));
+            }
+        })
+    }
+}
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Consumes the iterator and returns the most frequent consecutive `(prev, current)` pair, or
+    /// `None` if there are fewer than two elements. If several pairs are equally frequent, the one
+    /// that occurred first wins.
+    ///
+    /// Memory use is bounded by the number of distinct pairs rather than the length of the stream.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new("ababac".chars());
+    ///
+    /// assert_eq!(Some(('a', 'b')), it.most_common_transition());
+    /// ```
+    pub fn most_common_transition(&mut self) -> Option<(I::Item, I::Item)>
+    where
+        I::Item: Eq + Hash,
+    {
+        // Maps each pair to how often it occurred and when it first occurred
+        let mut tally: HashMap<(I::Item, I::Item), (usize, usize)> = HashMap::new();
+        let mut index = 0;
+
+        while let Some(cur) = self.next() {
+            if let Some(prev) = self.prev() {
+                tally.entry((prev, cur)).or_insert((0, index)).0 += 1;
+                index += 1;
+            }
+        }
+
+        tally
+            .into_iter()
+            .max_by(|&(_, (a_count, a_first)), &(_, (b_count, b_first))| {
+                a_count.cmp(&b_count).then(b_first.cmp(&a_first))
+            })
+            .map(|(pair, _)| pair)
+    }
+
+    /// Yields each element along with how many times it has occurred so far, counting itself, so
+    /// the first occurrence of a value is 1. Unlike [`with_run_position`], the count doesn't start
+    /// over when the value changes, since it's kept for every distinct value seen.
+    ///
+    /// Memory use is bounded by the number of distinct elements rather than the length of the
+    /// stream.
+    ///
+    /// [`with_run_position`]: #method.with_run_position
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec!["GET /", "GET /a", "GET /", "GET /"];
+    /// let counts: Vec<_> = PrevPeekable::new(v.into_iter()).occurrence_index().collect();
+    ///
+    /// assert_eq!(vec![("GET /", 1), ("GET /a", 1), ("GET /", 2), ("GET /", 3)], counts);
+    /// ```
+    pub fn occurrence_index(mut self) -> impl Iterator<Item = (I::Item, usize)>
+    where
+        I::Item: Eq + Hash,
+    {
+        let mut counts: HashMap<I::Item, usize> = HashMap::new();
+
+        ::std::iter::from_fn(move || {
+            let cur = self.next()?;
+            let count = counts.entry(cur.clone()).or_insert(0);
+            *count += 1;
+
+            Some((cur, *count))
+        })
+    }
+
+    /// Yields each element along with how many places back the same value last occurred, or
+    /// `None` if it hasn't occurred before. Adjacent equal elements are 1 apart.
+    ///
+    /// Memory use is bounded by the number of distinct elements rather than the length of the
+    /// stream.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec!['a', 'b', 'c', 'a', 'a'];
+    /// let distances: Vec<_> = PrevPeekable::new(v.into_iter()).distance_since_last_equal().collect();
+    ///
+    /// assert_eq!(vec![('a', None), ('b', None), ('c', None), ('a', Some(3)), ('a', Some(1))], distances);
+    /// ```
+    pub fn distance_since_last_equal(mut self) -> impl Iterator<Item = (I::Item, Option<usize>)>
+    where
+        I::Item: Eq + Hash,
+    {
+        let mut last_seen: HashMap<I::Item, usize> = HashMap::new();
+        let mut index = 0;
+
+        ::std::iter::from_fn(move || {
+            let cur = self.next()?;
+            let distance = last_seen.insert(cur.clone(), index).map(|last| index - last);
+            index += 1;
+
+            Some((cur, distance))
+        })
+    }
+
+    /// Consumes the iterator and maps every element except the first to the element before it,
+    /// giving an ordered predecessor lookup. If an element occurs more than once, it maps to the
+    /// predecessor of its last occurrence.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![3, 1, 2];
+    /// let map = PrevPeekable::new(v.into_iter()).collect_prev_btreemap();
+    ///
+    /// assert_eq!(vec![(&1, &3), (&2, &1)], map.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn collect_prev_btreemap(mut self) -> BTreeMap<I::Item, I::Item>
+    where
+        I::Item: Ord,
+    {
+        let mut map = BTreeMap::new();
+
+        while let Some(cur) = self.next() {
+            if let Some(prev) = self.prev() {
+                map.insert(cur, prev);
+            }
+        }
+
+        map
+    }
+
+    /// Consumes every remaining element, including any that were peeked at, and returns them in a
+    /// `Vec`. The previous and current elements are updated along the way, so afterwards
+    /// [`current`] is the last element and [`prev`] the one before it, just as if `next()` had
+    /// been called until it returned the last element.
+    ///
+    /// This differs from `by_ref().collect()`, which also calls `next()` once more to find the
+    /// end, and moves the last element into `prev` when it does.
+    ///
+    /// [`current`]: struct.PrevPeekableWith.html#method.current
+    /// [`prev`]: struct.PrevPeekableWith.html#method.prev
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new(vec![1, 2, 3, 4].into_iter());
+    ///
+    /// it.next();
+    /// assert_eq!(vec![2, 3, 4], it.collect_remaining());
+    /// assert_eq!(Some(3), it.prev());
+    /// assert_eq!(Some(&4), it.current());
+    /// ```
+    pub fn collect_remaining(&mut self) -> Vec<I::Item> {
+        let mut remaining = Vec::new();
+
+        while self.peek().is_some() {
+            remaining.extend(self.next());
+        }
+
+        remaining
+    }
+
+    /// Consumes the iterator and returns its last `n` elements, in their original order. Only `n`
+    /// elements are kept at a time, so memory use doesn't depend on the length of the iterator. If
+    /// there are fewer than `n` elements, all of them are returned. The whole iterator is consumed
+    /// even when `n` is 0.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    /// use std::io::{BufRead, Cursor};
+    ///
+    /// // tail -n 2
+    /// let input = Cursor::new("first\nsecond\nthird\nfourth\n");
+    /// let lines = input.lines().map(|line| line.expect("reading from memory can't fail"));
+    /// let tail = PrevPeekable::new(lines).take_last_n(2);
+    ///
+    /// assert_eq!(vec!["third", "fourth"], Vec::from(tail));
+    /// ```
+    pub fn take_last_n(mut self, n: usize) -> VecDeque<I::Item> {
+        self.last_n(n)
+    }
+
+    /// Consumes the rest of the iterator and returns its last `n` elements, in their original
+    /// order, or all of them if there are fewer than `n`. This is like [`take_last_n`], but it
+    /// borrows the iterator, so [`prev`] can still be asked for the last element afterwards, for
+    /// messages like "unexpected end of input after ...".
+    ///
+    /// [`take_last_n`]: #method.take_last_n
+    /// [`prev`]: struct.PrevPeekableWith.html#method.prev
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new("let x = (1 +".split(' '));
+    ///
+    /// assert_eq!(vec!["(1", "+"], it.tail(2));
+    /// assert_eq!(Some("+"), it.prev());
+    /// ```
+    pub fn tail(&mut self, n: usize) -> Vec<I::Item> {
+        self.last_n(n).into()
+    }
+
+    /// Consumes the rest of the iterator and returns its last `n` elements. The buffer grows as
+    /// elements are read, so a large `n` doesn't allocate anything up front.
+    fn last_n(&mut self, n: usize) -> VecDeque<I::Item> {
+        let mut last = VecDeque::new();
+
+        for cur in self.by_ref() {
+            if last.len() == n {
+                last.pop_front();
+            }
+            if n != 0 {
+                last.push_back(cur);
+            }
+        }
+
+        last
+    }
+
+    /// Consumes the iterator and returns every element along with its index and the index of the
+    /// element before it, which is `None` for the first element. This links each position to its
+    /// predecessor, for rebuilding a path or graph from the stream.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec!['a', 'b', 'c'];
+    /// let linked = PrevPeekable::new(v.into_iter()).into_linked();
+    ///
+    /// assert_eq!(vec![(0, 'a', None), (1, 'b', Some(0)), (2, 'c', Some(1))], linked);
+    /// ```
+    pub fn into_linked(self) -> Vec<(usize, I::Item, Option<usize>)> {
+        let mut linked: Vec<(usize, I::Item, Option<usize>)> = Vec::new();
+
+        for cur in self {
+            let index = linked.len();
+            let prev = linked.last().map(|&(prev, _, _)| prev);
+            linked.push((index, cur, prev));
+        }
+
+        linked
+    }
+
+    /// Consumes the iterator and returns every element it can still get at in a [`VecDeque`],
+    /// along with the index of the element [`peek`] would have returned, so the rest of the work
+    /// can carry on from the same position with random access.
+    ///
+    /// Elements that were already returned can only be recovered as far as the iterator keeps
+    /// them, which is the previous and the current element. So the deque starts with [`prev`] and
+    /// [`current`], whichever there are, followed by the remaining elements, and the index is 0, 1
+    /// or 2. Once the end has been reached it's the length of the deque.
+    ///
+    /// [`VecDeque`]: https://doc.rust-lang.org/nightly/std/collections/struct.VecDeque.html
+    /// [`peek`]: struct.PrevPeekableWith.html#method.peek
+    /// [`prev`]: struct.PrevPeekableWith.html#method.prev
+    /// [`current`]: struct.PrevPeekableWith.html#method.current
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 2, 3, 4, 5];
+    /// let mut it = PrevPeekable::new(v.into_iter());
+    ///
+    /// it.next();
+    /// it.next();
+    /// it.next();
+    /// let (all, cursor) = it.into_vecdeque_with_cursor();
+    ///
+    /// // 1 is gone, but 2 and 3 were kept
+    /// assert_eq!(vec![2, 3, 4, 5], Vec::from(all.clone()));
+    /// assert_eq!(4, all[cursor]);
+    /// assert_eq!(3, all[cursor - 1]);
+    /// ```
+    pub fn into_vecdeque_with_cursor(mut self) -> (VecDeque<I::Item>, usize) {
+        let mut deque = VecDeque::new();
+        deque.extend(self.prev());
+        // An element swapped into a caller's buffer by next_reuse() isn't in current
+        deque.extend(self.current.take());
+        let cursor = deque.len();
+
+        deque.extend(self);
+
+        (deque, cursor)
+    }
+
+    /// Yields every element except the last `n`, such as to drop trailing summary rows or a
+    /// checksum. Each element is yielded once `n` more elements have been read after it, so only `n`
+    /// elements are kept at a time, and nothing is yielded if there are `n` or fewer.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let rows = vec!["a,1", "b,2", "total,3"];
+    /// let data: Vec<_> = PrevPeekable::new(rows.into_iter()).skip_last(1).collect();
+    ///
+    /// assert_eq!(vec!["a,1", "b,2"], data);
+    /// ```
+    pub fn skip_last(self, n: usize) -> SkipLast<I> {
+        SkipLast {
+            iter: self,
+            held: VecDeque::new(),
+            n,
+        }
+    }
+}
+
+/// An iterator that yields every element of a `PrevPeekable` except the last few.
+///
+/// This `struct` is created by [`skip_last`].
+///
+/// [`skip_last`]: struct.PrevPeekableWith.html#method.skip_last
+#[derive(Debug)]
+pub struct SkipLast<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    iter: PrevPeekable<I>,
+    /// The last `n` elements that were read, which can't be yielded until more are read
+    held: VecDeque<I::Item>,
+    n: usize,
+}
+
+impl<I> Iterator for SkipLast<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        while self.held.len() < self.n {
+            let cur = self.iter.next()?;
+            self.held.push_back(cur);
+        }
+
+        let cur = self.iter.next()?;
+        if self.n == 0 {
+            return Some(cur);
+        }
+        self.held.push_back(cur);
+
+        self.held.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Everything still in the source, plus the elements held back
+        let (lower, upper) = self.iter.source.iterator.size_hint();
+        let unread = self.iter.source.buffer.len() + self.held.len();
+        let lower = lower.saturating_add(unread).saturating_sub(self.n);
+        let upper = upper
+            .and_then(|upper| upper.checked_add(unread))
+            .map(|upper| upper.saturating_sub(self.n));
+
+        (lower, upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::RecordingIterator;
+
+    fn set(elements: &[i32]) -> HashSet<i32> {
+        elements.iter().cloned().collect()
+    }
+
+    fn sorted(mut v: Vec<i32>) -> Vec<i32> {
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn test_set_deltas() {
+        let sets = vec![set(&[1, 2]), set(&[2, 3])];
+        let deltas: Vec<_> = PrevPeekable::new(sets.into_iter()).set_deltas().collect();
+
+        assert_eq!(vec![(vec![3], vec![1])], deltas);
+    }
+
+    #[test]
+    fn test_set_deltas_several_steps() {
+        let sets = vec![set(&[]), set(&[1, 2, 3]), set(&[1, 2, 3]), set(&[4])];
+        let deltas: Vec<_> = PrevPeekable::new(sets.into_iter())
+            .set_deltas()
+            .map(|(added, removed)| (sorted(added), sorted(removed)))
+            .collect();
+
+        assert_eq!(
+            vec![
+                (vec![1, 2, 3], vec![]),
+                (vec![], vec![]),
+                (vec![4], vec![1, 2, 3]),
+            ],
+            deltas
+        );
+    }
+
+    #[test]
+    fn test_set_deltas_single_set() {
+        let mut deltas = PrevPeekable::new(vec![set(&[1])].into_iter()).set_deltas();
+
+        assert_eq!(None, deltas.next());
+    }
+
+    #[test]
+    fn test_most_common_transition() {
+        let v = ['a', 'b', 'a', 'b', 'a', 'c'];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(Some(('a', 'b')), it.most_common_transition());
+        assert_eq!(None, it.next());
+    }
+
+    #[test]
+    fn test_most_common_transition_tie_keeps_first() {
+        let v = [3, 4, 1, 2, 1, 2, 3, 4];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!(Some((&3, &4)), it.most_common_transition());
+    }
+
+    #[test]
+    fn test_most_common_transition_short() {
+        let v = [1];
+
+        assert_eq!(None, PrevPeekable::new(v.iter()).most_common_transition());
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).most_common_transition());
+    }
+
+    #[test]
+    fn test_occurrence_index() {
+        let v = [1, 2, 1, 1, 2];
+        let counts: Vec<_> = PrevPeekable::new(v.iter().cloned()).occurrence_index().collect();
+
+        assert_eq!(vec![(1, 1), (2, 1), (1, 2), (1, 3), (2, 2)], counts);
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).occurrence_index().next());
+    }
+
+    #[test]
+    fn test_occurrence_index_partially_consumed() {
+        let mut it = PrevPeekable::new("abca".chars());
+        it.next();
+        let counts: Vec<_> = it.occurrence_index().collect();
+
+        // Elements consumed before the adapter was created aren't counted
+        assert_eq!(vec![('b', 1), ('c', 1), ('a', 1)], counts);
+    }
+
+    #[test]
+    fn test_distance_since_last_equal() {
+        let v = [1, 2, 1, 1, 2];
+        let distances: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .distance_since_last_equal()
+            .collect();
+
+        assert_eq!(
+            vec![(1, None), (2, None), (1, Some(2)), (1, Some(1)), (2, Some(3))],
+            distances
+        );
+        assert_eq!(
+            None,
+            PrevPeekable::new(v[..0].iter())
+                .distance_since_last_equal()
+                .next()
+        );
+    }
+
+    #[test]
+    fn test_distance_since_last_equal_distinct() {
+        let distances: Vec<_> = PrevPeekable::new("abc".chars())
+            .distance_since_last_equal()
+            .collect();
+
+        assert_eq!(vec![('a', None), ('b', None), ('c', None)], distances);
+    }
+
+    #[test]
+    fn test_collect_prev_btreemap() {
+        let v = [3, 1, 2];
+        let map = PrevPeekable::new(v.iter().cloned()).collect_prev_btreemap();
+
+        assert_eq!(vec![(1, 3), (2, 1)], map.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_collect_prev_btreemap_repeated_element() {
+        let v = ['a', 'b', 'c', 'b'];
+        let map = PrevPeekable::new(v.iter().cloned()).collect_prev_btreemap();
+
+        assert_eq!(2, map.len());
+        assert_eq!(Some(&'c'), map.get(&'b'));
+        assert_eq!(Some(&'b'), map.get(&'c'));
+        assert_eq!(None, map.get(&'a'));
+    }
+
+    #[test]
+    fn test_collect_remaining() {
+        let v = [1, 2, 3, 4, 5];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Some(&3), it.peek_nth(1));
+        assert_eq!(vec![2, 3, 4, 5], it.collect_remaining());
+        assert_eq!(Some(4), it.prev());
+        assert_eq!(Some(&5), it.current());
+
+        assert!(it.collect_remaining().is_empty());
+        assert_eq!(Some(4), it.prev());
+        // Reaching the end from here works as usual
+        assert_eq!(None, it.next());
+        assert_eq!(Some(5), it.prev());
+    }
+
+    #[test]
+    fn test_collect_remaining_short() {
+        let mut it = PrevPeekable::new([7].iter().cloned());
+
+        assert_eq!(vec![7], it.collect_remaining());
+        assert_eq!(None, it.prev());
+        assert_eq!(Some(&7), it.current());
+        assert!(PrevPeekable::new([0; 0].iter()).collect_remaining().is_empty());
+    }
+
+    #[test]
+    fn test_take_last_n() {
+        let v = [1, 2, 3, 4, 5];
+
+        assert_eq!(
+            vec![&3, &4, &5],
+            Vec::from(PrevPeekable::new(v.iter()).take_last_n(3))
+        );
+        assert_eq!(vec![&5], Vec::from(PrevPeekable::new(v.iter()).take_last_n(1)));
+    }
+
+    #[test]
+    fn test_take_last_n_short() {
+        let v = [1, 2];
+
+        assert_eq!(
+            vec![&1, &2],
+            Vec::from(PrevPeekable::new(v.iter()).take_last_n(5))
+        );
+        assert!(PrevPeekable::new(v.iter()).take_last_n(0).is_empty());
+        assert!(PrevPeekable::new(v[..0].iter()).take_last_n(3).is_empty());
+    }
+
+    #[test]
+    fn test_take_last_n_bounded_memory() {
+        let last = PrevPeekable::new(0..100_000).take_last_n(4);
+        assert!(last.capacity() < 100);
+
+        assert_eq!(vec![99_996, 99_997, 99_998, 99_999], Vec::from(last));
+        assert_eq!(
+            vec![0, 1],
+            Vec::from(PrevPeekable::new(0..2).take_last_n(usize::MAX))
+        );
+    }
+
+    #[test]
+    fn test_take_last_n_zero_consumes() {
+        let source = RecordingIterator::new(1..4);
+        let recording = source.recording();
+
+        assert!(PrevPeekable::new(source).take_last_n(0).is_empty());
+        recording.assert_yielded(&[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_tail() {
+        let v = [1, 2, 3, 4, 5];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(Some(1), it.next());
+        assert_eq!(vec![4, 5], it.tail(2));
+        assert_eq!(None, it.next());
+        assert_eq!(Some(5), it.prev());
+        assert!(it.tail(2).is_empty());
+    }
+
+    #[test]
+    fn test_tail_short() {
+        assert_eq!(vec![1], PrevPeekable::new([1].iter().cloned()).tail(3));
+        assert!(PrevPeekable::new([1, 2].iter()).tail(0).is_empty());
+        assert!(PrevPeekable::new([0; 0].iter()).tail(3).is_empty());
+        assert_eq!(
+            vec![1, 2],
+            PrevPeekable::new([1, 2].iter().cloned()).tail(usize::MAX)
+        );
+    }
+
+    #[test]
+    fn test_into_linked() {
+        let v = ['a', 'b', 'c'];
+        let linked = PrevPeekable::new(v.iter().cloned()).into_linked();
+
+        assert_eq!(vec![(0, 'a', None), (1, 'b', Some(0)), (2, 'c', Some(1))], linked);
+        // Following the links from the end visits every element in reverse
+        let mut path = Vec::new();
+        let mut at = linked.last().map(|&(index, _, _)| index);
+        while let Some(index) = at {
+            path.push(linked[index].1);
+            at = linked[index].2;
+        }
+        assert_eq!(vec!['c', 'b', 'a'], path);
+        assert!(PrevPeekable::new(v[..0].iter()).into_linked().is_empty());
+    }
+
+    #[test]
+    fn test_into_vecdeque_with_cursor() {
+        for steps in 0..7usize {
+            let mut it = PrevPeekable::new(1..6);
+            for _ in 0..steps {
+                it.next();
+            }
+            let peeked = it.peek().cloned();
+            let prev = it.prev();
+            let current = it.current().cloned();
+
+            let (all, cursor) = it.into_vecdeque_with_cursor();
+            assert_eq!(peeked, all.get(cursor).cloned(), "after {} steps", steps);
+            assert_eq!(current.or(prev), cursor.checked_sub(1).map(|i| all[i]));
+            // Only the elements before prev are lost
+            assert_eq!(Some(&5), all.back());
+            assert_eq!(all.len(), 5 - steps.saturating_sub(2).min(4));
+        }
+    }
+
+    #[test]
+    fn test_into_vecdeque_with_cursor_lookahead() {
+        let mut it = PrevPeekable::new("abcde".chars());
+
+        it.next();
+        assert_eq!(Some(&'d'), it.peek_nth(2));
+        let (all, cursor) = it.into_vecdeque_with_cursor();
+
+        assert_eq!(vec!['a', 'b', 'c', 'd', 'e'], Vec::from(all));
+        assert_eq!(1, cursor);
+    }
+
+    #[test]
+    fn test_into_vecdeque_with_cursor_empty() {
+        let (all, cursor) = PrevPeekable::new(0..0).into_vecdeque_with_cursor();
+
+        assert!(all.is_empty());
+        assert_eq!(0, cursor);
+    }
+
+    #[test]
+    fn test_skip_last() {
+        let v = [1, 2, 3, 4, 5];
+
+        for n in 0..7 {
+            let kept: Vec<_> = PrevPeekable::new(v.iter().cloned()).skip_last(n).collect();
+            assert_eq!(v[..v.len().saturating_sub(n)].to_vec(), kept, "n = {}", n);
+        }
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).skip_last(0).next());
+        assert_eq!(None, PrevPeekable::new(v.iter()).skip_last(usize::MAX).next());
+    }
+
+    #[test]
+    fn test_skip_last_delay() {
+        let source = RecordingIterator::new(1..10);
+        let recording = source.recording();
+        let mut kept = PrevPeekable::new(source).skip_last(3);
+
+        assert_eq!(Some(1), kept.next());
+        recording.assert_next_calls(4);
+        assert_eq!(Some(2), kept.next());
+        recording.assert_next_calls(5);
+    }
+
+    #[test]
+    fn test_skip_last_size_hint() {
+        let mut kept = PrevPeekable::new(1..10).skip_last(3);
+        assert_eq!((6, Some(6)), kept.size_hint());
+
+        kept.next();
+        assert_eq!((5, Some(5)), kept.size_hint());
+        assert_eq!(5, kept.by_ref().count());
+        assert_eq!((0, Some(0)), kept.size_hint());
+
+        assert_eq!((0, Some(0)), PrevPeekable::new(1..3).skip_last(5).size_hint());
+        assert_eq!(
+            (usize::MAX - 5, None),
+            PrevPeekable::new(1..).skip_last(5).size_hint()
+        );
+    }
+
+    #[test]
+    fn test_skip_last_prev() {
+        let mut it = PrevPeekable::new(PrevPeekable::new("abcd".chars()).skip_last(1));
+
+        assert_eq!(Some('a'), it.next());
+        assert_eq!(Some('b'), it.next());
+        assert_eq!(Some('a'), it.prev());
+        assert_eq!(Some(&'c'), it.peek());
+        assert_eq!(Some('c'), it.next());
+        assert_eq!(None, it.next());
+        assert_eq!(Some('c'), it.prev());
+    }
+}
@@ -0,0 +1,247 @@
+//! A lean `PrevPeekable` for `Copy` elements.
+
+use std::iter::Peekable;
+
+/// An iterator with `prev()`, `peek_prev()`, and `peek()` functions, like [`PrevPeekable`], for
+/// elements that implement [`Copy`] such as bytes, `char`s and small numbers.
+///
+/// The previous and current elements are kept as plain copies, so there are no clones and no
+/// `Clone` bounds, and `prev()` and `peek_prev()` both return the previous element by value. It
+/// doesn't have [`PrevPeekable`]'s adapters or its lookahead beyond the next element.
+///
+/// This `struct` is created by passing an [`Iterator`] whose `Item` implements [`Copy`] to the
+/// [`new`] function.
+///
+/// [`PrevPeekable`]: type.PrevPeekable.html
+/// [`Copy`]: https://doc.rust-lang.org/nightly/core/marker/trait.Copy.html
+/// [`Iterator`]: https://doc.rust-lang.org/nightly/core/iter/iterator/trait.Iterator.html
+/// [`new`]: struct.PrevCopyPeekable.html#method.new
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use prev_iter::PrevCopyPeekable;
+///
+/// let mut it = PrevCopyPeekable::new(b"ab".iter().copied());
+///
+/// assert_eq!(Some(b'a'), it.next());
+/// assert_eq!(Some(&b'b'), it.peek());
+/// assert_eq!(Some(b'b'), it.next());
+/// assert_eq!(Some(b'a'), it.prev());
+/// ```
+#[derive(Debug)]
+pub struct PrevCopyPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::marker::Copy,
+{
+    /// Iterator that `PrevCopyPeekable` wraps
+    iterator: Peekable<I>,
+    /// The element before the one we just returned. Initially it's `None`.
+    prev: Option<I::Item>,
+    /// The current element we just returned.
+    current: Option<I::Item>,
+    /// Keeps track of whether the iterator has reached the end or not
+    finished: bool,
+}
+
+impl<I> PrevCopyPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::marker::Copy,
+{
+    /// Creates a new `PrevCopyPeekable`. It takes an [`Iterator`] whose `Item` implements [`Copy`].
+    ///
+    /// [`Iterator`]: https://doc.rust-lang.org/nightly/core/iter/iterator/trait.Iterator.html
+    /// [`Copy`]: https://doc.rust-lang.org/nightly/core/marker/trait.Copy.html
+    pub fn new(iterator: I) -> Self {
+        PrevCopyPeekable {
+            iterator: iterator.peekable(),
+            prev: None,
+            current: None,
+            finished: false,
+        }
+    }
+
+    /// Returns a reference to the next element without advancing the iterator. Like `next()`, it
+    /// returns `None` if there are no elements left.
+    #[inline]
+    pub fn peek(&mut self) -> Option<&I::Item> {
+        self.iterator.peek()
+    }
+
+    /// Returns the previous element, or `None` if there isn't one yet.
+    #[inline]
+    pub fn prev(&self) -> Option<I::Item> {
+        self.prev
+    }
+
+    /// Returns the previous element, or `None` if there isn't one yet. This is the same as
+    /// [`prev`], and exists so code written against [`PrevPeekable`] keeps working.
+    ///
+    /// [`prev`]: #method.prev
+    /// [`PrevPeekable`]: type.PrevPeekable.html
+    #[inline]
+    pub fn peek_prev(&self) -> Option<I::Item> {
+        self.prev
+    }
+
+    /// Returns a reference to the element returned by the last call to `next()`, or `None` before
+    /// the first call and after the end has been reached.
+    #[inline]
+    pub fn current(&self) -> Option<&I::Item> {
+        self.current.as_ref()
+    }
+
+    /// Replaces the underlying iterator with `new_iter` and returns the old one, including any
+    /// element it had peeked at. The previous and current elements are kept, just like
+    /// [`PrevPeekable::replace_source`].
+    ///
+    /// [`PrevPeekable::replace_source`]: struct.PrevPeekableWith.html#method.replace_source
+    pub fn replace_source(&mut self, new_iter: I) -> Peekable<I> {
+        self.finished = false;
+        ::std::mem::replace(&mut self.iterator, new_iter.peekable())
+    }
+}
+
+impl<I> Iterator for PrevCopyPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::marker::Copy,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        match self.iterator.next() {
+            Some(next) => {
+                if self.current.is_some() {
+                    self.prev = self.current;
+                }
+                self.current = Some(next);
+                Some(next)
+            }
+            // The first time we reach the end, the last element becomes the previous one
+            None => {
+                if !self.finished {
+                    if let Some(last) = self.current.take() {
+                        self.prev = Some(last);
+                    }
+                    self.finished = true;
+                }
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PrevPeekable;
+
+    /// Lets the parity tests compare `peek_prev()`, which returns a reference from `PrevPeekable`
+    /// and a value from `PrevCopyPeekable`.
+    trait Value {
+        fn value(self) -> u8;
+    }
+
+    impl Value for u8 {
+        fn value(self) -> u8 {
+            self
+        }
+    }
+
+    impl Value for &u8 {
+        fn value(self) -> u8 {
+            *self
+        }
+    }
+
+    /// Generates the same tests for `PrevPeekable` and `PrevCopyPeekable` to show they behave the
+    /// same.
+    macro_rules! parity_tests {
+        ($name:ident, $ty:ident) => {
+            mod $name {
+                use super::*;
+
+                fn new(bytes: &'static [u8]) -> $ty<::std::iter::Copied<::std::slice::Iter<'static, u8>>> {
+                    $ty::new(bytes.iter().copied())
+                }
+
+                #[test]
+                fn test_next_and_prev() {
+                    let mut it = new(b"abc");
+
+                    assert_eq!(None, it.prev());
+                    assert_eq!(Some(b'a'), it.next());
+                    assert_eq!(None, it.prev());
+                    assert_eq!(Some(b'b'), it.next());
+                    assert_eq!(Some(b'a'), it.prev());
+                    assert_eq!(Some(b'a'), it.peek_prev().map(Value::value));
+                    assert_eq!(Some(b'c'), it.next());
+                    assert_eq!(Some(b'b'), it.prev());
+                    assert_eq!(None, it.next());
+                    assert_eq!(Some(b'c'), it.prev());
+                    assert_eq!(None, it.next());
+                    assert_eq!(Some(b'c'), it.prev());
+                }
+
+                #[test]
+                fn test_peek() {
+                    let mut it = new(b"ab");
+
+                    assert_eq!(Some(&b'a'), it.peek());
+                    assert_eq!(None, it.prev());
+                    assert_eq!(Some(b'a'), it.next());
+                    assert_eq!(Some(&b'b'), it.peek());
+                    assert_eq!(Some(b'b'), it.next());
+                    assert_eq!(None, it.peek());
+                    assert_eq!(Some(b'a'), it.prev());
+                }
+
+                #[test]
+                fn test_current() {
+                    let mut it = new(b"ab");
+
+                    assert_eq!(None, it.current());
+                    assert_eq!(Some(&b'a'), it.peek());
+                    assert_eq!(None, it.current());
+                    assert_eq!(Some(b'a'), it.next());
+                    assert_eq!(Some(&b'a'), it.current());
+                    assert_eq!(Some(b'b'), it.next());
+                    assert_eq!(Some(&b'b'), it.current());
+                    assert_eq!(None, it.next());
+                    assert_eq!(None, it.current());
+                }
+
+                #[test]
+                fn test_empty() {
+                    let mut it = new(b"");
+
+                    assert_eq!(None, it.peek());
+                    assert_eq!(None, it.next());
+                    assert_eq!(None, it.prev());
+                    assert_eq!(None, it.peek_prev().map(Value::value));
+                }
+
+                #[test]
+                fn test_replace_source_after_end() {
+                    let mut it = new(b"ab");
+
+                    assert_eq!(2, it.by_ref().count());
+                    let _ = it.replace_source(b"c".iter().copied());
+                    assert_eq!(Some(b'c'), it.next());
+                    assert_eq!(Some(b'b'), it.prev());
+                    assert_eq!(None, it.next());
+                    assert_eq!(Some(b'c'), it.prev());
+                }
+            }
+        };
+    }
+
+    parity_tests!(clone_parity, PrevPeekable);
+    parity_tests!(copy_parity, PrevCopyPeekable);
+}
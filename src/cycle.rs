@@ -0,0 +1,136 @@
+//! Detecting where the elements of a stream start repeating.
+
+use super::PrevPeekable;
+
+/// Where the elements of a stream start repeating, as found by [`detect_cycle`].
+///
+/// [`detect_cycle`]: struct.PrevPeekableWith.html#method.detect_cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CycleInfo {
+    /// The index of the first element of the cycle, counting from the next element.
+    pub start: usize,
+    /// How many elements the cycle has.
+    pub len: usize,
+}
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator + Clone,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Finds where the remaining elements start repeating with Brent's algorithm, and returns the
+    /// index where the cycle starts and its length. Returns `None` if the iterator ends, or if no
+    /// cycle was found within `max_steps` elements.
+    ///
+    /// Each element is assumed to determine the one after it, as with the states of a
+    /// deterministic state machine or a pseudo random number generator, so the sequence repeats
+    /// from the first element that's equal to an earlier one. Only a few elements are kept at a
+    /// time, rather than every element seen, and the source is cloned to go over it again.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{CycleInfo, PrevPeekable};
+    ///
+    /// // 3, 10, 101, 2, 5, 26, 167, 95, 101, ...
+    /// let states = std::iter::successors(Some(3u32), |x| Some((x * x + 1) % 255));
+    ///
+    /// assert_eq!(
+    ///     Some(CycleInfo { start: 2, len: 6 }),
+    ///     PrevPeekable::new(states).detect_cycle(1_000)
+    /// );
+    /// ```
+    pub fn detect_cycle(self, max_steps: usize) -> Option<CycleInfo>
+    where
+        I::Item: PartialEq,
+    {
+        let start = self.source.clone();
+        let mut source = self.source;
+        let mut steps = 0;
+
+        // Find the length of the cycle by moving the tortoise to the hare at every power of two
+        let mut power = 1;
+        let mut len = 1;
+        let mut tortoise = source.next()?;
+        let mut hare = source.next()?;
+        while tortoise != hare {
+            steps += 1;
+            if steps > max_steps {
+                return None;
+            }
+
+            if power == len {
+                tortoise = hare.clone();
+                power *= 2;
+                len = 0;
+            }
+            hare = source.next()?;
+            len += 1;
+        }
+
+        // With the hare `len` elements ahead, the two meet at the start of the cycle
+        let mut tortoise_source = start.clone();
+        let mut hare_source = start;
+        for _ in 0..len {
+            hare_source.next();
+        }
+
+        let mut index = 0;
+        loop {
+            let (tortoise, hare) = (tortoise_source.next()?, hare_source.next()?);
+            if tortoise == hare {
+                return Some(CycleInfo { start: index, len });
+            }
+            index += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns `prefix` followed by `cycle` repeated forever.
+    fn eventually_periodic(
+        prefix: &'static [u32],
+        cycle: &'static [u32],
+    ) -> impl Iterator<Item = u32> + Clone {
+        prefix.iter().chain(cycle.iter().cycle()).cloned()
+    }
+
+    #[test]
+    fn test_detect_cycle() {
+        let it = PrevPeekable::new(eventually_periodic(&[10, 11, 12], &[0, 1, 2, 3]));
+
+        assert_eq!(Some(CycleInfo { start: 3, len: 4 }), it.detect_cycle(1_000));
+    }
+
+    #[test]
+    fn test_detect_cycle_from_start() {
+        let it = PrevPeekable::new(eventually_periodic(&[], &[5, 6, 7]));
+        assert_eq!(Some(CycleInfo { start: 0, len: 3 }), it.detect_cycle(1_000));
+
+        let it = PrevPeekable::new(eventually_periodic(&[], &[5]));
+        assert_eq!(Some(CycleInfo { start: 0, len: 1 }), it.detect_cycle(1_000));
+    }
+
+    #[test]
+    fn test_detect_cycle_counts_from_next() {
+        let mut it = PrevPeekable::new(eventually_periodic(&[10, 11, 12], &[0, 1]));
+
+        it.next();
+        assert_eq!(Some(&12), it.peek_nth(1));
+        assert_eq!(Some(CycleInfo { start: 2, len: 2 }), it.detect_cycle(1_000));
+    }
+
+    #[test]
+    fn test_detect_cycle_none() {
+        assert_eq!(None, PrevPeekable::new(0..100).detect_cycle(1_000));
+        assert_eq!(None, PrevPeekable::new(0..0).detect_cycle(1_000));
+        // A long cycle that isn't found within the bound
+        assert_eq!(None, PrevPeekable::new((0..1_000).cycle()).detect_cycle(100));
+        assert_eq!(None, PrevPeekable::new(0..).detect_cycle(100));
+    }
+}
@@ -0,0 +1,240 @@
+//! An opt-in check that no buffered elements are lost when an iterator is dropped.
+
+use super::PrevPeekableWith;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::thread;
+use storage::PrevStorage;
+
+impl<I, S> PrevPeekableWith<I, S>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone + fmt::Debug,
+    S: PrevStorage<I::Item>,
+{
+    /// Returns an [`AssertDrained`], which complains when it's dropped while elements it pulled out
+    /// of the source haven't been returned yet, such as a peeked element or elements put back by
+    /// [`rewind_to`], or while there are checkpoints that haven't been rewound to. That input would
+    /// otherwise be lost silently.
+    ///
+    /// In debug builds it panics, and in release builds it prints a warning to stderr. Nothing
+    /// happens if the thread is already panicking.
+    ///
+    /// [`AssertDrained`]: struct.AssertDrained.html
+    /// [`rewind_to`]: #method.rewind_to
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new(1..3).debug_assert_drained();
+    ///
+    /// assert_eq!(Some(&1), it.peek());
+    /// assert_eq!(vec![1, 2], it.by_ref().collect::<Vec<_>>());
+    /// // Every element was returned, so dropping it is fine
+    /// ```
+    pub fn debug_assert_drained(self) -> AssertDrained<I, S> {
+        AssertDrained { iter: Some(self) }
+    }
+}
+
+/// A `PrevPeekable` that checks that it was drained when it's dropped.
+///
+/// This `struct` is created by [`debug_assert_drained`]. It dereferences to the `PrevPeekable`, so
+/// all of its methods can be called on it.
+///
+/// [`debug_assert_drained`]: struct.PrevPeekableWith.html#method.debug_assert_drained
+pub struct AssertDrained<I, S>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone + fmt::Debug,
+    S: PrevStorage<I::Item>,
+{
+    /// Only `None` once `into_inner()` has taken it
+    iter: Option<PrevPeekableWith<I, S>>,
+}
+
+impl<I, S> AssertDrained<I, S>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone + fmt::Debug,
+    S: PrevStorage<I::Item>,
+{
+    /// Returns the `PrevPeekable` without checking it, e.g. to hand it to a consuming adapter.
+    pub fn into_inner(mut self) -> PrevPeekableWith<I, S> {
+        self.iter.take().expect("only into_inner() takes the iterator")
+    }
+}
+
+impl<I, S> Deref for AssertDrained<I, S>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone + fmt::Debug,
+    S: PrevStorage<I::Item>,
+{
+    type Target = PrevPeekableWith<I, S>;
+
+    fn deref(&self) -> &PrevPeekableWith<I, S> {
+        self.iter.as_ref().expect("only into_inner() takes the iterator")
+    }
+}
+
+impl<I, S> DerefMut for AssertDrained<I, S>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone + fmt::Debug,
+    S: PrevStorage<I::Item>,
+{
+    fn deref_mut(&mut self) -> &mut PrevPeekableWith<I, S> {
+        self.iter.as_mut().expect("only into_inner() takes the iterator")
+    }
+}
+
+impl<I, S> Iterator for AssertDrained<I, S>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone + fmt::Debug,
+    S: PrevStorage<I::Item>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        (**self).next()
+    }
+}
+
+impl<I, S> fmt::Debug for AssertDrained<I, S>
+where
+    I: Iterator + fmt::Debug,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone + fmt::Debug,
+    S: PrevStorage<I::Item> + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AssertDrained").field("iter", &self.iter).finish()
+    }
+}
+
+impl<I, S> Drop for AssertDrained<I, S>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone + fmt::Debug,
+    S: PrevStorage<I::Item>,
+{
+    fn drop(&mut self) {
+        // Complaining while unwinding would abort, and hide the original panic
+        if thread::panicking() {
+            return;
+        }
+        let iter = match self.iter {
+            Some(ref mut iter) => iter,
+            None => return,
+        };
+
+        let checkpoints = iter.journal.as_ref().map_or(0, |journal| journal.checkpoints());
+        let (count, first) = iter.source.pending();
+        if count == 0 && checkpoints == 0 {
+            return;
+        }
+
+        let mut message = format!(
+            "PrevPeekable dropped with {} unconsumed buffered element(s)",
+            count
+        );
+        if let Some(first) = first {
+            message += &format!(", starting with {:?}", first);
+        }
+        if checkpoints != 0 {
+            message += &format!(", and {} active checkpoint(s)", checkpoints);
+        }
+
+        if cfg!(debug_assertions) {
+            panic!("{}", message);
+        } else {
+            eprintln!("warning: {}", message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use PrevPeekable;
+
+    #[test]
+    #[should_panic(
+        expected = "PrevPeekable dropped with 1 unconsumed buffered element(s), starting with 'a'"
+    )]
+    fn test_leaked_peek() {
+        let mut it = PrevPeekable::new("abc".chars()).debug_assert_drained();
+
+        assert_eq!(Some(&'a'), it.peek());
+    }
+
+    #[test]
+    #[should_panic(expected = "PrevPeekable dropped with 3 unconsumed buffered element(s), starting with 2")]
+    fn test_leaked_peek_nth() {
+        let mut it = PrevPeekable::new(1..10).debug_assert_drained();
+
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Some(&4), it.peek_nth(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "and 1 active checkpoint(s)")]
+    fn test_leaked_checkpoint() {
+        let mut it = PrevPeekable::new(1..3).debug_assert_drained();
+
+        let _ = it.checkpoint();
+        assert_eq!(vec![1, 2], it.by_ref().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_rewound_checkpoint() {
+        let mut it = PrevPeekable::new(1..3).debug_assert_drained();
+
+        let cp = it.checkpoint();
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Ok(()), it.rewind_to(cp));
+        // Rewinding took the checkpoint, so dropping the drained iterator is fine
+        assert_eq!(vec![1, 2], it.by_ref().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_drained() {
+        let mut it = PrevPeekable::new("abc".chars()).debug_assert_drained();
+
+        assert_eq!(Some(&'a'), it.peek());
+        assert_eq!(Some(&'c'), it.peek_nth(2));
+        assert_eq!(vec!['a', 'b', 'c'], it.by_ref().collect::<Vec<_>>());
+        // Peeking past the end doesn't buffer anything
+        assert_eq!(None, it.peek());
+        assert_eq!(Some('c'), it.prev());
+    }
+
+    #[test]
+    fn test_partially_read() {
+        // Elements that are still in the source aren't lost
+        let mut it = PrevPeekable::new(1..10).debug_assert_drained();
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Some(2), it.next());
+        drop(it);
+
+        let mut it = PrevPeekable::new(1..10).debug_assert_drained();
+        it.peek();
+        let _ = it.into_inner();
+    }
+
+    #[test]
+    fn test_no_double_panic() {
+        let result = ::std::panic::catch_unwind(|| {
+            let mut it = PrevPeekable::new(1..10).debug_assert_drained();
+            it.peek();
+            panic!("the original panic");
+        });
+
+        let error = result.unwrap_err();
+        assert_eq!(Some(&"the original panic"), error.downcast_ref::<&str>());
+    }
+}
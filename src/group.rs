@@ -0,0 +1,206 @@
+//! Adapters that split the stream into groups of consecutive elements.
+
+use super::PrevPeekable;
+use std::ops::Sub;
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Groups the stream into sessions, starting a new session whenever the gap between an
+    /// element's timestamp and the previous element's timestamp is greater than `max_gap`. Each
+    /// session is yielded as a `Vec` of its elements, in order.
+    ///
+    /// `ts` extracts the timestamp of an element. Timestamps are expected to be non-decreasing; a
+    /// timestamp going backwards produces a negative (or, for unsigned types, panicking) gap.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let clicks = vec![
+    ///     (100, "/home"),
+    ///     (130, "/search"),
+    ///     (150, "/item/1"),
+    ///     (2000, "/home"),
+    ///     (2010, "/cart"),
+    /// ];
+    /// let sessions: Vec<_> = PrevPeekable::new(clicks.into_iter())
+    ///     .sessionize_by(|&(ts, _)| ts, 1800)
+    ///     .collect();
+    ///
+    /// assert_eq!(
+    ///     vec![
+    ///         vec![(100, "/home"), (130, "/search"), (150, "/item/1")],
+    ///         vec![(2000, "/home"), (2010, "/cart")],
+    ///     ],
+    ///     sessions
+    /// );
+    /// ```
+    pub fn sessionize_by<K, F>(mut self, mut ts: F, max_gap: K::Output) -> impl Iterator<Item = Vec<I::Item>>
+    where
+        K: Sub,
+        K::Output: PartialOrd,
+        F: FnMut(&I::Item) -> K,
+    {
+        ::std::iter::from_fn(move || {
+            let mut session = vec![self.next()?];
+
+            loop {
+                let continues = match self.source.peek() {
+                    Some(next) => {
+                        let prev = self.current.as_ref().expect("an element was just returned");
+                        ts(next) - ts(prev) <= max_gap
+                    }
+                    None => false,
+                };
+
+                if !continues {
+                    return Some(session);
+                }
+
+                session.push(self.next().expect("peek() returned an element"));
+            }
+        })
+    }
+
+    /// Yields every element unchanged, and inserts a separator before the first element of each
+    /// group of consecutive elements with the same `key`. The separator is computed by calling
+    /// `sep` with the last element of the old group and the first element of the new one.
+    ///
+    /// There is no separator before the first group or after the last one. Unlike
+    /// [`Itertools::intersperse`], separators only go between groups rather than between every
+    /// pair of elements.
+    ///
+    /// [`Itertools::intersperse`]: https://docs.rs/itertools/latest/itertools/trait.Itertools.html#method.intersperse
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// enum Row {
+    ///     Date(&'static str),
+    ///     Entry(&'static str, &'static str, &'static str),
+    /// }
+    ///
+    /// fn date(row: &Row) -> &'static str {
+    ///     match *row {
+    ///         Row::Date(date) | Row::Entry(date, _, _) => date,
+    ///     }
+    /// }
+    ///
+    /// let log = vec![
+    ///     Row::Entry("2024-03-01", "09:15", "deploy started"),
+    ///     Row::Entry("2024-03-01", "09:40", "deploy finished"),
+    ///     Row::Entry("2024-03-02", "11:02", "alert fired"),
+    /// ];
+    /// let rows: Vec<_> = PrevPeekable::new(log.into_iter())
+    ///     .separate_groups_with(date, |_, next| Row::Date(date(next)))
+    ///     .collect();
+    ///
+    /// assert_eq!(
+    ///     vec![
+    ///         Row::Entry("2024-03-01", "09:15", "deploy started"),
+    ///         Row::Entry("2024-03-01", "09:40", "deploy finished"),
+    ///         Row::Date("2024-03-02"),
+    ///         Row::Entry("2024-03-02", "11:02", "alert fired"),
+    ///     ],
+    ///     rows
+    /// );
+    /// ```
+    pub fn separate_groups_with<K, F, G>(mut self, mut key: F, mut sep: G) -> impl Iterator<Item = I::Item>
+    where
+        K: PartialEq,
+        F: FnMut(&I::Item) -> K,
+        G: FnMut(&I::Item, &I::Item) -> I::Item,
+    {
+        // The first element of a new group, held back while its separator is returned
+        let mut pending = None;
+
+        ::std::iter::from_fn(move || {
+            if let Some(first) = pending.take() {
+                return Some(first);
+            }
+
+            let cur = self.next()?;
+            if let Some(prev) = self.peek_prev() {
+                if key(prev) != key(&cur) {
+                    let separator = sep(prev, &cur);
+                    pending = Some(cur);
+                    return Some(separator);
+                }
+            }
+
+            Some(cur)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessionize_by() {
+        let v = [1, 2, 10, 11, 30];
+        let sessions: Vec<_> = PrevPeekable::new(v.iter()).sessionize_by(|&&x| x, 5).collect();
+
+        assert_eq!(vec![vec![&1, &2], vec![&10, &11], vec![&30]], sessions);
+    }
+
+    #[test]
+    fn test_sessionize_by_single_session() {
+        let v = [1, 2, 3, 4, 5];
+        let sessions: Vec<_> = PrevPeekable::new(v.iter()).sessionize_by(|&&x| x, 1).collect();
+
+        assert_eq!(vec![vec![&1, &2, &3, &4, &5]], sessions);
+    }
+
+    #[test]
+    fn test_sessionize_by_every_event_new_session() {
+        let v = [1.0, 3.0, 5.5, 8.0];
+        let sessions: Vec<_> = PrevPeekable::new(v.iter()).sessionize_by(|&&x| x, 1.5).collect();
+
+        assert_eq!(vec![vec![&1.0], vec![&3.0], vec![&5.5], vec![&8.0]], sessions);
+    }
+
+    #[test]
+    fn test_sessionize_by_empty() {
+        let v: [u32; 0] = [];
+        let mut sessions = PrevPeekable::new(v.iter()).sessionize_by(|&&x| x, 1);
+
+        assert_eq!(None, sessions.next());
+    }
+
+    #[test]
+    fn test_separate_groups_with() {
+        let v = [1, 3, 4, 6, 8, 9];
+        let separated: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .separate_groups_with(|x| x % 2, |prev, next| -(prev * 10 + next))
+            .collect();
+
+        assert_eq!(vec![1, 3, -34, 4, 6, 8, -89, 9], separated);
+    }
+
+    #[test]
+    fn test_separate_groups_with_single_group() {
+        let v = [2, 4, 6];
+        let separated: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .separate_groups_with(|x| x % 2, |_, _| panic!("there's only one group"))
+            .collect();
+
+        assert_eq!(vec![2, 4, 6], separated);
+
+        let v: [i32; 0] = [];
+        let mut separated = PrevPeekable::new(v.iter().cloned()).separate_groups_with(|x| x % 2, |_, _| 0);
+        assert_eq!(None, separated.next());
+    }
+}
@@ -0,0 +1,590 @@
+//! Adapters that transform the stream but keep the lookbehind of the source.
+
+use super::PrevPeekable;
+use std::fmt;
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Like [`Iterator::map`], but the returned adapter still has [`prev`] and [`peek_prev`], which
+    /// return the previous element of the source rather than a mapped one. This lets code outside
+    /// the closure keep looking behind after the stream has been transformed.
+    ///
+    /// [`Iterator::map`]: https://doc.rust-lang.org/nightly/core/iter/trait.Iterator.html#method.map
+    /// [`prev`]: struct.MapKeepPrev.html#method.prev
+    /// [`peek_prev`]: struct.MapKeepPrev.html#method.peek_prev
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 2, 3];
+    /// let mut it = PrevPeekable::new(v.into_iter()).map_keep_prev(|x| x * 10);
+    ///
+    /// assert_eq!(Some(10), it.next());
+    /// assert_eq!(Some(20), it.next());
+    /// assert_eq!(Some(1), it.prev());
+    /// ```
+    pub fn map_keep_prev<B, F>(self, f: F) -> MapKeepPrev<I, F>
+    where
+        F: FnMut(I::Item) -> B,
+    {
+        MapKeepPrev { iter: self, f }
+    }
+
+    /// Like [`Iterator::filter`], but the returned adapter still has [`prev`] and [`peek_prev`],
+    /// which return the previous element of the source. That's the element right before the one
+    /// just returned, whether or not it was filtered out.
+    ///
+    /// [`Iterator::filter`]: https://doc.rust-lang.org/nightly/core/iter/trait.Iterator.html#method.filter
+    /// [`prev`]: struct.FilterKeepPrev.html#method.prev
+    /// [`peek_prev`]: struct.FilterKeepPrev.html#method.peek_prev
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec!['a', ' ', 'b'];
+    /// let mut it = PrevPeekable::new(v.into_iter()).filter_keep_prev(|c| !c.is_whitespace());
+    ///
+    /// assert_eq!(Some('a'), it.next());
+    /// assert_eq!(Some('b'), it.next());
+    /// // 'b' came right after a space
+    /// assert_eq!(Some(' '), it.prev());
+    /// ```
+    pub fn filter_keep_prev<P>(self, predicate: P) -> FilterKeepPrev<I, P>
+    where
+        P: FnMut(&I::Item) -> bool,
+    {
+        FilterKeepPrev {
+            iter: self,
+            predicate,
+        }
+    }
+
+    /// Like [`Iterator::take_while`], but also yields the first element that doesn't satisfy
+    /// `predicate`, and then stops. This reads a range up to and including its terminator, such as
+    /// a string up to its closing quote.
+    ///
+    /// The terminator is consumed from the source. The returned adapter still has [`prev`] and
+    /// [`peek_prev`], and [`into_inner`] gives the `PrevPeekable` back to carry on after the
+    /// terminator.
+    ///
+    /// [`Iterator::take_while`]: https://doc.rust-lang.org/nightly/core/iter/trait.Iterator.html#method.take_while
+    /// [`prev`]: struct.TakeWhileInclusive.html#method.prev
+    /// [`peek_prev`]: struct.TakeWhileInclusive.html#method.peek_prev
+    /// [`into_inner`]: struct.TakeWhileInclusive.html#method.into_inner
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new("\"hi\" there".chars());
+    ///
+    /// assert_eq!(Some('"'), it.next());
+    /// let mut string = it.take_while_inclusive(|&c| c != '"');
+    /// assert_eq!("hi\"", string.by_ref().collect::<String>());
+    ///
+    /// let mut it = string.into_inner();
+    /// assert_eq!(Some(' '), it.next());
+    /// ```
+    pub fn take_while_inclusive<P>(self, predicate: P) -> TakeWhileInclusive<I, P>
+    where
+        P: FnMut(&I::Item) -> bool,
+    {
+        TakeWhileInclusive {
+            iter: self,
+            predicate,
+            state: InclusiveState::Running,
+        }
+    }
+
+    /// Like [`take_while_inclusive`], but `predicate` is also passed the element before the one
+    /// it's testing, or `None` for the first element of the source.
+    ///
+    /// [`take_while_inclusive`]: #method.take_while_inclusive
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// // Read a string up to the first quote that isn't escaped
+    /// let it = PrevPeekable::new(r#"a\"b"c"#.chars());
+    /// let string: String = it
+    ///     .take_while_inclusive_with_prev(|prev, &c| c != '"' || prev == Some(&'\\'))
+    ///     .collect();
+    ///
+    /// assert_eq!(r#"a\"b""#, string);
+    /// ```
+    pub fn take_while_inclusive_with_prev<P>(self, predicate: P) -> TakeWhileInclusiveWithPrev<I, P>
+    where
+        P: FnMut(Option<&I::Item>, &I::Item) -> bool,
+    {
+        TakeWhileInclusiveWithPrev {
+            iter: self,
+            predicate,
+            state: InclusiveState::Running,
+        }
+    }
+}
+
+/// An iterator that maps the elements of a `PrevPeekable` and keeps its lookbehind.
+///
+/// This `struct` is created by [`map_keep_prev`].
+///
+/// [`map_keep_prev`]: struct.PrevPeekableWith.html#method.map_keep_prev
+pub struct MapKeepPrev<I, F>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    iter: PrevPeekable<I>,
+    f: F,
+}
+
+impl<I, F> MapKeepPrev<I, F>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Returns the source element before the one that was mapped last, or `None` if there isn't
+    /// one yet. When the end is reached, it returns the last element of the source.
+    pub fn prev(&self) -> Option<I::Item> {
+        self.iter.prev()
+    }
+
+    /// Returns a reference to the source element before the one that was mapped last, or `None` if
+    /// there isn't one yet. When the end is reached, it returns the last element of the source.
+    pub fn peek_prev(&self) -> Option<&I::Item> {
+        self.iter.peek_prev()
+    }
+}
+
+impl<B, I, F> Iterator for MapKeepPrev<I, F>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+    F: FnMut(I::Item) -> B,
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<B> {
+        self.iter.next().map(&mut self.f)
+    }
+}
+
+impl<I, F> fmt::Debug for MapKeepPrev<I, F>
+where
+    I: Iterator + fmt::Debug,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MapKeepPrev").field("iter", &self.iter).finish()
+    }
+}
+
+/// An iterator that filters the elements of a `PrevPeekable` and keeps its lookbehind.
+///
+/// This `struct` is created by [`filter_keep_prev`].
+///
+/// [`filter_keep_prev`]: struct.PrevPeekableWith.html#method.filter_keep_prev
+pub struct FilterKeepPrev<I, P>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    iter: PrevPeekable<I>,
+    predicate: P,
+}
+
+impl<I, P> FilterKeepPrev<I, P>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Returns the source element before the one that was returned last, whether or not it was
+    /// filtered out, or `None` if there isn't one yet. When the end is reached, it returns the last
+    /// element of the source.
+    pub fn prev(&self) -> Option<I::Item> {
+        self.iter.prev()
+    }
+
+    /// Returns a reference to the source element before the one that was returned last, whether or
+    /// not it was filtered out, or `None` if there isn't one yet. When the end is reached, it
+    /// returns the last element of the source.
+    pub fn peek_prev(&self) -> Option<&I::Item> {
+        self.iter.peek_prev()
+    }
+}
+
+impl<I, P> Iterator for FilterKeepPrev<I, P>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+    P: FnMut(&I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let predicate = &mut self.predicate;
+        self.iter.find(|x| predicate(x))
+    }
+}
+
+impl<I, P> fmt::Debug for FilterKeepPrev<I, P>
+where
+    I: Iterator + fmt::Debug,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FilterKeepPrev")
+            .field("iter", &self.iter)
+            .finish()
+    }
+}
+
+/// An iterator that yields elements of a `PrevPeekable` while a predicate holds, and then the
+/// element that ended the run.
+///
+/// This `struct` is created by [`take_while_inclusive`].
+///
+/// [`take_while_inclusive`]: struct.PrevPeekableWith.html#method.take_while_inclusive
+pub struct TakeWhileInclusive<I, P>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    iter: PrevPeekable<I>,
+    predicate: P,
+    state: InclusiveState,
+}
+
+/// How far a `take_while_inclusive` adapter has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InclusiveState {
+    Running,
+    /// The element that ended the run was returned
+    Terminated,
+    /// `None` was returned
+    Finished,
+}
+
+impl<I, P> TakeWhileInclusive<I, P>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Returns the element before the one that was returned last, or `None` if there isn't one
+    /// yet. Once the adapter has returned `None`, it returns the last element it returned, which is
+    /// the terminator if there was one.
+    pub fn prev(&self) -> Option<I::Item> {
+        self.peek_prev().cloned()
+    }
+
+    /// Returns a reference to the element before the one that was returned last, or `None` if
+    /// there isn't one yet. Once the adapter has returned `None`, it returns the last element it
+    /// returned, which is the terminator if there was one.
+    pub fn peek_prev(&self) -> Option<&I::Item> {
+        inclusive_prev(&self.iter, self.state)
+    }
+
+    /// Returns the `PrevPeekable`, which is right after the terminator if it was returned.
+    pub fn into_inner(self) -> PrevPeekable<I> {
+        self.iter
+    }
+}
+
+impl<I, P> Iterator for TakeWhileInclusive<I, P>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+    P: FnMut(&I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let predicate = &mut self.predicate;
+
+        inclusive_next(&mut self.iter, &mut self.state, |_, cur| predicate(cur))
+    }
+}
+
+impl<I, P> fmt::Debug for TakeWhileInclusive<I, P>
+where
+    I: Iterator + fmt::Debug,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TakeWhileInclusive")
+            .field("iter", &self.iter)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+/// An iterator that yields elements of a `PrevPeekable` while a predicate of each element and the
+/// one before it holds, and then the element that ended the run.
+///
+/// This `struct` is created by [`take_while_inclusive_with_prev`].
+///
+/// [`take_while_inclusive_with_prev`]: struct.PrevPeekableWith.html#method.take_while_inclusive_with_prev
+pub struct TakeWhileInclusiveWithPrev<I, P>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    iter: PrevPeekable<I>,
+    predicate: P,
+    state: InclusiveState,
+}
+
+impl<I, P> TakeWhileInclusiveWithPrev<I, P>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Returns the element before the one that was returned last, or `None` if there isn't one
+    /// yet. Once the adapter has returned `None`, it returns the last element it returned, which is
+    /// the terminator if there was one.
+    pub fn prev(&self) -> Option<I::Item> {
+        self.peek_prev().cloned()
+    }
+
+    /// Returns a reference to the element before the one that was returned last, or `None` if
+    /// there isn't one yet. Once the adapter has returned `None`, it returns the last element it
+    /// returned, which is the terminator if there was one.
+    pub fn peek_prev(&self) -> Option<&I::Item> {
+        inclusive_prev(&self.iter, self.state)
+    }
+
+    /// Returns the `PrevPeekable`, which is right after the terminator if it was returned.
+    pub fn into_inner(self) -> PrevPeekable<I> {
+        self.iter
+    }
+}
+
+impl<I, P> Iterator for TakeWhileInclusiveWithPrev<I, P>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+    P: FnMut(Option<&I::Item>, &I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        inclusive_next(&mut self.iter, &mut self.state, &mut self.predicate)
+    }
+}
+
+impl<I, P> fmt::Debug for TakeWhileInclusiveWithPrev<I, P>
+where
+    I: Iterator + fmt::Debug,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TakeWhileInclusiveWithPrev")
+            .field("iter", &self.iter)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+/// Advances a `take_while_inclusive` adapter, passing `predicate` the previous and the current
+/// element.
+fn inclusive_next<I, P>(
+    iter: &mut PrevPeekable<I>,
+    state: &mut InclusiveState,
+    mut predicate: P,
+) -> Option<I::Item>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+    P: FnMut(Option<&I::Item>, &I::Item) -> bool,
+{
+    if *state != InclusiveState::Running {
+        *state = InclusiveState::Finished;
+        return None;
+    }
+
+    match iter.next() {
+        Some(cur) => {
+            if !predicate(iter.peek_prev(), &cur) {
+                *state = InclusiveState::Terminated;
+            }
+            Some(cur)
+        }
+        None => {
+            *state = InclusiveState::Finished;
+            None
+        }
+    }
+}
+
+/// Returns the previous element of a `take_while_inclusive` adapter.
+fn inclusive_prev<I>(iter: &PrevPeekable<I>, state: InclusiveState) -> Option<&I::Item>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    match state {
+        // The source only moved on if there was no terminator, in which case the last element is
+        // its previous one
+        InclusiveState::Finished => iter.current().or_else(|| iter.peek_prev()),
+        InclusiveState::Running | InclusiveState::Terminated => iter.peek_prev(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Token {
+        Ident(&'static str),
+        Number(u32),
+        Comma,
+    }
+
+    fn tokens() -> Vec<Token> {
+        vec![Token::Ident("x"), Token::Comma, Token::Number(7), Token::Comma]
+    }
+
+    fn render(token: Token) -> String {
+        match token {
+            Token::Ident(name) => name.to_string(),
+            Token::Number(n) => n.to_string(),
+            Token::Comma => ",".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_map_keep_prev() {
+        let mut it = PrevPeekable::new(tokens().into_iter()).map_keep_prev(render);
+
+        assert_eq!(Some("x".to_string()), it.next());
+        assert_eq!(None, it.prev());
+        assert_eq!(Some(",".to_string()), it.next());
+        assert_eq!(Some(Token::Ident("x")), it.prev());
+        assert_eq!(Some("7".to_string()), it.next());
+        assert_eq!(Some(&Token::Comma), it.peek_prev());
+        assert_eq!(Some(",".to_string()), it.next());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(Token::Comma), it.prev());
+    }
+
+    #[test]
+    fn test_filter_keep_prev() {
+        let mut it = PrevPeekable::new(tokens().into_iter()).filter_keep_prev(|t| *t != Token::Comma);
+
+        assert_eq!(Some(Token::Ident("x")), it.next());
+        assert_eq!(None, it.prev());
+        assert_eq!(Some(Token::Number(7)), it.next());
+        // The comma that was filtered out is still the previous source element
+        assert_eq!(Some(Token::Comma), it.prev());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(&Token::Comma), it.peek_prev());
+    }
+
+    #[test]
+    fn test_keep_prev_chained() {
+        let rendered: Vec<_> = PrevPeekable::new(tokens().into_iter())
+            .filter_keep_prev(|t| *t != Token::Comma)
+            .map(render)
+            .collect();
+
+        assert_eq!(vec!["x", "7"], rendered);
+    }
+
+    #[test]
+    fn test_take_while_inclusive() {
+        let v = [1, 2, 3, 10, 4];
+        let mut it = PrevPeekable::new(v.iter().cloned()).take_while_inclusive(|&x| x < 5);
+
+        assert_eq!(vec![1, 2, 3, 10], it.by_ref().collect::<Vec<_>>());
+        // The terminator is the last element returned
+        assert_eq!(Some(10), it.prev());
+        assert_eq!(None, it.next());
+
+        let mut rest = it.into_inner();
+        assert_eq!(Some(&10), rest.current());
+        assert_eq!(Some(4), rest.next());
+        assert_eq!(Some(10), rest.prev());
+    }
+
+    #[test]
+    fn test_take_while_inclusive_terminator_first() {
+        let v = [10, 1, 2];
+        let mut it = PrevPeekable::new(v.iter().cloned()).take_while_inclusive(|&x| x < 5);
+
+        assert_eq!(Some(10), it.next());
+        assert_eq!(None, it.prev());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(&10), it.peek_prev());
+        assert_eq!(vec![1, 2], it.into_inner().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_take_while_inclusive_terminator_last() {
+        let v = [1, 2, 10];
+        let mut it = PrevPeekable::new(v.iter().cloned()).take_while_inclusive(|&x| x < 5);
+
+        assert_eq!(vec![1, 2, 10], it.by_ref().collect::<Vec<_>>());
+        assert_eq!(Some(10), it.prev());
+        assert_eq!(None, it.into_inner().next());
+    }
+
+    #[test]
+    fn test_take_while_inclusive_no_terminator() {
+        let v = [1, 2, 3];
+        let mut it = PrevPeekable::new(v.iter().cloned()).take_while_inclusive(|&x| x < 5);
+
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Some(2), it.next());
+        assert_eq!(Some(3), it.next());
+        assert_eq!(Some(2), it.prev());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(3), it.prev());
+        assert_eq!(None, it.next());
+
+        let empty = PrevPeekable::new(v[..0].iter().cloned());
+        assert_eq!(0, empty.take_while_inclusive(|&x| x < 5).count());
+    }
+
+    #[test]
+    fn test_take_while_inclusive_with_prev() {
+        // Read up to the first element that's smaller than the one before it
+        let v = [1, 3, 7, 4, 8];
+        let mut it = PrevPeekable::new(v.iter().cloned())
+            .take_while_inclusive_with_prev(|prev, &cur| prev.is_none_or(|&prev| cur >= prev));
+
+        assert_eq!(vec![1, 3, 7, 4], it.by_ref().collect::<Vec<_>>());
+        assert_eq!(Some(4), it.prev());
+        assert_eq!(vec![8], it.into_inner().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_take_while_inclusive_with_prev_terminator_first() {
+        let v = [1, 2];
+        let mut it =
+            PrevPeekable::new(v.iter().cloned()).take_while_inclusive_with_prev(|prev, _| prev.is_some());
+
+        assert_eq!(Some(1), it.next());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(1), it.prev());
+        assert_eq!(Some(2), it.into_inner().next());
+    }
+}
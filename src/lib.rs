@@ -1,31 +1,128 @@
 //! `prev-iter` contains an iterator which allows you to view the previous element.
 
+#[cfg(feature = "futures")]
+extern crate futures_core;
+#[cfg(feature = "memchr")]
+extern crate memchr;
+#[cfg(all(test, feature = "num-traits"))]
+extern crate num_rational;
+#[cfg(feature = "num-traits")]
+extern crate num_traits;
+#[cfg(feature = "derive")]
+extern crate prev_iter_derive;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+
+use checkpoint::Journal;
+use lookahead::Lookahead;
 use std::iter::Iterator;
 use std::iter::Peekable;
 
-/// An iterator with `prev()`, `prev_peek()`, and `peek()` functions that return the previous element, a
+mod byte_slice;
+mod bytes;
+mod channel;
+mod checkpoint;
+mod collections;
+mod copy;
+mod cycle;
+mod drain;
+mod group;
+mod keep_prev;
+mod lookahead;
+mod merge;
+mod neighbors;
+#[cfg(feature = "num-traits")]
+mod num;
+mod numeric;
+mod options;
+mod order;
+mod pairs;
+#[cfg(feature = "rayon")]
+pub mod par;
+mod parse;
+mod push;
+mod results;
+mod runs;
+mod shared;
+pub mod slice;
+mod state;
+mod storage;
+#[cfg(feature = "futures")]
+mod stream;
+mod sync;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+mod text;
+mod utf8;
+mod windows;
+
+pub use byte_slice::PrevBytes;
+pub use bytes::{MagicError, UnexpectedEof};
+pub use channel::TryRecvIter;
+pub use checkpoint::{Checkpoint, StaleCheckpoint};
+pub use collections::SkipLast;
+pub use copy::PrevCopyPeekable;
+pub use cycle::CycleInfo;
+pub use drain::AssertDrained;
+pub use keep_prev::{FilterKeepPrev, MapKeepPrev, TakeWhileInclusive, TakeWhileInclusiveWithPrev};
+pub use merge::{align_on, kmerge_with_prev, KMerge};
+pub use numeric::{CounterReset, Direction, Timestamp, ZeroPolicy};
+pub use order::Monotonicity;
+pub use pairs::Transition;
+#[cfg(feature = "derive")]
+pub use prev_iter_derive::PrevIterator;
+pub use push::{PushNext, PushPrevPeekable};
+pub use shared::SharedPrevPeekable;
+pub use state::PrevState;
+pub use storage::{CloneStorage, KeyStorage, PrevStorage, RcStorage};
+#[cfg(feature = "futures")]
+pub use stream::{Dedup, MapWithPrev, PrevPeekableStream, Transitions, WithPrev};
+pub use sync::SyncPrevPeekable;
+pub use text::IndentChange;
+pub use utf8::{Utf8Chars, Utf8Error, Utf8ErrorKind};
+
+/// An iterator with `prev()`, `peek_prev()`, and `peek()` functions that return the previous element, a
 /// reference to the previous element, or a reference to the next element, respectively.
 ///
-/// This `struct` is created by passing an [`Iterator`] whose `Item` implements [`Clone`] to the
-/// [`new`] function.
+/// This is the [`PrevPeekableWith`] that keeps a clone of the previous element, and it's created by
+/// passing an [`Iterator`] whose `Item` implements [`Clone`] to the [`new`] function.
 ///
+/// [`PrevPeekableWith`]: struct.PrevPeekableWith.html
 /// [`Iterator`]: https://doc.rust-lang.org/nightly/core/iter/iterator/trait.Iterator.html
 /// [`Clone`]: https://doc.rust-lang.org/nightly/core/clone/trait.Clone.html
-/// [`new`]: struct.PrevPeekable.html#method.new
+/// [`new`]: struct.PrevPeekableWith.html#method.new
+pub type PrevPeekable<I> = PrevPeekableWith<I, CloneStorage<<I as ::std::iter::Iterator>::Item>>;
+
+/// An iterator like [`PrevPeekable`] which is generic over how the previous element is stored.
+///
+/// This `struct` is created by passing an [`Iterator`] whose `Item` implements [`Clone`] and a
+/// [`PrevStorage`] to the [`with_storage`] function.
+///
+/// [`PrevPeekable`]: type.PrevPeekable.html
+/// [`Iterator`]: https://doc.rust-lang.org/nightly/core/iter/iterator/trait.Iterator.html
+/// [`Clone`]: https://doc.rust-lang.org/nightly/core/clone/trait.Clone.html
+/// [`PrevStorage`]: trait.PrevStorage.html
+/// [`with_storage`]: struct.PrevPeekableWith.html#method.with_storage
 #[derive(Debug)]
-pub struct PrevPeekable<I>
+pub struct PrevPeekableWith<I, S>
 where
     I: Iterator,
     <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+    S: PrevStorage<I::Item>,
 {
-    /// Iterator that `PrevPeekable` wraps
-    iterator: Peekable<I>,
-    /// The element before the one we just returned. Initially it's `None`.
-    prev: Option<I::Item>,
+    /// Iterator that `PrevPeekable` wraps, along with any elements buffered to look ahead
+    source: Lookahead<I>,
+    /// Keeps what's needed of the element before the one we just returned. Initially it's empty.
+    storage: S,
     /// The current element we just returned.
     current: Option<I::Item>,
     /// Keeps track of whether the iterator has reached the end or not
     finished: bool,
+    /// Whether the element returned most recently was swapped into a caller's buffer by
+    /// `next_reuse()` rather than kept in `current`.
+    lent: bool,
+    /// What's needed to rewind to checkpoints, once one has been created
+    journal: Option<Journal<I::Item, S>>,
 }
 
 impl<I> PrevPeekable<I>
@@ -53,11 +150,132 @@ where
     /// assert_eq!(Some(&1), iter.prev());
     /// ```
     pub fn new(iterator: I) -> Self {
-        PrevPeekable {
-            iterator: iterator.peekable(),
-            prev: None,
+        PrevPeekableWith::with_storage(iterator, CloneStorage::default())
+    }
+
+    /// Replaces the previous element with `value`, so `prev()` returns it until the iterator
+    /// advances again. The current element and the position in the iterator aren't affected.
+    ///
+    /// This lets a parser pretend there was an element before the real ones, for example a
+    /// separator at the start of the input so the first token is handled like any other.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new("ab".chars());
+    ///
+    /// it.set_prev(Some('\n'));
+    /// assert_eq!(Some('\n'), it.prev());
+    /// // The first element doesn't replace it, since there was no current element yet
+    /// assert_eq!(Some('a'), it.next());
+    /// assert_eq!(Some('\n'), it.prev());
+    /// assert_eq!(Some('b'), it.next());
+    /// assert_eq!(Some('a'), it.prev());
+    /// ```
+    pub fn set_prev(&mut self, value: Option<I::Item>) {
+        self.storage.prev = value;
+    }
+
+    /// Returns references to the previous and current elements, in that order, leaving out any that
+    /// there isn't. This is handy for logging where the iterator is.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new("ab".chars());
+    ///
+    /// it.next();
+    /// assert_eq!(vec![&'a'], it.prev_current_slice());
+    /// it.next();
+    /// assert_eq!(vec![&'a', &'b'], it.prev_current_slice());
+    /// // After the end, the last element is the previous one
+    /// it.next();
+    /// assert_eq!(vec![&'b'], it.prev_current_slice());
+    /// ```
+    pub fn prev_current_slice(&self) -> Vec<&I::Item> {
+        self.storage.prev.iter().chain(self.current.iter()).collect()
+    }
+
+    /// Returns clones of the previous and the next element, as `(prev, next)`, without advancing
+    /// the iterator. Since they're clones, both can be held on to while the iterator is used,
+    /// which isn't possible with the references returned by [`peek_prev`] and [`peek`].
+    ///
+    /// [`peek_prev`]: struct.PrevPeekableWith.html#method.peek_prev
+    /// [`peek`]: struct.PrevPeekableWith.html#method.peek
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new("abc".chars());
+    ///
+    /// it.next();
+    /// it.next();
+    /// let (before, after) = it.peek_both();
+    /// assert_eq!((Some('a'), Some('c')), (before, after));
+    /// assert_eq!(Some('c'), it.next());
+    /// ```
+    pub fn peek_both(&mut self) -> (Option<I::Item>, Option<I::Item>) {
+        (self.prev(), self.peek().cloned())
+    }
+
+    /// Returns the element `next()` returned most recently. Once the end has been reached that's
+    /// the previous element, because `next()` moves the last element there.
+    pub(crate) fn last_returned(&self) -> Option<&I::Item> {
+        if self.finished {
+            self.storage.prev.as_ref()
+        } else {
+            self.current.as_ref()
+        }
+    }
+}
+
+impl<I, S> PrevPeekableWith<I, S>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+    S: PrevStorage<I::Item>,
+{
+    /// Creates a new `PrevPeekableWith` which keeps the previous element in `storage`. It takes an
+    /// [`Iterator`] whose `Item` implements [`Clone`].
+    ///
+    /// [`Iterator`]: https://doc.rust-lang.org/nightly/core/iter/iterator/trait.Iterator.html
+    /// [`Clone`]: https://doc.rust-lang.org/nightly/core/clone/trait.Clone.html
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{PrevPeekableWith, RcStorage};
+    /// use std::rc::Rc;
+    ///
+    /// let v = vec![1, 2, 3];
+    /// let mut iter = PrevPeekableWith::with_storage(v.iter(), RcStorage::default());
+    ///
+    /// assert_eq!(Some(&1), iter.next());
+    /// assert_eq!(Some(&2), iter.next());
+    /// assert_eq!(Some(Rc::new(&1)), iter.prev());
+    /// ```
+    pub fn with_storage(iterator: I, storage: S) -> Self {
+        PrevPeekableWith {
+            source: Lookahead::new(iterator),
+            storage,
             current: None,
             finished: false,
+            lent: false,
+            journal: None,
         }
     }
 
@@ -99,13 +317,94 @@ where
     /// assert_eq!(iter.next(), None);
     /// ```
     pub fn peek(&mut self) -> Option<&I::Item> {
-        self.iterator.peek()
+        self.source.peek()
+    }
+
+    /// Returns a reference to the element `n` places after the next one without advancing the
+    /// iterator, so `peek_nth(0)` is the same as `peek()`. Everything up to that element is
+    /// buffered, and returns `None` if the iterator ends before it.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 2, 3];
+    /// let mut it = PrevPeekable::new(v.iter());
+    ///
+    /// assert_eq!(Some(&&3), it.peek_nth(2));
+    /// assert_eq!(Some(&&1), it.peek_nth(0));
+    /// assert_eq!(None, it.peek_nth(3));
+    ///
+    /// // Looking ahead doesn't advance the iterator
+    /// assert_eq!(Some(&1), it.next());
+    /// assert_eq!(Some(&&3), it.peek_nth(1));
+    /// ```
+    pub fn peek_nth(&mut self, n: usize) -> Option<&I::Item> {
+        self.source.peek_nth(n)
+    }
+
+    /// Returns a clone of the next element without advancing the iterator, or the default value
+    /// once there are no elements left. For numeric streams, that treats everything after the end
+    /// as zero.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new(vec![3, 5].into_iter());
+    ///
+    /// assert_eq!(3, it.peek_or_default());
+    /// it.next();
+    /// it.next();
+    /// assert_eq!(0, it.peek_or_default());
+    /// ```
+    pub fn peek_or_default(&mut self) -> I::Item
+    where
+        I::Item: Default,
+    {
+        self.peek().cloned().unwrap_or_default()
+    }
+
+    /// Returns whether at least `n` more elements can be returned by `next()`, buffering as many
+    /// elements as needed to find out.
+    ///
+    /// This lets a parser check that there are enough elements left before attempting to match a
+    /// rule that spans several of them.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 2, 3];
+    /// let mut it = PrevPeekable::new(v.iter());
+    ///
+    /// assert!(it.has_lookahead(3));
+    /// assert!(!it.has_lookahead(4));
+    ///
+    /// it.next();
+    /// assert!(it.has_lookahead(2));
+    /// assert!(!it.has_lookahead(3));
+    /// ```
+    pub fn has_lookahead(&mut self, n: usize) -> bool {
+        n == 0 || self.source.peek_nth(n - 1).is_some()
     }
 
     /// Returns the previous value in the iterator without moving the iterator backwards.
     /// When the end is reached, it will always return the last element.
     ///
-    /// This function performs a `clone()` when returning the data.
+    /// This function performs a `clone()` when returning the data. What's returned is whatever the
+    /// storage kept from the previous element; for [`PrevPeekable`] that's the element itself.
+    ///
+    /// [`PrevPeekable`]: type.PrevPeekable.html
     ///
     /// # Examples
     ///
@@ -133,14 +432,17 @@ where
     /// assert_eq!(None, it.next());
     /// assert_eq!(Some(&2), it.prev());
     /// ```
-    pub fn prev(&self) -> Option<I::Item> {
-        self.prev.clone()
+    pub fn prev(&self) -> Option<S::Stored>
+    where
+        S::Stored: Clone,
+    {
+        self.storage.get().cloned()
     }
 
     /// Returns a reference to the previous value in the iterator without moving the iterator
     /// backwards. When the end is reached, it will always return the last element.
     ///
-    /// Because `prev_peek()` returns a reference, and many iterators iterate over
+    /// Because `peek_prev()` returns a reference, and many iterators iterate over
     /// references, there can be a possibly confusing situation where the
     /// return value is a double reference. You can see this effect in the
     /// examples below.
@@ -156,40 +458,300 @@ where
     /// let mut it = PrevPeekable::new(v.iter());
     ///
     /// // Initially there is nothing to peek at
-    /// assert_eq!(None, it.prev_peek());
+    /// assert_eq!(None, it.peek_prev());
     /// assert_eq!(Some(&1), it.next());
     ///
     /// // There is nothing before the first element
-    /// assert_eq!(None, it.prev_peek());
+    /// assert_eq!(None, it.peek_prev());
     /// assert_eq!(Some(&2), it.next());
     ///
     /// // 1 comes before 2
-    /// assert_eq!(Some(&&1), it.prev_peek());
+    /// assert_eq!(Some(&&1), it.peek_prev());
     /// assert_eq!(None, it.next());
     ///
     /// // 2 will always be returned as the last element
-    /// assert_eq!(Some(&&2), it.prev_peek());
+    /// assert_eq!(Some(&&2), it.peek_prev());
+    /// ```
+    pub fn peek_prev(&self) -> Option<&S::Stored> {
+        self.storage.get()
+    }
+
+    /// Returns a reference to the element last returned by `next()`, or `None` before the first
+    /// element has been returned and once the end has been reached.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 2];
+    /// let mut it = PrevPeekable::new(v.iter());
+    ///
+    /// assert_eq!(None, it.current());
+    /// assert_eq!(Some(&1), it.next());
+    /// assert_eq!(Some(&&1), it.current());
+    /// assert_eq!(Some(&2), it.next());
+    /// assert_eq!(Some(&&2), it.current());
+    ///
+    /// // After the end, the last element is the previous one instead
+    /// assert_eq!(None, it.next());
+    /// assert_eq!(None, it.current());
+    /// assert_eq!(Some(&&2), it.peek_prev());
+    /// ```
+    pub fn current(&self) -> Option<&I::Item> {
+        self.current.as_ref()
+    }
+
+    /// Returns a reference to the previous value in the iterator without moving the iterator
+    /// backwards.
+    ///
+    /// This is the old name of [`peek_prev`], which is consistent with the naming in std.
+    ///
+    /// [`peek_prev`]: #method.peek_prev
+    #[deprecated(since = "0.3.0", note = "renamed to `peek_prev()`")]
+    pub fn prev_peek(&self) -> Option<&S::Stored> {
+        self.peek_prev()
+    }
+
+    /// Replaces the underlying iterator with `new_iter` and returns the old one, including any
+    /// element it had peeked at. The previous and current elements are kept, so the first element
+    /// of the new iterator has the last element returned from the old one as its previous element.
+    ///
+    /// Elements that [`peek_nth`] or [`has_lookahead`] buffered have already been taken out of the
+    /// old iterator, so they stay behind and are returned before the elements of `new_iter`.
+    ///
+    /// This is useful for splicing input sources together, e.g. expanding an include file in a
+    /// lexer.
+    ///
+    /// [`peek_nth`]: #method.peek_nth
+    /// [`has_lookahead`]: #method.has_lookahead
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let main = vec![1, 2, 3];
+    /// let include = vec![10, 11];
+    /// let mut it = PrevPeekable::new(main.into_iter());
+    ///
+    /// assert_eq!(Some(1), it.next());
+    /// assert_eq!(Some(2), it.next());
+    ///
+    /// let mut rest = it.replace_source(include.into_iter());
+    ///
+    /// // 2 is still the previous element when the first element of the new source is returned
+    /// assert_eq!(Some(10), it.next());
+    /// assert_eq!(Some(2), it.prev());
+    ///
+    /// // The old source can be picked up again later
+    /// assert_eq!(Some(3), rest.next());
+    /// ```
+    pub fn replace_source(&mut self, new_iter: I) -> Peekable<I> {
+        // The new iterator may have elements even if the old one ran out
+        self.finished = false;
+        self.journal = None;
+        self.source.peeked = false;
+        std::mem::replace(&mut self.source.iterator, new_iter.peekable())
+    }
+
+    /// Drops every element that was pulled out of the underlying iterator but not returned yet,
+    /// such as one [`peek`] looked at or ones [`peek_nth`] buffered, so the next call to `peek()`
+    /// or `next()` reads from the underlying iterator again. The previous and current elements
+    /// are kept.
+    ///
+    /// This is for sources whose contents can change behind the iterator's back, such as a
+    /// [`try_from_receiver`] that peeked at "no message yet" before a message arrived, or a source
+    /// that shares a queue with a producer. It also forgets that `peek()` saw the end, so a source
+    /// that has more elements now is read again. Elements put back by [`rewind_to`] are kept, since
+    /// they were already returned once.
+    ///
+    /// [`peek`]: #method.peek
+    /// [`peek_nth`]: #method.peek_nth
+    /// [`try_from_receiver`]: #method.try_from_receiver
+    /// [`rewind_to`]: #method.rewind_to
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    /// use std::sync::mpsc;
+    ///
+    /// let (tx, rx) = mpsc::channel();
+    /// let mut it = PrevPeekable::try_from_receiver(rx);
+    ///
+    /// assert_eq!(Some(&None), it.peek());
+    /// tx.send(1).unwrap();
+    /// // The peeked "no message yet" is stale, so look again
+    /// it.invalidate_peek();
+    /// assert_eq!(Some(&Some(1)), it.peek());
+    /// ```
+    pub fn invalidate_peek(&mut self) {
+        self.source.discard();
+    }
+
+    /// Advances the iterator like `next()`, but swaps the next element into `buf` instead of
+    /// returning a clone of it. Returns whether there was a next element; if there wasn't, `buf` is
+    /// left alone.
+    ///
+    /// The old contents of `buf` are taken to be the element returned before, and are moved into
+    /// the storage, so [`prev`] keeps returning the element before the one in `buf`. Reading owned
+    /// buffers such as `String`s this way makes no clones, so the allocations of the elements are
+    /// recycled instead of copied. On the first call the old contents of `buf` are dropped instead,
+    /// since nothing was returned before them.
+    ///
+    /// The element in `buf` belongs to the caller, so [`current`] doesn't see it, and if you switch
+    /// back to `next()` it won't become the previous element.
+    ///
+    /// [`prev`]: #method.prev
+    /// [`current`]: #method.current
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let lines = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    /// let mut it = PrevPeekable::new(lines.into_iter());
+    /// let mut line = String::new();
+    ///
+    /// assert!(it.next_reuse(&mut line));
+    /// assert_eq!("a", line);
+    /// assert!(it.next_reuse(&mut line));
+    /// assert_eq!("b", line);
+    /// assert_eq!(Some("a".to_string()), it.prev());
+    ///
+    /// assert!(it.next_reuse(&mut line));
+    /// assert!(!it.next_reuse(&mut line));
+    /// assert_eq!("c", line);
+    /// assert_eq!(Some("b".to_string()), it.prev());
+    /// ```
+    pub fn next_reuse(&mut self, buf: &mut I::Item) -> bool {
+        let next = match self.source.next() {
+            Some(next) => next,
+            None => return false,
+        };
+        if let Some(ref mut journal) = self.journal {
+            journal.record(next.clone());
+        }
+        let old = std::mem::replace(buf, next);
+
+        // The element returned before is wherever the last call left it
+        if let Some(cur) = self.current.take() {
+            self.storage.store_owned(cur);
+        } else if self.lent {
+            self.storage.store_owned(old);
+        }
+        self.lent = true;
+
+        true
+    }
+
+    /// Consumes the `PrevPeekable` and returns the [`Peekable`] it wraps, dropping the previous
+    /// element. An element that `peek()` looked at is kept, so it's the next one returned.
+    ///
+    /// [`Peekable`]: https://doc.rust-lang.org/nightly/core/iter/struct.Peekable.html
+    ///
+    /// # Panics
+    ///
+    /// A `Peekable` can only hold one element it has looked at, so this panics if [`peek_nth`] or
+    /// [`has_lookahead`] buffered elements that haven't been returned yet. It also panics after
+    /// [`rewind_to`] until the elements it put back have been returned again, since they're held
+    /// the same way. Use [`into_fresh`] to keep such elements instead.
+    ///
+    /// [`peek_nth`]: #method.peek_nth
+    /// [`has_lookahead`]: #method.has_lookahead
+    /// [`rewind_to`]: #method.rewind_to
+    /// [`into_fresh`]: #method.into_fresh
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 2, 3];
+    /// let mut it = PrevPeekable::new(v.into_iter());
+    ///
+    /// assert_eq!(Some(1), it.next());
+    /// assert_eq!(Some(&2), it.peek());
+    ///
+    /// let mut rest = it.into_std_peekable();
+    /// assert_eq!(Some(&2), rest.peek());
+    /// assert_eq!(vec![2, 3], rest.collect::<Vec<_>>());
+    /// ```
+    pub fn into_std_peekable(self) -> Peekable<I> {
+        assert!(
+            self.source.buffer.is_empty(),
+            "elements buffered by peek_nth() or put back by rewind_to() can't be moved into a Peekable"
+        );
+
+        self.source.iterator
+    }
+
+    /// Consumes the `PrevPeekable` and returns a new one over the rest of the elements, with no
+    /// previous or current element. Unlike [`into_std_peekable`], elements buffered by
+    /// [`peek_nth`] or [`has_lookahead`] are kept, and are returned first.
+    ///
+    /// This is handy for handing the rest of the input to a sub-parser that expects to start from
+    /// the beginning.
+    ///
+    /// [`into_std_peekable`]: #method.into_std_peekable
+    /// [`peek_nth`]: #method.peek_nth
+    /// [`has_lookahead`]: #method.has_lookahead
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
     /// ```
-    pub fn prev_peek(&self) -> Option<&I::Item> {
-        self.prev.as_ref()
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 2, 3, 4];
+    /// let mut it = PrevPeekable::new(v.into_iter());
+    ///
+    /// assert_eq!(Some(1), it.next());
+    /// assert_eq!(Some(&3), it.peek_nth(1));
+    ///
+    /// let mut rest = it.into_fresh();
+    /// assert_eq!(None, rest.current());
+    /// assert_eq!(Some(2), rest.next());
+    /// assert_eq!(None, rest.prev());
+    /// assert_eq!(vec![3, 4], rest.collect::<Vec<_>>());
+    /// ```
+    pub fn into_fresh(self) -> PrevPeekable<impl Iterator<Item = I::Item>> {
+        PrevPeekable::new(self.source.buffer.into_iter().chain(self.source.iterator))
     }
 }
 
-impl<I> Iterator for PrevPeekable<I>
+impl<I, S> Iterator for PrevPeekableWith<I, S>
 where
     I: Iterator,
     <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+    S: PrevStorage<I::Item>,
 {
     type Item = I::Item;
 
     fn next(&mut self) -> Option<I::Item> {
-        // If self.iterator.peek() is None, we've reached the end, and self.prev should
-        // the second last element
-        if self.iterator.peek().is_some() {
-            self.prev = std::mem::replace(&mut self.current, self.iterator.next());
+        // If self.source.peek() is None, we've reached the end, and the storage should hold
+        // the last element
+        if self.source.peek().is_some() {
+            self.advance();
+            if let Some(ref mut journal) = self.journal {
+                journal.record(self.current.clone().expect("an element was just returned"));
+            }
             return self.current.clone();
         } else if !self.finished {
-            self.prev = std::mem::replace(&mut self.current, self.iterator.next());
+            self.advance();
             self.finished = true;
         }
 
@@ -197,9 +759,25 @@ where
     }
 }
 
+impl<I, S> PrevPeekableWith<I, S>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+    S: PrevStorage<I::Item>,
+{
+    /// Moves the next element into `current`, handing the old current element to the storage.
+    fn advance(&mut self) {
+        let next = self.source.next();
+        if let Some(old) = std::mem::replace(&mut self.current, next) {
+            self.storage.store_owned(old);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_util::{CloneCounter, RecordingIterator};
 
     macro_rules! iter {
         ($v: expr) => {{
@@ -209,22 +787,22 @@ mod tests {
 
     #[test]
     fn test_next() {
-        let v = vec![1, 2, 3];
+        let v = [1, 2, 3];
         let mut iter = iter!(v);
 
         assert_eq!(Some(&1), iter.next());
-        assert_eq!(None, iter.prev);
+        assert_eq!(None, iter.storage.prev);
         assert_eq!(Some(&2), iter.next());
-        assert_eq!(Some(&1), iter.prev);
+        assert_eq!(Some(&1), iter.storage.prev);
         assert_eq!(Some(&3), iter.next());
-        assert_eq!(Some(&2), iter.prev);
+        assert_eq!(Some(&2), iter.storage.prev);
         assert_eq!(None, iter.next());
         assert_eq!(None, iter.next());
     }
 
     #[test]
     fn test_peek() {
-        let v = vec![1, 2];
+        let v = [1, 2];
         let mut iter = iter!(v);
 
         assert_eq!(Some(&&1), iter.peek());
@@ -236,7 +814,7 @@ mod tests {
 
     #[test]
     fn test_prev() {
-        let v = vec![1, 2];
+        let v = [1, 2];
         let mut it = iter!(v);
 
         assert_eq!(None, it.prev());
@@ -252,19 +830,495 @@ mod tests {
     }
 
     #[test]
-    fn test_prev_peek() {
-        let v = vec![1, 2];
+    fn test_peek_prev() {
+        let v = [1, 2];
         let mut it = iter!(v);
 
-        assert_eq!(None, it.prev_peek());
+        assert_eq!(None, it.peek_prev());
         assert_eq!(Some(&1), it.next());
-        assert_eq!(None, it.prev_peek());
+        assert_eq!(None, it.peek_prev());
         assert_eq!(Some(&2), it.next());
-        assert_eq!(Some(&&1), it.prev_peek());
+        assert_eq!(Some(&&1), it.peek_prev());
         assert_eq!(None, it.next());
-        assert_eq!(Some(&&2), it.prev_peek());
+        assert_eq!(Some(&&2), it.peek_prev());
+
+        assert_eq!(None, it.next());
+        assert_eq!(Some(&&2), it.peek_prev());
+    }
+
+    /// Takes a `PrevPeekable` the same way code written before `PrevPeekableWith` existed would.
+    fn prev_sum<I>(mut it: PrevPeekable<I>) -> i32
+    where
+        I: Iterator<Item = i32>,
+    {
+        let mut sum = 0;
+        while it.next().is_some() {
+            sum += it.prev().unwrap_or(0);
+        }
+
+        sum
+    }
+
+    #[test]
+    fn test_alias_is_drop_in_replacement() {
+        struct Holder<'a> {
+            it: PrevPeekable<::std::slice::Iter<'a, i32>>,
+        }
+
+        let v = [1, 2, 3];
+        let mut holder = Holder {
+            it: PrevPeekable::new(v.iter()),
+        };
+
+        assert_eq!(Some(&1), holder.it.next());
+        assert_eq!(Some(&&2), holder.it.peek());
+        assert!(format!("{:?}", holder.it).starts_with("PrevPeekableWith"));
+
+        // The first element has no predecessor, so this is 1 + 2
+        assert_eq!(3, prev_sum(PrevPeekable::new(v.iter().cloned())));
+    }
+
+    #[test]
+    fn test_replace_source() {
+        let a = [1, 2, 3];
+        let b = [4, 5];
+        let mut it = iter!(a);
+
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(Some(&&2), it.peek());
+
+        let mut old = it.replace_source(b.iter());
+        assert_eq!(Some(&1), it.current);
+        assert_eq!(None, it.prev());
+
+        assert_eq!(Some(&4), it.next());
+        assert_eq!(Some(&1), it.prev());
+        assert_eq!(Some(&5), it.next());
+        assert_eq!(Some(&4), it.prev());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(&5), it.prev());
+
+        // The old source keeps the element it had peeked at
+        assert_eq!(Some(&&2), old.peek());
+        assert_eq!(vec![&2, &3], old.collect::<Vec<_>>());
+    }
 
+    #[test]
+    fn test_replace_source_after_end() {
+        let a = [1];
+        let b = [2];
+        let mut it = iter!(a);
+
+        assert_eq!(Some(&1), it.next());
         assert_eq!(None, it.next());
+        assert_eq!(Some(&1), it.prev());
+
+        let _ = it.replace_source(b.iter());
+        assert_eq!(Some(&2), it.next());
+        assert_eq!(Some(&1), it.prev());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(&2), it.prev());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_prev_peek_shim() {
+        let v = [1, 2];
+        let mut it = iter!(v);
+        let mut renamed = iter!(v);
+
+        loop {
+            assert_eq!(renamed.peek_prev(), it.prev_peek());
+
+            let next = it.next();
+            assert_eq!(renamed.next(), next);
+            if next.is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(renamed.peek_prev(), it.prev_peek());
         assert_eq!(Some(&&2), it.prev_peek());
     }
+
+    #[test]
+    fn test_peek_nth() {
+        let v = [1, 2, 3];
+        let mut it = iter!(v);
+
+        assert_eq!(Some(&&2), it.peek_nth(1));
+        assert_eq!(Some(&&1), it.peek());
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(Some(&&3), it.peek_nth(1));
+        assert_eq!(None, it.peek_nth(2));
+        assert_eq!(Some(&2), it.next());
+        assert_eq!(Some(&1), it.prev());
+        assert_eq!(Some(&3), it.next());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(&3), it.prev());
+    }
+
+    #[test]
+    fn test_has_lookahead() {
+        let v = [1, 2, 3, 4];
+        let mut it = iter!(v);
+
+        assert!(it.has_lookahead(0));
+        assert!(it.has_lookahead(4));
+        assert!(!it.has_lookahead(5));
+
+        it.next();
+        it.next();
+        assert!(it.has_lookahead(2));
+        assert!(!it.has_lookahead(3));
+        // Buffering doesn't change what's returned
+        assert_eq!(Some(&3), it.next());
+        assert_eq!(Some(&2), it.prev());
+
+        it.next();
+        assert!(it.has_lookahead(0));
+        assert!(!it.has_lookahead(1));
+        assert_eq!(None, it.next());
+        assert_eq!(Some(&4), it.prev());
+    }
+
+    #[test]
+    fn test_replace_source_keeps_buffered_lookahead() {
+        let a = [1, 2, 3];
+        let b = [4];
+        let mut it = iter!(a);
+
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(Some(&&3), it.peek_nth(1));
+
+        let mut old = it.replace_source(b.iter());
+        assert_eq!(None, old.next());
+        assert_eq!(vec![&2, &3, &4], it.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_invalidate_peek() {
+        use std::cell::RefCell;
+        use std::collections::VecDeque;
+        use std::rc::Rc;
+
+        let queue = Rc::new(RefCell::new(VecDeque::from(vec![1, 2, 3])));
+        let source = {
+            let queue = Rc::clone(&queue);
+            ::std::iter::from_fn(move || queue.borrow_mut().pop_front())
+        };
+        let mut it = PrevPeekable::new(source);
+
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Some(&3), it.peek_nth(1));
+
+        // 2 and 3 were already taken out of the queue, so replacing its contents doesn't affect them
+        *queue.borrow_mut() = VecDeque::from(vec![20, 30]);
+        assert_eq!(Some(&2), it.peek());
+
+        it.invalidate_peek();
+        assert_eq!(Some(&20), it.peek());
+        assert_eq!(Some(20), it.next());
+        assert_eq!(Some(1), it.prev());
+        assert_eq!(Some(30), it.next());
+        assert_eq!(Some(20), it.prev());
+    }
+
+    #[test]
+    fn test_invalidate_peek_after_end() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let queue = Rc::new(RefCell::new(vec![1]));
+        let source = {
+            let queue = Rc::clone(&queue);
+            ::std::iter::from_fn(move || queue.borrow_mut().pop())
+        };
+        let mut it = PrevPeekable::new(source);
+
+        assert_eq!(Some(1), it.next());
+        assert_eq!(None, it.peek());
+        // Peekable remembers that it saw the end
+        queue.borrow_mut().push(2);
+        assert_eq!(None, it.peek());
+
+        it.invalidate_peek();
+        assert_eq!(Some(&2), it.peek());
+        assert_eq!(Some(2), it.next());
+        assert_eq!(Some(1), it.prev());
+
+        // Nothing was peeked at, so nothing is dropped
+        queue.borrow_mut().push(3);
+        it.invalidate_peek();
+        assert_eq!(Some(3), it.next());
+    }
+
+    #[test]
+    fn test_invalidate_peek_after_rewind() {
+        let v = [1, 2, 3, 4];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(Some(1), it.next());
+        let cp = it.checkpoint();
+        assert_eq!(Some(2), it.next());
+        assert_eq!(Some(3), it.next());
+        assert_eq!(Ok(()), it.rewind_to(cp));
+
+        // 2 and 3 are replayed, but 4 was only looked at, so it's dropped
+        assert_eq!(Some(&4), it.peek_nth(2));
+        it.invalidate_peek();
+        assert_eq!(Some(&2), it.peek());
+        assert_eq!(vec![2, 3], it.by_ref().collect::<Vec<_>>());
+        assert_eq!(Some(3), it.prev());
+    }
+
+    #[test]
+    fn test_peek_nth_reads_only_as_needed() {
+        let source = RecordingIterator::new(1..10);
+        let recording = source.recording();
+        let mut it = PrevPeekable::new(source);
+
+        assert_eq!(Some(&3), it.peek_nth(2));
+        recording.assert_next_calls(3);
+        assert!(it.has_lookahead(3));
+        recording.assert_next_calls(3);
+        assert_eq!(Some(1), it.next());
+        recording.assert_next_calls(3);
+    }
+
+    #[test]
+    fn test_current() {
+        let v = [1, 2];
+        let mut it = iter!(v);
+
+        assert_eq!(None, it.current());
+        assert_eq!(Some(&&1), it.peek());
+        assert_eq!(None, it.current());
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(Some(&&1), it.current());
+        assert_eq!(Some(&2), it.next());
+        assert_eq!(Some(&&2), it.current());
+        assert_eq!(None, it.next());
+        assert_eq!(None, it.current());
+        assert_eq!(None, it.next());
+        assert_eq!(None, it.current());
+    }
+
+    #[test]
+    fn test_next_reuse() {
+        let v = [1, 2, 3];
+        let mut it = iter!(v);
+        let mut buf = &0;
+
+        assert!(it.next_reuse(&mut buf));
+        assert_eq!(&1, buf);
+        // The initial contents of the buffer aren't an element
+        assert_eq!(None, it.prev());
+        assert!(it.next_reuse(&mut buf));
+        assert_eq!(&2, buf);
+        assert_eq!(Some(&1), it.prev());
+        assert_eq!(Some(&&3), it.peek());
+        assert!(it.next_reuse(&mut buf));
+        assert!(!it.next_reuse(&mut buf));
+        assert_eq!(&3, buf);
+        assert_eq!(Some(&2), it.prev());
+    }
+
+    #[test]
+    fn test_next_reuse_after_next() {
+        let v = [1, 2, 3];
+        let mut it = iter!(v);
+        let mut buf = &0;
+
+        assert_eq!(Some(&1), it.next());
+        assert!(it.next_reuse(&mut buf));
+        assert_eq!(&2, buf);
+        assert_eq!(Some(&1), it.prev());
+        assert_eq!(None, it.current());
+    }
+
+    #[test]
+    fn test_next_reuse_recycles_allocations() {
+        let lines = CloneCounter::wrap_all(vec![String::from("first"), String::from("second")]);
+        let counter = lines[0].sibling(String::new());
+        let mut it = PrevPeekable::new(lines.into_iter());
+        let mut buf = counter.sibling(String::new());
+
+        assert!(it.next_reuse(&mut buf));
+        let first = buf.value().as_ptr();
+        assert!(it.next_reuse(&mut buf));
+
+        // The buffer holding the first line was moved into the storage rather than cloned
+        assert_eq!("second", buf.value());
+        assert_eq!(first, it.peek_prev().unwrap().value().as_ptr());
+        counter.assert_clones(0);
+    }
+
+    #[test]
+    fn test_into_std_peekable() {
+        let v = [1, 2, 3, 4];
+        let mut it = iter!(v);
+
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(Some(&&2), it.peek());
+
+        let mut rest = it.into_std_peekable();
+        assert_eq!(Some(&&2), rest.peek());
+        assert_eq!(vec![&2, &3, &4], rest.collect::<Vec<_>>());
+
+        // Once buffered lookahead has been returned it's fine to convert
+        let mut it = iter!(v);
+        assert_eq!(Some(&&2), it.peek_nth(1));
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(Some(&2), it.next());
+        assert_eq!(vec![&3, &4], it.into_std_peekable().collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic(expected = "elements buffered by peek_nth() or put back by rewind_to() can't be moved")]
+    fn test_into_std_peekable_with_buffered_lookahead() {
+        let v = [1, 2, 3];
+        let mut it = iter!(v);
+
+        it.peek_nth(1);
+        let _ = it.into_std_peekable();
+    }
+
+    #[test]
+    #[should_panic(expected = "elements buffered by peek_nth() or put back by rewind_to() can't be moved")]
+    fn test_into_std_peekable_after_rewind() {
+        let v = [1, 2, 3];
+        let mut it = iter!(v);
+
+        let cp = it.checkpoint();
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(Ok(()), it.rewind_to(cp));
+        let _ = it.into_std_peekable();
+    }
+
+    #[test]
+    fn test_into_std_peekable_after_replay() {
+        let v = [1, 2, 3];
+        let mut it = iter!(v);
+
+        let cp = it.checkpoint();
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(Ok(()), it.rewind_to(cp));
+        // Once the replayed element has been returned again, nothing is held back
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(vec![&2, &3], it.into_std_peekable().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_set_prev() {
+        let v = [1, 2, 3];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Some(2), it.next());
+        it.set_prev(Some(10));
+        assert_eq!(Some(10), it.prev());
+        assert_eq!(Some(&2), it.current());
+        assert_eq!(Some(&3), it.peek());
+
+        // Advancing goes back to tracking the real previous element
+        assert_eq!(Some(3), it.next());
+        assert_eq!(Some(2), it.prev());
+
+        it.set_prev(None);
+        assert_eq!(None, it.peek_prev());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(3), it.prev());
+    }
+
+    #[test]
+    fn test_prev_current_slice() {
+        let v = [1, 2, 3];
+        let mut it = iter!(v);
+
+        assert!(it.prev_current_slice().is_empty());
+        it.next();
+        assert_eq!(vec![&&1], it.prev_current_slice());
+        it.next();
+        assert_eq!(vec![&&1, &&2], it.prev_current_slice());
+        it.next();
+        assert_eq!(vec![&&2, &&3], it.prev_current_slice());
+        it.next();
+        assert_eq!(vec![&&3], it.prev_current_slice());
+        it.next();
+        assert_eq!(vec![&&3], it.prev_current_slice());
+
+        let mut empty = PrevPeekable::new(::std::iter::empty::<i32>());
+        assert_eq!(None, empty.next());
+        assert!(empty.prev_current_slice().is_empty());
+    }
+
+    #[test]
+    fn test_into_fresh() {
+        let v = [1, 2, 3, 4, 5, 6];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Some(2), it.next());
+        assert_eq!(Some(&5), it.peek_nth(2));
+
+        let mut fresh = it.into_fresh();
+        assert_eq!(None, fresh.prev());
+        assert_eq!(None, fresh.current());
+        assert_eq!(Some(&3), fresh.peek());
+        assert_eq!(Some(3), fresh.next());
+        assert_eq!(None, fresh.prev());
+        assert_eq!(Some(4), fresh.next());
+        assert_eq!(Some(3), fresh.prev());
+        assert_eq!(vec![5, 6], fresh.by_ref().collect::<Vec<_>>());
+        assert_eq!(Some(6), fresh.prev());
+    }
+
+    #[test]
+    fn test_into_fresh_after_peek() {
+        // Only peek() was used, so the element is held by the source rather than the buffer
+        let source = RecordingIterator::new(1..4);
+        let recording = source.recording();
+        let mut it = PrevPeekable::new(source);
+        assert_eq!(Some(&1), it.peek());
+
+        let fresh = it.into_fresh();
+        assert_eq!(vec![1, 2, 3], fresh.collect::<Vec<_>>());
+        recording.assert_yielded(&[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_peek_or_default() {
+        let v = [1.5, 2.5];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(1.5, it.peek_or_default());
+        assert_eq!(Some(1.5), it.next());
+        assert_eq!(2.5, it.peek_or_default());
+        // Peeking doesn't advance
+        assert_eq!(2.5, it.peek_or_default());
+        assert_eq!(Some(2.5), it.next());
+        assert_eq!(0.0, it.peek_or_default());
+        assert_eq!(None, it.next());
+        assert_eq!(0.0, it.peek_or_default());
+
+        let mut words = PrevPeekable::new(::std::iter::empty::<String>());
+        assert_eq!(String::new(), words.peek_or_default());
+    }
+
+    #[test]
+    fn test_peek_both() {
+        let v = [1, 2, 3];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!((None, Some(1)), it.peek_both());
+        for _ in 0..v.len() + 1 {
+            let (prev, next) = it.peek_both();
+            assert_eq!(it.prev(), prev);
+            assert_eq!(it.peek().cloned(), next);
+            // Neither is a borrow of the iterator, so it can still be advanced
+            assert_eq!(next, it.next());
+        }
+        assert_eq!((Some(3), None), it.peek_both());
+    }
 }
@@ -0,0 +1,112 @@
+//! The source of elements, with room to look more than one element ahead.
+
+use std::collections::VecDeque;
+use std::iter::Peekable;
+
+/// Wraps the underlying iterator so that any number of elements can be peeked at. Elements that
+/// were pulled out of `iterator` to look past the next one wait in `buffer`, and are returned before
+/// anything left in `iterator`.
+#[derive(Debug, Clone)]
+pub(crate) struct Lookahead<I>
+where
+    I: Iterator,
+{
+    pub(crate) buffer: VecDeque<I::Item>,
+    /// How many elements at the front of `buffer` were put back by `replay()` rather than pulled
+    /// out of `iterator` to look ahead
+    replayed: usize,
+    pub(crate) iterator: Peekable<I>,
+    /// Whether `iterator` holds what was peeked at, which may be the end of input, since `Peekable`
+    /// can't tell
+    pub(crate) peeked: bool,
+}
+
+impl<I> Lookahead<I>
+where
+    I: Iterator,
+{
+    pub(crate) fn new(iterator: I) -> Self {
+        Lookahead {
+            buffer: VecDeque::new(),
+            replayed: 0,
+            iterator: iterator.peekable(),
+            peeked: false,
+        }
+    }
+
+    pub(crate) fn next(&mut self) -> Option<I::Item> {
+        match self.buffer.pop_front() {
+            Some(item) => {
+                self.replayed = self.replayed.saturating_sub(1);
+                Some(item)
+            }
+            None => {
+                self.peeked = false;
+                self.iterator.next()
+            }
+        }
+    }
+
+    pub(crate) fn peek(&mut self) -> Option<&I::Item> {
+        match self.buffer.front() {
+            Some(item) => Some(item),
+            None => {
+                self.peeked = true;
+                self.iterator.peek()
+            }
+        }
+    }
+
+    /// Returns a reference to the element `n` places after the next one, buffering everything in
+    /// between.
+    pub(crate) fn peek_nth(&mut self, n: usize) -> Option<&I::Item> {
+        if n == 0 {
+            return self.peek();
+        }
+
+        self.peeked = false;
+        while self.buffer.len() <= n {
+            match self.iterator.next() {
+                Some(item) => self.buffer.push_back(item),
+                None => return None,
+            }
+        }
+
+        self.buffer.get(n)
+    }
+
+    /// Returns how many elements were pulled out of `iterator` and haven't been returned yet, along
+    /// with the first of them.
+    pub(crate) fn pending(&mut self) -> (usize, Option<&I::Item>) {
+        let held = self.peeked && self.iterator.peek().is_some();
+        let count = self.buffer.len() + held as usize;
+        if count == 0 {
+            return (0, None);
+        }
+
+        (count, self.peek())
+    }
+
+    /// Puts `items`, which were returned before, back in front of everything else, so they're
+    /// returned again in the same order.
+    pub(crate) fn replay<J>(&mut self, items: J)
+    where
+        J: DoubleEndedIterator<Item = I::Item>,
+    {
+        for item in items.rev() {
+            self.buffer.push_front(item);
+            self.replayed += 1;
+        }
+    }
+
+    /// Drops everything that was pulled out of `iterator` and hasn't been returned yet, including
+    /// a peeked end of input, so the next call reads from `iterator` again. Elements put back by
+    /// `replay()` are kept, since they were already returned once.
+    pub(crate) fn discard(&mut self) {
+        self.buffer.truncate(self.replayed);
+        if self.peeked {
+            self.iterator.next();
+            self.peeked = false;
+        }
+    }
+}
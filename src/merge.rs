@@ -0,0 +1,272 @@
+//! Merging sorted iterators into one `PrevPeekable`, and aligning them with each other.
+
+use super::PrevPeekable;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// Merges `sources`, each sorted in non-decreasing order, into one sorted `PrevPeekable`. Since
+/// the merged output is a single iterator, [`prev`] returns the previously merged element no matter
+/// which source it came from, which makes it easy to check the output is sorted or to drop
+/// duplicates that span sources.
+///
+/// The next element of every source is kept in a binary heap, so each step takes `O(log k)` for `k`
+/// sources. Equal elements are returned in the order of their sources.
+///
+/// [`prev`]: struct.PrevPeekableWith.html#method.prev
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use prev_iter::kmerge_with_prev;
+///
+/// let shards = vec![vec![1, 4, 7], vec![2, 4], vec![3, 8]];
+/// let mut merged = kmerge_with_prev(shards.into_iter().map(Vec::into_iter));
+///
+/// let mut unique = Vec::new();
+/// while let Some(x) = merged.next() {
+///     if merged.prev() != Some(x) {
+///         unique.push(x);
+///     }
+/// }
+///
+/// assert_eq!(vec![1, 2, 3, 4, 7, 8], unique);
+/// ```
+pub fn kmerge_with_prev<S, I>(sources: S) -> PrevPeekable<KMerge<I>>
+where
+    S: IntoIterator<Item = I>,
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::cmp::Ord + ::std::clone::Clone,
+{
+    let mut sources: Vec<I> = sources.into_iter().collect();
+    let heads = sources
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(index, source)| source.next().map(|head| Reverse((head, index))))
+        .collect();
+
+    PrevPeekable::new(KMerge { sources, heads })
+}
+
+/// An iterator that merges sorted iterators into one sorted iterator.
+///
+/// This `struct` is created by [`kmerge_with_prev`], wrapped in a `PrevPeekable`.
+///
+/// [`kmerge_with_prev`]: fn.kmerge_with_prev.html
+#[derive(Debug)]
+pub struct KMerge<I>
+where
+    I: Iterator,
+{
+    sources: Vec<I>,
+    /// The next element of every source that has one, along with the index of its source, so that
+    /// ties are broken by source order
+    heads: BinaryHeap<Reverse<(I::Item, usize)>>,
+}
+
+impl<I> Iterator for KMerge<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::cmp::Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let Reverse((next, index)) = self.heads.pop()?;
+
+        if let Some(head) = self.sources[index].next() {
+            self.heads.push(Reverse((head, index)));
+        }
+
+        Some(next)
+    }
+}
+
+/// Advances whichever of `a` and `b` is behind until their next elements are equal, and returns
+/// that element. Both are left right before it, so the next call to `next()` on either returns it.
+/// Returns `None` if either runs out first.
+///
+/// Both streams are expected to be sorted in non-decreasing order, so the one whose next element
+/// is smaller is behind. This resynchronizes sequences that diverged, such as two sorted logs
+/// that each have entries missing from the other. If the next elements can't be compared, like
+/// `NaN`, both are skipped.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use prev_iter::{align_on, PrevPeekable};
+///
+/// let mut a = PrevPeekable::new(vec![1, 2, 5, 6].into_iter());
+/// let mut b = PrevPeekable::new(vec![3, 4, 5, 6].into_iter());
+///
+/// assert_eq!(Some(5), align_on(&mut a, &mut b));
+/// assert_eq!(Some(2), a.current().cloned());
+/// assert_eq!(Some(4), b.current().cloned());
+/// assert_eq!(a.collect::<Vec<_>>(), b.collect::<Vec<_>>());
+/// ```
+pub fn align_on<I, J>(a: &mut PrevPeekable<I>, b: &mut PrevPeekable<J>) -> Option<I::Item>
+where
+    I: Iterator,
+    J: Iterator<Item = I::Item>,
+    <I as ::std::iter::Iterator>::Item: ::std::cmp::PartialOrd + ::std::clone::Clone,
+{
+    loop {
+        let ordering = {
+            let (next_a, next_b) = (a.peek()?, b.peek()?);
+            next_a.partial_cmp(next_b)
+        };
+
+        match ordering {
+            Some(Ordering::Equal) => return a.peek().cloned(),
+            Some(Ordering::Less) => {
+                a.next();
+            }
+            Some(Ordering::Greater) => {
+                b.next();
+            }
+            None => {
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kmerge_with_prev() {
+        let shards = [vec![1, 3, 3, 9], vec![2, 3, 5], vec![0, 3, 9, 10]];
+        let mut merged = kmerge_with_prev(shards.iter().map(|shard| shard.iter()));
+
+        assert_eq!(Some(&0), merged.next());
+        assert_eq!(Some(&1), merged.next());
+        assert_eq!(Some(&0), merged.prev());
+
+        let mut merged = kmerge_with_prev(shards.iter().map(|shard| shard.iter()));
+        assert_eq!(None, merged.first_descent());
+        assert_eq!(Some(&10), merged.prev());
+    }
+
+    #[test]
+    fn test_kmerge_with_prev_dedup() {
+        let shards = vec![vec![1, 3, 3, 9], vec![2, 3, 5], vec![0, 3, 9, 10]];
+        let mut merged = kmerge_with_prev(shards.into_iter().map(Vec::into_iter));
+        let mut unique = Vec::new();
+
+        while let Some(x) = merged.next() {
+            if merged.prev() != Some(x) {
+                unique.push(x);
+            }
+        }
+
+        assert_eq!(vec![0, 1, 2, 3, 5, 9, 10], unique);
+    }
+
+    #[test]
+    fn test_kmerge_with_prev_stable() {
+        /// Compares by key only, so equal elements from different sources can be told apart
+        #[derive(Debug, Clone)]
+        struct ByKey(u32, char);
+
+        impl PartialEq for ByKey {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+
+        impl Eq for ByKey {}
+
+        impl PartialOrd for ByKey {
+            fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for ByKey {
+            fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+
+        let shards = vec![vec![(1, 'a'), (2, 'a')], vec![(1, 'b')], vec![(1, 'c'), (2, 'c')]];
+        let merged: Vec<_> = kmerge_with_prev(
+            shards
+                .into_iter()
+                .map(|shard| shard.into_iter().map(|(key, source)| ByKey(key, source))),
+        )
+        .map(|ByKey(key, source)| (key, source))
+        .collect();
+
+        assert_eq!(vec![(1, 'a'), (1, 'b'), (1, 'c'), (2, 'a'), (2, 'c')], merged);
+    }
+
+    #[test]
+    fn test_kmerge_with_prev_empty() {
+        let shards: Vec<Vec<i32>> = vec![vec![], vec![]];
+
+        assert_eq!(
+            None,
+            kmerge_with_prev(shards.into_iter().map(Vec::into_iter)).next()
+        );
+        assert_eq!(
+            None,
+            kmerge_with_prev(Vec::<::std::vec::IntoIter<i32>>::new()).next()
+        );
+    }
+
+    #[test]
+    fn test_align_on() {
+        let (v, w) = ([1, 2, 5, 6], [3, 4, 5, 6]);
+        let mut a = PrevPeekable::new(v.iter().cloned());
+        let mut b = PrevPeekable::new(w.iter().cloned());
+
+        assert_eq!(Some(5), align_on(&mut a, &mut b));
+        assert_eq!(Some(&5), a.peek());
+        assert_eq!(Some(&5), b.peek());
+        // Aligning again right away stays put
+        assert_eq!(Some(5), align_on(&mut a, &mut b));
+        assert_eq!(Some(5), a.next());
+        assert_eq!(Some(5), b.next());
+        assert_eq!(Some(6), align_on(&mut a, &mut b));
+    }
+
+    #[test]
+    fn test_align_on_diverged_again() {
+        let (v, w) = ([1, 3, 4, 8, 9], [2, 3, 6, 7, 9]);
+        let mut a = PrevPeekable::new(v.iter().cloned());
+        let mut b = PrevPeekable::new(w.iter().cloned());
+
+        assert_eq!(Some(3), align_on(&mut a, &mut b));
+        a.next();
+        b.next();
+        assert_eq!(Some(9), align_on(&mut a, &mut b));
+        assert_eq!(Some(&8), a.current());
+        assert_eq!(Some(&7), b.current());
+    }
+
+    #[test]
+    fn test_align_on_none() {
+        let mut a = PrevPeekable::new([1, 3, 5].iter().cloned());
+        let mut b = PrevPeekable::new([2, 4, 6].iter().cloned());
+        assert_eq!(None, align_on(&mut a, &mut b));
+        assert_eq!(None, a.peek());
+
+        let mut a = PrevPeekable::new([1.0, f64::NAN, 2.0].iter().cloned());
+        let mut b = PrevPeekable::new([0.0, 1.5, 2.0].iter().cloned());
+        a.next();
+        b.next();
+        // NaN can't be compared, so it's skipped along with 1.5
+        assert_eq!(Some(2.0), align_on(&mut a, &mut b));
+
+        let mut empty = PrevPeekable::new(::std::iter::empty());
+        let mut b = PrevPeekable::new([1].iter().cloned());
+        assert_eq!(None, align_on(&mut empty, &mut b));
+        assert_eq!(Some(&1), b.peek());
+    }
+}
@@ -0,0 +1,356 @@
+//! Point queries about the current element and its neighbors.
+
+use super::PrevPeekable;
+use std::cmp::Ordering;
+use std::ops::Sub;
+use storage::PrevStorage;
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Returns whether the current element (the one last returned by `next()`) is a strict local
+    /// maximum, i.e. strictly greater than both the previous element and the next one.
+    ///
+    /// Returns `None` when either neighbor is missing: before the first element has a predecessor,
+    /// or when there is nothing left to peek at.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 3, 2];
+    /// let mut it = PrevPeekable::new(v.iter());
+    ///
+    /// // The first element has no predecessor
+    /// assert_eq!(Some(&1), it.next());
+    /// assert_eq!(None, it.current_is_peak());
+    ///
+    /// // 3 is greater than both 1 and 2
+    /// assert_eq!(Some(&3), it.next());
+    /// assert_eq!(Some(true), it.current_is_peak());
+    ///
+    /// // The last element has no successor
+    /// assert_eq!(Some(&2), it.next());
+    /// assert_eq!(None, it.current_is_peak());
+    /// ```
+    pub fn current_is_peak(&mut self) -> Option<bool>
+    where
+        I::Item: PartialOrd,
+    {
+        let next = self.source.peek()?;
+        let current = self.current.as_ref()?;
+        let prev = self.storage.get()?;
+
+        Some(current > prev && current > next)
+    }
+
+    /// Compares the current element (the one last returned by `next()`) with the next one, without
+    /// consuming it. `Less` means the series is about to rise, and `Greater` means it's about to
+    /// fall.
+    ///
+    /// Returns `None` before the first element has been returned, or when there is nothing left to
+    /// peek at.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    /// use std::cmp::Ordering;
+    ///
+    /// let v = vec![1, 3, 2];
+    /// let mut it = PrevPeekable::new(v.iter());
+    ///
+    /// assert_eq!(Some(&1), it.next());
+    /// assert_eq!(Some(Ordering::Less), it.next_ordering());
+    /// assert_eq!(Some(&3), it.next());
+    /// assert_eq!(Some(Ordering::Greater), it.next_ordering());
+    /// assert_eq!(Some(&2), it.next());
+    /// assert_eq!(None, it.next_ordering());
+    /// ```
+    pub fn next_ordering(&mut self) -> Option<Ordering>
+    where
+        I::Item: Ord,
+    {
+        let next = self.source.peek()?;
+        let current = self.current.as_ref()?;
+
+        Some(current.cmp(next))
+    }
+
+    /// Consumes the iterator and returns the maximum element along with the elements right before
+    /// and after it, as `(predecessor, maximum, successor)`. The neighbors are `None` when the
+    /// maximum is the first or last element, and `None` is returned if the iterator is empty. If
+    /// several elements are equally large, the first one wins.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let latencies = vec![12, 15, 240, 14];
+    /// let it = PrevPeekable::new(latencies.into_iter());
+    ///
+    /// assert_eq!(Some((Some(15), 240, Some(14))), it.max_with_neighbors());
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn max_with_neighbors(self) -> Option<(Option<I::Item>, I::Item, Option<I::Item>)>
+    where
+        I::Item: PartialOrd,
+    {
+        self.extreme_with_neighbors(|cur, best| cur > best)
+    }
+
+    /// Like [`max_with_neighbors`], but returns the minimum element. If several elements are equally
+    /// small, the first one wins.
+    ///
+    /// [`max_with_neighbors`]: #method.max_with_neighbors
+    #[allow(clippy::type_complexity)]
+    pub fn min_with_neighbors(self) -> Option<(Option<I::Item>, I::Item, Option<I::Item>)>
+    where
+        I::Item: PartialOrd,
+    {
+        self.extreme_with_neighbors(|cur, best| cur < best)
+    }
+
+    /// Like [`max_with_neighbors`], but compares the elements by the value `key` returns for them.
+    ///
+    /// [`max_with_neighbors`]: #method.max_with_neighbors
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let readings = vec![("a", 3), ("b", 9), ("c", 1)];
+    /// let it = PrevPeekable::new(readings.into_iter());
+    ///
+    /// assert_eq!(
+    ///     Some((Some(("a", 3)), ("b", 9), Some(("c", 1)))),
+    ///     it.max_with_neighbors_by_key(|&(_, value)| value)
+    /// );
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn max_with_neighbors_by_key<K, F>(
+        self,
+        mut key: F,
+    ) -> Option<(Option<I::Item>, I::Item, Option<I::Item>)>
+    where
+        K: PartialOrd,
+        F: FnMut(&I::Item) -> K,
+    {
+        self.extreme_with_neighbors(|cur, best| key(cur) > key(best))
+    }
+
+    /// Like [`min_with_neighbors`], but compares the elements by the value `key` returns for them.
+    ///
+    /// [`min_with_neighbors`]: #method.min_with_neighbors
+    #[allow(clippy::type_complexity)]
+    pub fn min_with_neighbors_by_key<K, F>(
+        self,
+        mut key: F,
+    ) -> Option<(Option<I::Item>, I::Item, Option<I::Item>)>
+    where
+        K: PartialOrd,
+        F: FnMut(&I::Item) -> K,
+    {
+        self.extreme_with_neighbors(|cur, best| key(cur) < key(best))
+    }
+
+    /// Returns the best element along with its neighbors, where an element only becomes the best one
+    /// if `replaces(element, best)` returns `true` for the best element so far.
+    #[allow(clippy::type_complexity)]
+    fn extreme_with_neighbors<F>(
+        mut self,
+        mut replaces: F,
+    ) -> Option<(Option<I::Item>, I::Item, Option<I::Item>)>
+    where
+        F: FnMut(&I::Item, &I::Item) -> bool,
+    {
+        let mut best: Option<(Option<I::Item>, I::Item, Option<I::Item>)> = None;
+
+        while let Some(cur) = self.next() {
+            let is_better = match best {
+                Some((_, ref best, _)) => replaces(&cur, best),
+                None => true,
+            };
+
+            if is_better {
+                best = Some((self.prev(), cur, self.peek().cloned()));
+            }
+        }
+
+        best
+    }
+
+    /// Returns `next - prev`, the spread between the two neighbors of the current element (the one
+    /// last returned by `next()`), without consuming the next element. This is the central
+    /// difference of a series, up to a factor of 2.
+    ///
+    /// Returns `None` when either neighbor is missing: before the first element has a predecessor,
+    /// or when there is nothing left to peek at.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 4, 9];
+    /// let mut it = PrevPeekable::new(v.into_iter());
+    ///
+    /// assert_eq!(Some(1), it.next());
+    /// assert_eq!(None, it.neighbor_spread());
+    /// assert_eq!(Some(4), it.next());
+    /// assert_eq!(Some(8), it.neighbor_spread());
+    /// ```
+    pub fn neighbor_spread(&mut self) -> Option<I::Item>
+    where
+        I::Item: Sub<Output = I::Item>,
+    {
+        let next = self.source.peek()?.clone();
+        let prev = self.storage.get()?.clone();
+
+        Some(next - prev)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_is_peak() {
+        let v = [1, 3, 2];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!(None, it.current_is_peak());
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(None, it.current_is_peak());
+        assert_eq!(Some(&3), it.next());
+        assert_eq!(Some(true), it.current_is_peak());
+        assert_eq!(Some(&2), it.next());
+        assert_eq!(None, it.current_is_peak());
+        assert_eq!(None, it.next());
+        assert_eq!(None, it.current_is_peak());
+    }
+
+    #[test]
+    fn test_current_is_peak_not_strict() {
+        let v = [1, 3, 3, 2, 4];
+        let mut it = PrevPeekable::new(v.iter());
+
+        it.next();
+        it.next();
+        // 3 is followed by another 3
+        assert_eq!(Some(false), it.current_is_peak());
+        it.next();
+        // 3 is preceded by another 3
+        assert_eq!(Some(false), it.current_is_peak());
+        it.next();
+        // 2 is a valley
+        assert_eq!(Some(false), it.current_is_peak());
+    }
+
+    #[test]
+    fn test_next_ordering() {
+        let v = [1, 2, 2, 0];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!(None, it.next_ordering());
+        it.next();
+        assert_eq!(Some(Ordering::Less), it.next_ordering());
+        it.next();
+        assert_eq!(Some(Ordering::Equal), it.next_ordering());
+        it.next();
+        assert_eq!(Some(Ordering::Greater), it.next_ordering());
+        // Nothing was consumed
+        assert_eq!(Some(&0), it.next());
+        assert_eq!(None, it.next_ordering());
+        assert_eq!(None, it.next());
+        assert_eq!(None, it.next_ordering());
+    }
+
+    #[test]
+    fn test_max_with_neighbors() {
+        let v = [1, 5, 2, 5, 3];
+
+        // Ties keep the first occurrence
+        assert_eq!(
+            Some((Some(&1), &5, Some(&2))),
+            PrevPeekable::new(v.iter()).max_with_neighbors()
+        );
+        assert_eq!(
+            Some((None, &1, Some(&5))),
+            PrevPeekable::new(v.iter()).min_with_neighbors()
+        );
+    }
+
+    #[test]
+    fn test_max_with_neighbors_at_ends() {
+        let v = [9, 4, 7, 0];
+
+        assert_eq!(
+            Some((None, &9, Some(&4))),
+            PrevPeekable::new(v.iter()).max_with_neighbors()
+        );
+        assert_eq!(
+            Some((Some(&7), &0, None)),
+            PrevPeekable::new(v.iter()).min_with_neighbors()
+        );
+
+        let v = [3];
+        assert_eq!(
+            Some((None, &3, None)),
+            PrevPeekable::new(v.iter()).max_with_neighbors()
+        );
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).min_with_neighbors());
+    }
+
+    #[test]
+    fn test_with_neighbors_by_key() {
+        let v = ["bb", "a", "ccc", "dd", "e"];
+
+        assert_eq!(
+            Some((Some(&"a"), &"ccc", Some(&"dd"))),
+            PrevPeekable::new(v.iter()).max_with_neighbors_by_key(|s| s.len())
+        );
+        assert_eq!(
+            Some((Some(&"bb"), &"a", Some(&"ccc"))),
+            PrevPeekable::new(v.iter()).min_with_neighbors_by_key(|s| s.len())
+        );
+    }
+
+    #[test]
+    fn test_neighbor_spread() {
+        let v = [1.0, 1.5, 3.0, 2.0];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(None, it.neighbor_spread());
+        assert_eq!(Some(1.0), it.next());
+        // The first element has no predecessor
+        assert_eq!(None, it.neighbor_spread());
+        assert_eq!(Some(1.5), it.next());
+        assert_eq!(Some(2.0), it.neighbor_spread());
+        assert_eq!(Some(3.0), it.next());
+        assert_eq!(Some(0.5), it.neighbor_spread());
+        // The next element isn't consumed
+        assert_eq!(Some(&2.0), it.peek());
+        assert_eq!(Some(2.0), it.next());
+        // The last element has no successor
+        assert_eq!(None, it.neighbor_spread());
+        assert_eq!(None, it.next());
+        assert_eq!(None, it.neighbor_spread());
+    }
+}
@@ -0,0 +1,125 @@
+//! Numeric adapters generic over the traits from [`num-traits`], for types such as big integers,
+//! decimals and rationals.
+//!
+//! This module is only available with the `num-traits` feature.
+//!
+//! [`num-traits`]: https://docs.rs/num-traits/
+
+use num_traits::{CheckedSub, FromPrimitive, Zero};
+use std::ops::{Div, Sub};
+use PrevPeekable;
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Like [`deltas`], but yields `None` for a pair whose difference overflows instead of
+    /// panicking or wrapping.
+    ///
+    /// [`deltas`]: #method.deltas
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v: Vec<i8> = vec![-100, 100, 90];
+    /// let deltas: Vec<_> = PrevPeekable::new(v.into_iter()).checked_deltas().collect();
+    ///
+    /// assert_eq!(vec![None, Some(-10)], deltas);
+    /// ```
+    pub fn checked_deltas(mut self) -> impl Iterator<Item = Option<I::Item>>
+    where
+        I::Item: CheckedSub,
+    {
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+
+            if let Some(prev) = self.peek_prev() {
+                return Some(cur.checked_sub(prev));
+            }
+        })
+    }
+
+    /// Yields the mean of every window of `n` consecutive elements, starting once `n` elements have
+    /// been read. The sum of the window is updated as it slides rather than recomputed, so for
+    /// floats the means can differ from summing each window by rounding errors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0, or doesn't fit in the element type.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![2, 4, 6, 10];
+    /// let averages: Vec<_> = PrevPeekable::new(v.into_iter()).moving_average(2).collect();
+    ///
+    /// assert_eq!(vec![3, 5, 8], averages);
+    /// ```
+    pub fn moving_average(self, n: usize) -> impl Iterator<Item = I::Item>
+    where
+        I::Item: Zero + Sub<Output = I::Item> + Div<Output = I::Item> + FromPrimitive,
+    {
+        let len = I::Item::from_usize(n).expect("the window size fits in the element type");
+
+        self.windowed_fold_invertible(
+            n,
+            I::Item::zero(),
+            |sum, x| sum + x.clone(),
+            |sum, x| sum - x.clone(),
+        )
+        .map(move |sum| sum / len.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_rational::Ratio;
+
+    #[test]
+    fn test_checked_deltas() {
+        let v: [u8; 4] = [1, 5, 3, 255];
+        let deltas: Vec<_> = PrevPeekable::new(v.iter().cloned()).checked_deltas().collect();
+
+        assert_eq!(vec![Some(4), None, Some(252)], deltas);
+    }
+
+    #[test]
+    fn test_checked_deltas_ratio() {
+        let v = [Ratio::new(1, 2), Ratio::new(1, 3), Ratio::new(1, 1)];
+        let deltas: Vec<_> = PrevPeekable::new(v.iter().cloned()).checked_deltas().collect();
+
+        assert_eq!(vec![Some(Ratio::new(-1, 6)), Some(Ratio::new(2, 3))], deltas);
+    }
+
+    #[test]
+    fn test_moving_average_ratio() {
+        let v: Vec<Ratio<i64>> = [1, 2, 4, 8].iter().map(|&x| Ratio::from_integer(x)).collect();
+        let averages: Vec<_> = PrevPeekable::new(v.into_iter()).moving_average(3).collect();
+
+        assert_eq!(vec![Ratio::new(7, 3), Ratio::new(14, 3)], averages);
+    }
+
+    #[test]
+    fn test_generic_adapters_ratio() {
+        let v = [Ratio::new(1, 2), Ratio::new(1, 3), Ratio::new(1, 6)];
+
+        let deltas: Vec<_> = PrevPeekable::new(v.iter().cloned()).deltas().collect();
+        assert_eq!(vec![Ratio::new(-1, 6), Ratio::new(-1, 6)], deltas);
+
+        let totals: Vec<_> = PrevPeekable::new(v.iter().cloned()).running_total().collect();
+        assert_eq!(
+            vec![Ratio::new(1, 2), Ratio::new(5, 6), Ratio::from_integer(1)],
+            totals
+        );
+    }
+}
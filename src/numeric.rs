@@ -0,0 +1,1183 @@
+//! Adapters for numeric streams.
+
+use super::PrevPeekable;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::ops::{Add, Sub};
+use std::time::Instant;
+
+/// The direction in which a stream crossed a level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// The stream went from below the level to at or above it.
+    Up,
+    /// The stream went from at or above the level to below it.
+    Down,
+}
+
+/// What relative change adapters do when the previous element is zero or not finite, so that
+/// dividing by it doesn't give a meaningful result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZeroPolicy {
+    /// Yield `Some(f64::NAN)`.
+    Nan,
+    /// Yield `None`.
+    Missing,
+    /// Don't yield anything for the pair.
+    Skip,
+}
+
+/// What [`rates`] does when a counter's value goes down, which usually means it was reset.
+///
+/// [`rates`]: struct.PrevPeekableWith.html#method.rates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CounterReset {
+    /// Yield the negative rate.
+    Keep,
+    /// Yield a rate of 0.0.
+    Clamp,
+}
+
+/// A point in time that [`rates`] can measure the time between.
+///
+/// [`rates`]: struct.PrevPeekableWith.html#method.rates
+pub trait Timestamp: Clone {
+    /// Returns how many seconds after `earlier` this is, which is negative if it's before it.
+    fn seconds_since(&self, earlier: &Self) -> f64;
+}
+
+impl Timestamp for f64 {
+    fn seconds_since(&self, earlier: &f64) -> f64 {
+        self - earlier
+    }
+}
+
+impl Timestamp for Instant {
+    fn seconds_since(&self, earlier: &Instant) -> f64 {
+        match self.checked_duration_since(*earlier) {
+            Some(elapsed) => elapsed.as_secs_f64(),
+            None => -earlier.duration_since(*self).as_secs_f64(),
+        }
+    }
+}
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator<Item = i64>,
+{
+    /// Densifies a sparse integer sequence by emitting every integer between each previous element
+    /// and the current one, so that increasing stretches of the output go up by exactly 1 each step.
+    ///
+    /// When the current element is equal to or smaller than the previous one there is no gap to
+    /// fill, so only the current element is emitted.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 4, 5];
+    /// let filled: Vec<_> = PrevPeekable::new(v.into_iter()).fill_gaps().collect();
+    ///
+    /// assert_eq!(vec![1, 2, 3, 4, 5], filled);
+    /// ```
+    pub fn fill_gaps(mut self) -> impl Iterator<Item = i64> {
+        ::std::iter::from_fn(move || {
+            let cur = self.next()?;
+            let start = match self.prev() {
+                Some(prev) if prev < cur => prev + 1,
+                _ => cur,
+            };
+
+            Some(start..=cur)
+        })
+        .flatten()
+    }
+}
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator<Item = f64>,
+{
+    /// Yields the relative change `(cur - prev) / prev` for every consecutive pair of elements, so
+    /// one fewer element than the input. `policy` decides what happens when `prev` is zero or not
+    /// finite.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{PrevPeekable, ZeroPolicy};
+    ///
+    /// let v = vec![100.0, 110.0, 0.0, 5.0];
+    /// let changes: Vec<_> = PrevPeekable::new(v.into_iter())
+    ///     .relative_changes(ZeroPolicy::Missing)
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![Some(0.1), Some(-1.0), None], changes);
+    /// ```
+    pub fn relative_changes(self, policy: ZeroPolicy) -> impl Iterator<Item = Option<f64>> {
+        self.relative_changes_by(|&x| x, policy)
+    }
+
+    /// Yields the log return `ln(cur / prev)` for every consecutive pair of elements, so one fewer
+    /// element than the input. `policy` decides what happens when `prev` is zero or not finite.
+    ///
+    /// Pairs with different signs have a negative ratio, whose logarithm is `NaN`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{PrevPeekable, ZeroPolicy};
+    ///
+    /// let v = vec![1.0, ::std::f64::consts::E, 1.0];
+    /// let returns: Vec<_> = PrevPeekable::new(v.into_iter())
+    ///     .log_returns(ZeroPolicy::Skip)
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![Some(1.0), Some(-1.0)], returns);
+    /// ```
+    pub fn log_returns(self, policy: ZeroPolicy) -> impl Iterator<Item = Option<f64>> {
+        self.log_returns_by(|&x| x, policy)
+    }
+
+    /// Yields the median of the last `window` elements, starting once `window` elements have been
+    /// read. For an even `window` it's the mean of the two middle elements.
+    ///
+    /// The elements of the window are kept sorted as it slides, so each step costs a binary search
+    /// and a shift of at most `window` elements rather than a sort. `NaN` sorts above every other
+    /// value, following [`f64::total_cmp`].
+    ///
+    /// [`f64::total_cmp`]: https://doc.rust-lang.org/nightly/std/primitive.f64.html#method.total_cmp
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is 0.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// // The spike at 100.0 doesn't drag the medians up
+    /// let v = vec![1.0, 2.0, 100.0, 3.0, 4.0];
+    /// let medians: Vec<_> = PrevPeekable::new(v.into_iter()).rolling_median(3).collect();
+    ///
+    /// assert_eq!(vec![2.0, 3.0, 4.0], medians);
+    /// ```
+    pub fn rolling_median(mut self, window: usize) -> impl Iterator<Item = f64> {
+        assert!(window != 0, "window size must be non-zero");
+        // The window in arrival order, and the same elements sorted
+        let mut arrived = VecDeque::new();
+        let mut sorted: Vec<f64> = Vec::new();
+
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+
+            if arrived.len() == window {
+                let oldest = arrived.pop_front().expect("the window is full");
+                let index = sorted
+                    .binary_search_by(|x| x.total_cmp(&oldest))
+                    .expect("every element of the window is in sorted");
+                sorted.remove(index);
+            }
+            arrived.push_back(cur);
+            let index = sorted.partition_point(|x| x.total_cmp(&cur) == Ordering::Less);
+            sorted.insert(index, cur);
+
+            if sorted.len() == window {
+                let middle = window / 2;
+                return Some(if window.is_multiple_of(2) {
+                    (sorted[middle - 1] + sorted[middle]) / 2.0
+                } else {
+                    sorted[middle]
+                });
+            }
+        })
+    }
+
+    /// Yields the ratio `cur / prev` for every consecutive pair of elements, so one fewer element
+    /// than the input.
+    ///
+    /// This is plain float division, so when `prev` is zero the ratio is infinite with the sign of
+    /// `cur`, or `NaN` if `cur` is zero too.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1.0, 3.0, 0.0, 2.0];
+    /// let ratios: Vec<_> = PrevPeekable::new(v.into_iter()).ratios().collect();
+    ///
+    /// assert_eq!(vec![3.0, 0.0, f64::INFINITY], ratios);
+    /// ```
+    pub fn ratios(mut self) -> impl Iterator<Item = f64> {
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+
+            if let Some(prev) = self.prev() {
+                return Some(cur / prev);
+            }
+        })
+    }
+
+    /// Yields the exponentially weighted moving average of the elements, which is
+    /// `alpha * cur + (1 - alpha) * previous_average` for every element after the first, and the
+    /// first element itself. A larger `alpha` follows the elements more closely, while a smaller
+    /// one smooths out more noise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alpha` isn't between 0 and 1, inclusive.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![10.0, 20.0, 20.0, 0.0];
+    /// let averages: Vec<_> = PrevPeekable::new(v.into_iter()).ewma(0.5).collect();
+    ///
+    /// assert_eq!(vec![10.0, 15.0, 17.5, 8.75], averages);
+    /// ```
+    pub fn ewma(mut self, alpha: f64) -> impl Iterator<Item = f64> {
+        assert!((0.0..=1.0).contains(&alpha), "alpha must be between 0 and 1");
+        let mut average: Option<f64> = None;
+
+        ::std::iter::from_fn(move || {
+            let cur = self.next()?;
+            let next = match average {
+                Some(average) => alpha * cur + (1.0 - alpha) * average,
+                None => cur,
+            };
+            average = Some(next);
+
+            Some(next)
+        })
+    }
+
+    /// Splits the elements into groups at every sign change, i.e. wherever the stream crosses
+    /// zero, and yields each group as a `Vec`.
+    ///
+    /// Zero, of either sign, and `NaN` don't have a sign, so they never start a new group and stay
+    /// in the current one. A group takes the sign of its first positive or negative element, so
+    /// `[1.0, 0.0, -1.0]` is split into `[1.0, 0.0]` and `[-1.0]`, and leading zeros are grouped
+    /// with the elements after them.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1.0, 2.0, -1.0, -3.0, 4.0];
+    /// let groups: Vec<_> = PrevPeekable::new(v.into_iter()).split_on_sign_change().collect();
+    ///
+    /// assert_eq!(vec![vec![1.0, 2.0], vec![-1.0, -3.0], vec![4.0]], groups);
+    /// ```
+    pub fn split_on_sign_change(mut self) -> impl Iterator<Item = Vec<f64>> {
+        let sign = |x: f64| {
+            x.partial_cmp(&0.0)
+                .filter(|&ordering| ordering != Ordering::Equal)
+        };
+
+        ::std::iter::from_fn(move || {
+            let first = self.next()?;
+            let mut group_sign = sign(first);
+            let mut group = vec![first];
+
+            while let Some(&next) = self.peek() {
+                match (group_sign, sign(next)) {
+                    (Some(current), Some(next_sign)) if current != next_sign => break,
+                    (None, next_sign) => group_sign = next_sign,
+                    _ => {}
+                }
+                group.push(next);
+                self.next();
+            }
+
+            Some(group)
+        })
+    }
+
+    /// Consumes and returns elements for as long as each one is within `tolerance` of the element
+    /// before it, stopping at the first one that jumps further. That element isn't consumed, so
+    /// it's returned by the next call to `next()`.
+    ///
+    /// The first element is compared to the element returned most recently, or taken regardless
+    /// if there isn't one yet. `NaN` is never within the band.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![20.0, 20.5, 21.0, 35.0, 35.2];
+    /// let mut it = PrevPeekable::new(v.into_iter());
+    ///
+    /// assert_eq!(vec![20.0, 20.5, 21.0], it.take_within_band(1.0));
+    /// assert_eq!(Some(35.0), it.next());
+    /// assert_eq!(vec![35.2], it.take_within_band(1.0));
+    /// ```
+    pub fn take_within_band(&mut self, tolerance: f64) -> Vec<f64> {
+        let mut taken = Vec::new();
+
+        while let Some(&next) = self.peek() {
+            let within = match self.current {
+                Some(cur) => (next - cur).abs() <= tolerance,
+                None => true,
+            };
+            if !within {
+                break;
+            }
+
+            taken.push(next);
+            self.next();
+        }
+
+        taken
+    }
+}
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator<Item = (f64, f64)>,
+{
+    /// Integrates `(x, y)` samples with the trapezoid rule, i.e. returns the sum of
+    /// `(x1 - x0) * (y0 + y1) / 2` over every consecutive pair of samples. Returns 0.0 if there
+    /// are fewer than two samples.
+    ///
+    /// The samples are expected to be in increasing order of `x`. Where `x` decreases, the
+    /// trapezoid's area is subtracted instead of added, as with integrating from right to left.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// // y = x from 0 to 2
+    /// let samples = vec![(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)];
+    ///
+    /// assert_eq!(2.0, PrevPeekable::new(samples.into_iter()).trapezoid());
+    /// ```
+    pub fn trapezoid(self) -> f64 {
+        self.cumulative_trapezoid().last().unwrap_or(0.0)
+    }
+
+    /// Yields the running integral of `(x, y)` samples with the trapezoid rule, i.e. the area from
+    /// the first sample up to each sample, as calculated by [`trapezoid`]. There's one value for
+    /// every sample, starting with 0.0 for the first one.
+    ///
+    /// [`trapezoid`]: #method.trapezoid
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// // A constant speed of 3 m/s, sampled every 2 s
+    /// let speeds = vec![(0.0, 3.0), (2.0, 3.0), (4.0, 3.0)];
+    /// let distances: Vec<_> = PrevPeekable::new(speeds.into_iter()).cumulative_trapezoid().collect();
+    ///
+    /// assert_eq!(vec![0.0, 6.0, 12.0], distances);
+    /// ```
+    pub fn cumulative_trapezoid(mut self) -> impl Iterator<Item = f64> {
+        let mut area = 0.0;
+
+        ::std::iter::from_fn(move || {
+            let (x1, y1) = self.next()?;
+            if let Some((x0, y0)) = self.prev() {
+                area += (x1 - x0) * (y0 + y1) / 2.0;
+            }
+
+            Some(area)
+        })
+    }
+}
+
+impl<I, T> PrevPeekable<I>
+where
+    I: Iterator<Item = (T, f64)>,
+    T: Timestamp,
+{
+    /// Yields the rate of change `(value - prev_value) / (t - prev_t)` per second for every
+    /// consecutive pair of `(t, value)` samples, along with the later timestamp `t`. Timestamps can
+    /// be `f64` seconds or [`Instant`]s.
+    ///
+    /// Samples where the time doesn't move forward are skipped, since a sample that's reported
+    /// again or arrives out of order has nothing new to say about the rate. The next rate is
+    /// measured from the last sample that wasn't skipped. `reset` decides what happens when the
+    /// value goes down, as when a counter is reset.
+    ///
+    /// [`Instant`]: https://doc.rust-lang.org/nightly/std/time/struct.Instant.html
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{CounterReset, PrevPeekable};
+    ///
+    /// // Requests served, sampled every 10 seconds. The server restarted before the last sample.
+    /// let requests = vec![(0.0, 100.0), (10.0, 150.0), (20.0, 250.0), (30.0, 20.0)];
+    /// let rates: Vec<_> = PrevPeekable::new(requests.into_iter())
+    ///     .rates(CounterReset::Clamp)
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![(10.0, 5.0), (20.0, 10.0), (30.0, 0.0)], rates);
+    /// ```
+    pub fn rates(mut self, reset: CounterReset) -> impl Iterator<Item = (T, f64)> {
+        // The last sample that wasn't skipped, which isn't always the previous one
+        let mut base: Option<(T, f64)> = None;
+
+        ::std::iter::from_fn(move || loop {
+            let (t, value) = self.next()?;
+            let (base_t, base_value) = match base.take() {
+                Some(base) => base,
+                None => {
+                    base = Some((t, value));
+                    continue;
+                }
+            };
+
+            let elapsed = t.seconds_since(&base_t);
+            // Unless time moved forward, which it doesn't for a NaN timestamp either, skip the sample
+            if elapsed > 0.0 {
+                let rate = (value - base_value) / elapsed;
+                let rate = match reset {
+                    CounterReset::Clamp if rate < 0.0 => 0.0,
+                    _ => rate,
+                };
+                base = Some((t.clone(), value));
+                return Some((t, rate));
+            }
+            base = Some((base_t, base_value));
+        })
+    }
+}
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Like [`relative_changes`], but computed over the values `f` extracts from the elements.
+    ///
+    /// [`relative_changes`]: #method.relative_changes
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{PrevPeekable, ZeroPolicy};
+    ///
+    /// let prices = vec![("mon", 10), ("tue", 15), ("wed", 12)];
+    /// let changes: Vec<_> = PrevPeekable::new(prices.into_iter())
+    ///     .relative_changes_by(|&(_, price)| price as f64, ZeroPolicy::Nan)
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![Some(0.5), Some(-0.2)], changes);
+    /// ```
+    pub fn relative_changes_by<F>(self, f: F, policy: ZeroPolicy) -> impl Iterator<Item = Option<f64>>
+    where
+        F: FnMut(&I::Item) -> f64,
+    {
+        self.changes_from_prev(f, policy, |prev, cur| (cur - prev) / prev)
+    }
+
+    /// Like [`log_returns`], but computed over the values `f` extracts from the elements.
+    ///
+    /// [`log_returns`]: #method.log_returns
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{PrevPeekable, ZeroPolicy};
+    ///
+    /// let prices = vec![("mon", 0), ("tue", 15), ("wed", 15)];
+    /// let returns: Vec<_> = PrevPeekable::new(prices.into_iter())
+    ///     .log_returns_by(|&(_, price)| price as f64, ZeroPolicy::Missing)
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![None, Some(0.0)], returns);
+    /// ```
+    pub fn log_returns_by<F>(self, f: F, policy: ZeroPolicy) -> impl Iterator<Item = Option<f64>>
+    where
+        F: FnMut(&I::Item) -> f64,
+    {
+        self.changes_from_prev(f, policy, |prev, cur| (cur / prev).ln())
+    }
+
+    /// Applies `change` to every consecutive `(prev, cur)` pair of values extracted by `f`, with
+    /// `policy` deciding what happens when `prev` is zero or not finite.
+    fn changes_from_prev<F, G>(
+        mut self,
+        mut f: F,
+        policy: ZeroPolicy,
+        change: G,
+    ) -> impl Iterator<Item = Option<f64>>
+    where
+        F: FnMut(&I::Item) -> f64,
+        G: Fn(f64, f64) -> f64,
+    {
+        ::std::iter::from_fn(move || loop {
+            let cur = f(&self.next()?);
+            let prev = match self.peek_prev() {
+                Some(prev) => f(prev),
+                None => continue,
+            };
+
+            if prev != 0.0 && prev.is_finite() {
+                return Some(Some(change(prev, cur)));
+            }
+
+            match policy {
+                ZeroPolicy::Nan => return Some(Some(f64::NAN)),
+                ZeroPolicy::Missing => return Some(None),
+                ZeroPolicy::Skip => {}
+            }
+        })
+    }
+}
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Reports every place where the stream crosses `level`, yielding
+    /// `(index, direction, prev, current)` tuples, where `index` is the position of `current`.
+    ///
+    /// An element equal to `level` counts as being at or above it, so a crossing happens whenever
+    /// the previous and current elements are on different sides of that predicate. Touching the
+    /// level from above and moving back up is not a crossing, but reaching it from below is.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{Direction, PrevPeekable};
+    ///
+    /// let v = vec![8, 9, 12, 11, 7];
+    /// let crossings: Vec<_> = PrevPeekable::new(v.into_iter()).threshold_crossings(10).collect();
+    ///
+    /// assert_eq!(vec![(2, Direction::Up, 9, 12), (4, Direction::Down, 11, 7)], crossings);
+    /// ```
+    pub fn threshold_crossings(
+        mut self,
+        level: I::Item,
+    ) -> impl Iterator<Item = (usize, Direction, I::Item, I::Item)>
+    where
+        I::Item: PartialOrd,
+    {
+        let mut index = 0;
+
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+            let cur_index = index;
+            index += 1;
+
+            if let Some(prev) = self.peek_prev() {
+                let was_above = *prev >= level;
+                let is_above = cur >= level;
+
+                if was_above != is_above {
+                    let direction = if is_above { Direction::Up } else { Direction::Down };
+                    return Some((cur_index, direction, prev.clone(), cur));
+                }
+            }
+        })
+    }
+
+    /// Yields the difference `cur - prev` for every consecutive pair of elements, so one fewer
+    /// element than the input.
+    ///
+    /// Any type that can be subtracted from itself works, e.g. big integers or decimals. For
+    /// primitive integers the subtraction can overflow; `checked_deltas()` with the `num-traits`
+    /// feature yields `None` instead.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 4, 9, 7];
+    /// let deltas: Vec<_> = PrevPeekable::new(v.into_iter()).deltas().collect();
+    ///
+    /// assert_eq!(vec![3, 5, -2], deltas);
+    /// ```
+    pub fn deltas(mut self) -> impl Iterator<Item = I::Item>
+    where
+        I::Item: Sub<Output = I::Item>,
+    {
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+
+            if let Some(prev) = self.prev() {
+                return Some(cur - prev);
+            }
+        })
+    }
+
+    /// Yields the running total of the elements, i.e. the sum of every element up to and including
+    /// the current one.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 4, 9, 7];
+    /// let totals: Vec<_> = PrevPeekable::new(v.into_iter()).running_total().collect();
+    ///
+    /// assert_eq!(vec![1, 5, 14, 21], totals);
+    /// ```
+    pub fn running_total(mut self) -> impl Iterator<Item = I::Item>
+    where
+        I::Item: Add<Output = I::Item>,
+    {
+        let mut total: Option<I::Item> = None;
+
+        ::std::iter::from_fn(move || {
+            let cur = self.next()?;
+            let sum = match total.take() {
+                Some(total) => total + cur,
+                None => cur,
+            };
+            total = Some(sum.clone());
+
+            Some(sum)
+        })
+    }
+
+    /// Yields the second difference `(cur - prev) - (prev - prev2)` for every element with two
+    /// elements before it, i.e. the difference of consecutive `deltas()`, so two fewer elements
+    /// than the input. This is the discrete counterpart of the second derivative, for measuring
+    /// curvature.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 2, 4, 7];
+    /// let second: Vec<_> = PrevPeekable::new(v.into_iter()).second_delta().collect();
+    ///
+    /// assert_eq!(vec![1, 1], second);
+    /// ```
+    pub fn second_delta(mut self) -> impl Iterator<Item = I::Item>
+    where
+        I::Item: Sub<Output = I::Item>,
+    {
+        let mut last_delta: Option<I::Item> = None;
+
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+            let prev = match self.prev() {
+                Some(prev) => prev,
+                None => continue,
+            };
+
+            let delta = cur - prev;
+            if let Some(last) = last_delta.replace(delta.clone()) {
+                return Some(delta - last);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_fill_gaps() {
+        let v = [1, 4, 5];
+        let filled: Vec<_> = PrevPeekable::new(v.iter().cloned()).fill_gaps().collect();
+
+        assert_eq!(vec![1, 2, 3, 4, 5], filled);
+    }
+
+    #[test]
+    fn test_fill_gaps_equal_and_decreasing() {
+        let v = [3, 3, 1, 2, -1, 1];
+        let filled: Vec<_> = PrevPeekable::new(v.iter().cloned()).fill_gaps().collect();
+
+        assert_eq!(vec![3, 3, 1, 2, -1, 0, 1], filled);
+    }
+
+    #[test]
+    fn test_fill_gaps_empty() {
+        let mut filled = PrevPeekable::new(::std::iter::empty()).fill_gaps();
+
+        assert_eq!(None, filled.next());
+    }
+
+    #[test]
+    fn test_threshold_crossings() {
+        let v = [8, 9, 12, 11, 7];
+        let crossings: Vec<_> = PrevPeekable::new(v.iter()).threshold_crossings(&10).collect();
+
+        assert_eq!(
+            vec![(2, Direction::Up, &9, &12), (4, Direction::Down, &11, &7)],
+            crossings
+        );
+    }
+
+    #[test]
+    fn test_threshold_crossings_touching_level() {
+        // Touching the level from above doesn't cross it
+        let v = [12, 10, 11, 10, 12];
+        let mut crossings = PrevPeekable::new(v.iter()).threshold_crossings(&10);
+        assert_eq!(None, crossings.next());
+
+        // Reaching the level from below does
+        let v = [8, 10, 8];
+        let crossings: Vec<_> = PrevPeekable::new(v.iter()).threshold_crossings(&10).collect();
+        assert_eq!(
+            vec![(1, Direction::Up, &8, &10), (2, Direction::Down, &10, &8)],
+            crossings
+        );
+    }
+
+    #[test]
+    fn test_threshold_crossings_floats() {
+        let v = [0.5, 1.5, 0.5];
+        let crossings: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .threshold_crossings(1.0)
+            .collect();
+
+        assert_eq!(
+            vec![(1, Direction::Up, 0.5, 1.5), (2, Direction::Down, 1.5, 0.5)],
+            crossings
+        );
+    }
+
+    #[test]
+    fn test_relative_changes() {
+        let v = [2.0, 3.0, 1.5, -3.0];
+        let changes: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .relative_changes(ZeroPolicy::Nan)
+            .collect();
+
+        assert_eq!(vec![Some(0.5), Some(-0.5), Some(-3.0)], changes);
+    }
+
+    #[test]
+    fn test_relative_changes_zero_policy() {
+        // Crosses zero: the pair starting at 0.0 can't be divided by
+        let v = [1.0, 0.0, -1.0, f64::INFINITY, 2.0];
+        let changes = |policy| -> Vec<_> {
+            PrevPeekable::new(v.iter().cloned())
+                .relative_changes(policy)
+                .collect()
+        };
+
+        let nan = changes(ZeroPolicy::Nan);
+        assert_eq!(4, nan.len());
+        assert_eq!(Some(-1.0), nan[0]);
+        assert!(nan[1].unwrap().is_nan());
+        assert_eq!(Some(-f64::INFINITY), nan[2]);
+        assert!(nan[3].unwrap().is_nan());
+
+        assert_eq!(
+            vec![Some(-1.0), None, Some(-f64::INFINITY), None],
+            changes(ZeroPolicy::Missing)
+        );
+        assert_eq!(vec![Some(-1.0), Some(-f64::INFINITY)], changes(ZeroPolicy::Skip));
+    }
+
+    #[test]
+    fn test_log_returns() {
+        let e = ::std::f64::consts::E;
+        let v = [e, e * e, 0.0, 1.0];
+        let returns: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .log_returns(ZeroPolicy::Missing)
+            .collect();
+
+        assert_eq!(3, returns.len());
+        assert!((returns[0].unwrap() - 1.0).abs() < 1e-12);
+        assert_eq!(Some(-f64::INFINITY), returns[1]);
+        assert_eq!(None, returns[2]);
+    }
+
+    #[test]
+    fn test_relative_changes_by() {
+        let v = [(1, 4), (2, 5), (3, 0), (4, 1)];
+        let changes: Vec<_> = PrevPeekable::new(v.iter())
+            .relative_changes_by(|&&(_, x)| x as f64, ZeroPolicy::Skip)
+            .collect();
+
+        assert_eq!(vec![Some(0.25), Some(-1.0)], changes);
+    }
+
+    #[test]
+    fn test_relative_changes_short() {
+        let mut changes = PrevPeekable::new(vec![1.0].into_iter()).relative_changes(ZeroPolicy::Nan);
+
+        assert_eq!(None, changes.next());
+    }
+
+    fn naive_rolling_median(v: &[f64], window: usize) -> Vec<f64> {
+        v.windows(window)
+            .map(|w| {
+                let mut sorted = w.to_vec();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                let middle = window / 2;
+                if window.is_multiple_of(2) {
+                    (sorted[middle - 1] + sorted[middle]) / 2.0
+                } else {
+                    sorted[middle]
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rolling_median() {
+        let v = [5.0, 1.0, 3.0, 2.0, 4.0];
+        let medians: Vec<_> = PrevPeekable::new(v.iter().cloned()).rolling_median(3).collect();
+
+        assert_eq!(vec![3.0, 2.0, 3.0], medians);
+        assert_eq!(naive_rolling_median(&v, 3), medians);
+    }
+
+    #[test]
+    fn test_rolling_median_matches_naive() {
+        let v = [5.0, 1.0, 3.0, 2.0, 4.0, 2.0, 2.0, -7.5, 0.0, 9.0, 3.0];
+
+        for window in 1..=v.len() {
+            let medians: Vec<_> = PrevPeekable::new(v.iter().cloned())
+                .rolling_median(window)
+                .collect();
+
+            assert_eq!(naive_rolling_median(&v, window), medians, "window of {}", window);
+        }
+    }
+
+    #[test]
+    fn test_rolling_median_shorter_than_window() {
+        let v = [1.0, 2.0];
+
+        assert_eq!(
+            None,
+            PrevPeekable::new(v.iter().cloned()).rolling_median(3).next()
+        );
+        // The buffers grow as elements arrive instead of being allocated up front
+        assert_eq!(
+            None,
+            PrevPeekable::new(v.iter().cloned())
+                .rolling_median(usize::MAX)
+                .next()
+        );
+    }
+
+    #[test]
+    fn test_deltas() {
+        let v = [1, 4, 9, 7];
+        let deltas: Vec<_> = PrevPeekable::new(v.iter().cloned()).deltas().collect();
+
+        assert_eq!(vec![3, 5, -2], deltas);
+        assert_eq!(None, PrevPeekable::new(v[..1].iter().cloned()).deltas().next());
+    }
+
+    #[test]
+    fn test_running_total() {
+        let v = [1.5, 2.0, -0.5];
+        let totals: Vec<_> = PrevPeekable::new(v.iter().cloned()).running_total().collect();
+
+        assert_eq!(vec![1.5, 3.5, 3.0], totals);
+        assert_eq!(
+            None,
+            PrevPeekable::new(v[..0].iter().cloned()).running_total().next()
+        );
+    }
+
+    #[test]
+    fn test_ratios() {
+        let v = [2.0, 4.0, 4.0, 1.0];
+        let ratios: Vec<_> = PrevPeekable::new(v.iter().cloned()).ratios().collect();
+
+        assert_eq!(vec![2.0, 1.0, 0.25], ratios);
+    }
+
+    #[test]
+    fn test_ratios_zero_prev() {
+        let v = [0.0, -1.0, 0.0, 0.0];
+        let ratios: Vec<_> = PrevPeekable::new(v.iter().cloned()).ratios().collect();
+
+        assert_eq!(f64::NEG_INFINITY, ratios[0]);
+        assert_eq!(-0.0, ratios[1]);
+        assert!(ratios[2].is_nan());
+        assert_eq!(None, PrevPeekable::new(v[..1].iter().cloned()).ratios().next());
+    }
+
+    #[test]
+    fn test_split_on_sign_change() {
+        let v = [1.0, 2.0, -1.0, -3.0, 4.0];
+        let groups: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .split_on_sign_change()
+            .collect();
+
+        assert_eq!(vec![vec![1.0, 2.0], vec![-1.0, -3.0], vec![4.0]], groups);
+        assert_eq!(
+            None,
+            PrevPeekable::new(v[..0].iter().cloned())
+                .split_on_sign_change()
+                .next()
+        );
+    }
+
+    #[test]
+    fn test_split_on_sign_change_zeros() {
+        let v = [0.0, -0.0, 2.0, 0.0, -1.0, f64::NAN, -2.0, 3.0];
+        let groups: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .split_on_sign_change()
+            .collect();
+
+        assert_eq!(3, groups.len());
+        assert_eq!(vec![0.0, -0.0, 2.0, 0.0], groups[0]);
+        assert_eq!((-1.0, -2.0), (groups[1][0], groups[1][2]));
+        assert!(groups[1][1].is_nan());
+        assert_eq!(vec![3.0], groups[2]);
+
+        let zeros = [0.0, 0.0];
+        let groups: Vec<_> = PrevPeekable::new(zeros.iter().cloned())
+            .split_on_sign_change()
+            .collect();
+        assert_eq!(vec![vec![0.0, 0.0]], groups);
+    }
+
+    #[test]
+    fn test_ewma() {
+        let v = [1.0, 2.0, 3.0];
+        let averages: Vec<_> = PrevPeekable::new(v.iter().cloned()).ewma(0.5).collect();
+
+        assert_eq!(vec![1.0, 1.5, 2.25], averages);
+        assert_eq!(None, PrevPeekable::new(v[..0].iter().cloned()).ewma(0.5).next());
+    }
+
+    #[test]
+    fn test_ewma_extreme_alpha() {
+        let v = [1.0, 2.0, 3.0];
+
+        // 1 follows the elements exactly, and 0 never moves from the first one
+        let averages: Vec<_> = PrevPeekable::new(v.iter().cloned()).ewma(1.0).collect();
+        assert_eq!(vec![1.0, 2.0, 3.0], averages);
+        let averages: Vec<_> = PrevPeekable::new(v.iter().cloned()).ewma(0.0).collect();
+        assert_eq!(vec![1.0, 1.0, 1.0], averages);
+    }
+
+    #[test]
+    #[should_panic(expected = "alpha must be between 0 and 1")]
+    fn test_ewma_invalid_alpha() {
+        let _ = PrevPeekable::new(::std::iter::once(1.0)).ewma(f64::NAN);
+    }
+
+    #[test]
+    fn test_take_within_band() {
+        let v = [1.0, 1.1, 1.15, 3.0];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(vec![1.0, 1.1, 1.15], it.take_within_band(0.2));
+        assert_eq!(Some(&3.0), it.peek());
+        assert_eq!(Some(1.1), it.prev());
+        // The jump is still out of the band
+        assert!(it.take_within_band(0.2).is_empty());
+        assert_eq!(vec![3.0], it.take_within_band(2.0));
+        assert!(it.take_within_band(2.0).is_empty());
+    }
+
+    #[test]
+    fn test_take_within_band_nan() {
+        let v = [1.0, f64::NAN, 1.0];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(vec![1.0], it.take_within_band(f64::INFINITY));
+        assert!(it.next().unwrap().is_nan());
+        assert!(it.take_within_band(f64::INFINITY).is_empty());
+    }
+
+    #[test]
+    fn test_trapezoid_sine() {
+        // The integral of sin(x) from 0 to pi is 2, and the error of the trapezoid rule shrinks
+        // with the square of the step
+        for &(steps, tolerance) in &[(10, 2e-2), (100, 2e-4), (1_000, 2e-6)] {
+            let step = ::std::f64::consts::PI / steps as f64;
+            let samples = (0..=steps).map(|i| {
+                let x = i as f64 * step;
+                (x, x.sin())
+            });
+
+            let area = PrevPeekable::new(samples).trapezoid();
+            assert!((area - 2.0).abs() < tolerance, "{} steps: {}", steps, area);
+        }
+    }
+
+    #[test]
+    fn test_trapezoid_few_samples() {
+        assert_eq!(0.0, PrevPeekable::new(::std::iter::empty()).trapezoid());
+        assert_eq!(0.0, PrevPeekable::new(Some((1.0, 5.0)).into_iter()).trapezoid());
+        assert_eq!(
+            vec![0.0],
+            PrevPeekable::new(Some((1.0, 5.0)).into_iter())
+                .cumulative_trapezoid()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_trapezoid_decreasing_x() {
+        // Going back over the same interval cancels out
+        let samples = [(0.0, 1.0), (2.0, 3.0), (0.0, 1.0)];
+        let areas: Vec<_> = PrevPeekable::new(samples.iter().cloned())
+            .cumulative_trapezoid()
+            .collect();
+
+        assert_eq!(vec![0.0, 4.0, 0.0], areas);
+    }
+
+    #[test]
+    fn test_cumulative_trapezoid_parabola() {
+        // The integral of 3x^2 is x^3, and the trapezoid rule overestimates a convex function
+        let samples = (0..=4).map(|i| {
+            let x = f64::from(i) / 2.0;
+            (x, 3.0 * x * x)
+        });
+        let areas: Vec<_> = PrevPeekable::new(samples).cumulative_trapezoid().collect();
+
+        assert_eq!(5, areas.len());
+        for (i, area) in areas.iter().enumerate() {
+            let x = i as f64 / 2.0;
+            assert!(*area >= x * x * x);
+            assert!(*area - x * x * x <= 0.125 * x + 1e-12, "x = {}: {}", x, area);
+        }
+    }
+
+    #[test]
+    fn test_rates() {
+        let samples = [(0.0, 0.0), (2.0, 10.0), (4.0, 10.0), (5.0, 13.0)];
+        let rates: Vec<_> = PrevPeekable::new(samples.iter().cloned())
+            .rates(CounterReset::Keep)
+            .collect();
+
+        assert_eq!(vec![(2.0, 5.0), (4.0, 0.0), (5.0, 3.0)], rates);
+        assert_eq!(
+            None,
+            PrevPeekable::new(samples[..1].iter().cloned())
+                .rates(CounterReset::Keep)
+                .next()
+        );
+    }
+
+    #[test]
+    fn test_rates_reset() {
+        let samples = [(0.0, 50.0), (1.0, 60.0), (2.0, 4.0), (3.0, 8.0)];
+
+        let kept: Vec<_> = PrevPeekable::new(samples.iter().cloned())
+            .rates(CounterReset::Keep)
+            .collect();
+        assert_eq!(vec![(1.0, 10.0), (2.0, -56.0), (3.0, 4.0)], kept);
+
+        let clamped: Vec<_> = PrevPeekable::new(samples.iter().cloned())
+            .rates(CounterReset::Clamp)
+            .collect();
+        assert_eq!(vec![(1.0, 10.0), (2.0, 0.0), (3.0, 4.0)], clamped);
+    }
+
+    #[test]
+    fn test_rates_zero_time_delta() {
+        // Re-reported and out of order samples are skipped, and the next rate is measured from the
+        // last sample that wasn't
+        let samples = [
+            (0.0, 0.0),
+            (1.0, 2.0),
+            (1.0, 2.0),
+            (0.5, 0.5),
+            (2.0, 4.0),
+            (f64::NAN, 5.0),
+        ];
+        let rates: Vec<_> = PrevPeekable::new(samples.iter().cloned())
+            .rates(CounterReset::Keep)
+            .collect();
+
+        assert_eq!(vec![(1.0, 2.0), (2.0, 2.0)], rates);
+    }
+
+    #[test]
+    fn test_rates_instant() {
+        let start = Instant::now();
+        let samples = vec![
+            (start, 0.0),
+            (start + Duration::from_millis(500), 1.0),
+            (start + Duration::from_millis(250), 2.0),
+            (start + Duration::from_secs(2), 4.0),
+        ];
+        let rates: Vec<_> = PrevPeekable::new(samples.into_iter())
+            .rates(CounterReset::Keep)
+            .collect();
+
+        assert_eq!(
+            vec![
+                (start + Duration::from_millis(500), 2.0),
+                (start + Duration::from_secs(2), 3.0 / 1.5),
+            ],
+            rates
+        );
+    }
+
+    #[test]
+    fn test_second_delta() {
+        let v = [1, 2, 4, 7];
+        let second: Vec<_> = PrevPeekable::new(v.iter().cloned()).second_delta().collect();
+
+        assert_eq!(vec![1, 1], second);
+        assert_eq!(
+            None,
+            PrevPeekable::new(v[..2].iter().cloned()).second_delta().next()
+        );
+    }
+
+    #[test]
+    fn test_second_delta_curvature() {
+        // Squares have a constant second difference, and a straight line has none
+        let squares: Vec<_> = PrevPeekable::new((0..6).map(|x: i32| x * x))
+            .second_delta()
+            .collect();
+        assert_eq!(vec![2; 4], squares);
+
+        let line = [0.5, 2.0, 3.5, 5.0];
+        let second: Vec<_> = PrevPeekable::new(line.iter().cloned()).second_delta().collect();
+        assert_eq!(vec![0.0, 0.0], second);
+    }
+}
@@ -0,0 +1,204 @@
+//! Adapters over streams of `Option`s, such as data with missing values.
+
+use super::PrevPeekable;
+
+impl<I, T> PrevPeekable<I>
+where
+    I: Iterator<Item = Option<T>>,
+    T: Clone,
+{
+    /// Replaces every `None` with the last `Some` before it, and passes every `Some` through.
+    /// `None`s before the first `Some` stay `None`, since there's nothing to fill them with.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let readings = vec![None, Some(20.5), None, Some(21.0)];
+    /// let filled: Vec<_> = PrevPeekable::new(readings.into_iter()).forward_fill().collect();
+    ///
+    /// assert_eq!(vec![None, Some(20.5), Some(20.5), Some(21.0)], filled);
+    /// ```
+    pub fn forward_fill(mut self) -> impl Iterator<Item = Option<T>> {
+        let mut last: Option<T> = None;
+
+        ::std::iter::from_fn(move || {
+            let cur = self.next()?;
+            if cur.is_some() {
+                last = cur.clone();
+            }
+
+            Some(cur.or_else(|| last.clone()))
+        })
+    }
+
+    /// Replaces every `None` with the first `Some` after it, and passes every `Some` through. This
+    /// is the counterpart to [`forward_fill`] for data that should be filled backwards. `None`s
+    /// after the last `Some` stay `None`, since there's nothing to fill them with.
+    ///
+    /// A gap can't be filled until the value after it has been read, so a run of `None`s is read
+    /// ahead in full before any of it is returned. Only the length of the run is kept.
+    ///
+    /// [`forward_fill`]: #method.forward_fill
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let readings = vec![None, Some(20.5), None, None, Some(21.0)];
+    /// let filled: Vec<_> = PrevPeekable::new(readings.into_iter()).fill_none_with_next().collect();
+    ///
+    /// assert_eq!(vec![Some(20.5), Some(20.5), Some(21.0), Some(21.0), Some(21.0)], filled);
+    /// ```
+    pub fn fill_none_with_next(mut self) -> impl Iterator<Item = Option<T>> {
+        // How many `None`s of the current gap are left to return, and the value that ended it
+        let mut gap = 0;
+        let mut fill: Option<T> = None;
+
+        ::std::iter::from_fn(move || {
+            if gap == 0 {
+                if let Some(value) = fill.take() {
+                    return Some(Some(value));
+                }
+
+                loop {
+                    match self.next() {
+                        Some(Some(value)) if gap == 0 => return Some(Some(value)),
+                        Some(Some(value)) => {
+                            fill = Some(value);
+                            break;
+                        }
+                        Some(None) => gap += 1,
+                        None if gap == 0 => return None,
+                        None => break,
+                    }
+                }
+            }
+
+            gap -= 1;
+            Some(fill.clone())
+        })
+    }
+
+    /// Collapses every run of consecutive `None`s into a single `None`, and passes every `Some`
+    /// through. This keeps a gap visible in sparse data without repeating it for every missing
+    /// value.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let readings = vec![Some(20.5), None, None, None, Some(21.0), Some(21.5)];
+    /// let compacted: Vec<_> = PrevPeekable::new(readings.into_iter()).compact_none().collect();
+    ///
+    /// assert_eq!(vec![Some(20.5), None, Some(21.0), Some(21.5)], compacted);
+    /// ```
+    pub fn compact_none(mut self) -> impl Iterator<Item = Option<T>> {
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+
+            if cur.is_some() || !matches!(self.peek_prev(), Some(None)) {
+                return Some(cur);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::RecordingIterator;
+
+    #[test]
+    fn test_forward_fill() {
+        let v = [Some(1), None, None, Some(2), None];
+        let filled: Vec<_> = PrevPeekable::new(v.iter().cloned()).forward_fill().collect();
+
+        assert_eq!(vec![Some(1), Some(1), Some(1), Some(2), Some(2)], filled);
+    }
+
+    #[test]
+    fn test_forward_fill_leading_none() {
+        let v = [None, None, Some('a'), None];
+        let filled: Vec<_> = PrevPeekable::new(v.iter().cloned()).forward_fill().collect();
+
+        assert_eq!(vec![None, None, Some('a'), Some('a')], filled);
+    }
+
+    #[test]
+    fn test_fill_none_with_next() {
+        let v = [None, Some(1), None, None, Some(2), Some(3), None];
+        let filled: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .fill_none_with_next()
+            .collect();
+
+        assert_eq!(
+            vec![Some(1), Some(1), Some(2), Some(2), Some(2), Some(3), None],
+            filled
+        );
+    }
+
+    #[test]
+    fn test_fill_none_with_next_all_none() {
+        let v: [Option<char>; 3] = [None, None, None];
+        let filled: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .fill_none_with_next()
+            .collect();
+
+        assert_eq!(vec![None, None, None], filled);
+        assert_eq!(
+            None,
+            PrevPeekable::new(v[..0].iter().cloned())
+                .fill_none_with_next()
+                .next()
+        );
+    }
+
+    #[test]
+    fn test_fill_none_with_next_reads_whole_gap() {
+        let source = RecordingIterator::new(vec![None, None, Some('a'), Some('b')].into_iter());
+        let recording = source.recording();
+        let mut filled = PrevPeekable::new(source).fill_none_with_next();
+
+        assert_eq!(Some(Some('a')), filled.next());
+        recording.assert_next_calls(3);
+        assert_eq!(Some(Some('a')), filled.next());
+        assert_eq!(Some(Some('a')), filled.next());
+        recording.assert_next_calls(3);
+        assert_eq!(Some(Some('b')), filled.next());
+        recording.assert_next_calls(4);
+    }
+
+    #[test]
+    fn test_compact_none() {
+        let v = [Some(1), None, None, Some(2), None];
+        let compacted: Vec<_> = PrevPeekable::new(v.iter().cloned()).compact_none().collect();
+
+        assert_eq!(vec![Some(1), None, Some(2), None], compacted);
+    }
+
+    #[test]
+    fn test_compact_none_edges() {
+        let compact = |v: &[Option<i32>]| -> Vec<Option<i32>> {
+            PrevPeekable::new(v.iter().cloned()).compact_none().collect()
+        };
+
+        assert_eq!(Vec::<Option<i32>>::new(), compact(&[]));
+        assert_eq!(vec![None], compact(&[None, None, None]));
+        assert_eq!(
+            vec![None, Some(3), None],
+            compact(&[None, None, Some(3), None, None])
+        );
+        // Equal `Some`s are kept
+        assert_eq!(vec![Some(1), Some(1)], compact(&[Some(1), Some(1)]));
+    }
+}
@@ -0,0 +1,373 @@
+//! Queries about the order of consecutive elements.
+
+use super::PrevPeekable;
+use std::cmp::Ordering;
+
+/// How the elements of an iterator are ordered, as returned by [`monotonicity`].
+///
+/// [`monotonicity`]: struct.PrevPeekableWith.html#method.monotonicity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Monotonicity {
+    /// Every element is greater than the one before it.
+    StrictlyIncreasing,
+    /// Every element is less than the one before it.
+    StrictlyDecreasing,
+    /// Every element is greater than or equal to the one before it, and at least one is equal.
+    NonDecreasing,
+    /// Every element is less than or equal to the one before it, and at least one is equal, but
+    /// not all of them.
+    NonIncreasing,
+    /// Some element is greater than the one before it and another is less, or some elements
+    /// can't be compared.
+    NotMonotonic,
+    /// There are fewer than two elements, so there is nothing to compare.
+    Trivial,
+}
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Consumes elements until one is less than the element before it, and returns its index. The
+    /// index counts the elements returned by this call, starting from 0. Returns `None` if the rest
+    /// of the iterator is non-decreasing.
+    ///
+    /// The iterator stops at the descent, so [`prev`] and [`current`] return the offending pair.
+    ///
+    /// [`prev`]: struct.PrevPeekableWith.html#method.prev
+    /// [`current`]: struct.PrevPeekableWith.html#method.current
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 4, 9, 7, 12];
+    /// let mut it = PrevPeekable::new(v.into_iter());
+    ///
+    /// assert_eq!(Some(3), it.first_descent());
+    /// assert_eq!(Some(9), it.prev());
+    /// assert_eq!(Some(&7), it.current());
+    /// ```
+    pub fn first_descent(&mut self) -> Option<usize>
+    where
+        I::Item: PartialOrd,
+    {
+        let mut index = 0;
+
+        while let Some(cur) = self.next() {
+            if let Some(prev) = self.peek_prev() {
+                if cur < *prev {
+                    return Some(index);
+                }
+            }
+
+            index += 1;
+        }
+
+        None
+    }
+
+    /// Yields the length of each maximal non-decreasing run of elements. A new run starts whenever
+    /// an element is less than the one before it, which makes this useful for finding the natural
+    /// runs a merge sort would start from.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 3, 2, 4, 5, 1];
+    /// let lengths: Vec<_> = PrevPeekable::new(v.into_iter()).ascending_run_lengths().collect();
+    ///
+    /// assert_eq!(vec![2, 3, 1], lengths);
+    /// ```
+    pub fn ascending_run_lengths(mut self) -> impl Iterator<Item = usize>
+    where
+        I::Item: PartialOrd,
+    {
+        let mut len = 0;
+
+        ::std::iter::from_fn(move || loop {
+            let cur = match self.next() {
+                Some(cur) => cur,
+                None if len == 0 => return None,
+                None => return Some(::std::mem::replace(&mut len, 0)),
+            };
+
+            // On a partly consumed iterator, the first element read still starts the first run
+            if len > 0 {
+                if let Some(prev) = self.peek_prev() {
+                    if cur < *prev {
+                        return Some(::std::mem::replace(&mut len, 1));
+                    }
+                }
+            }
+
+            len += 1;
+        })
+    }
+
+    /// Yields the first element of each maximal non-decreasing run, which is the first element and
+    /// every element that's less than the one before it. These are the points where a series
+    /// resets, such as a counter wrapping around.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 3, 2, 4, 5, 1];
+    /// let starts: Vec<_> = PrevPeekable::new(v.into_iter()).ascending_run_starts().collect();
+    ///
+    /// assert_eq!(vec![1, 2, 1], starts);
+    /// ```
+    pub fn ascending_run_starts(mut self) -> impl Iterator<Item = I::Item>
+    where
+        I::Item: PartialOrd,
+    {
+        let mut first = true;
+
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+            let starts_run = match self.peek_prev() {
+                Some(prev) => cur < *prev,
+                None => true,
+            };
+
+            if ::std::mem::replace(&mut first, false) || starts_run {
+                return Some(cur);
+            }
+        })
+    }
+
+    /// Consumes the iterator and returns one character for each element after the first: `'+'` if
+    /// it's greater than the element before it, `'-'` if it's less, and `'='` if they're equal.
+    /// This gives a compact summary of the shape of a series, for logs and test failures.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new(vec![3, 5, 8, 8, 2].into_iter());
+    ///
+    /// assert_eq!("++=-", it.direction_string());
+    /// ```
+    pub fn direction_string(&mut self) -> String
+    where
+        I::Item: Ord,
+    {
+        let mut directions = String::new();
+
+        while let Some(cur) = self.next() {
+            if let Some(prev) = self.peek_prev() {
+                directions.push(match cur.cmp(prev) {
+                    Ordering::Greater => '+',
+                    Ordering::Less => '-',
+                    Ordering::Equal => '=',
+                });
+            }
+        }
+
+        directions
+    }
+
+    /// Compares every element to the one before it, and returns which [`Monotonicity`] the
+    /// elements have. Elements that can't be compared, like `NaN`, make them
+    /// [`NotMonotonic`].
+    ///
+    /// Fewer than two elements are [`Trivial`]. Two or more elements that are all equal are
+    /// [`NonDecreasing`], although they're non-increasing as well. The iterator is consumed up to
+    /// the element that shows they aren't monotonic, or to the end.
+    ///
+    /// [`Monotonicity`]: enum.Monotonicity.html
+    /// [`NotMonotonic`]: enum.Monotonicity.html#variant.NotMonotonic
+    /// [`Trivial`]: enum.Monotonicity.html#variant.Trivial
+    /// [`NonDecreasing`]: enum.Monotonicity.html#variant.NonDecreasing
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{Monotonicity, PrevPeekable};
+    ///
+    /// let monotonicity = |v: Vec<i32>| PrevPeekable::new(v.into_iter()).monotonicity();
+    ///
+    /// assert_eq!(Monotonicity::StrictlyIncreasing, monotonicity(vec![1, 2, 5]));
+    /// assert_eq!(Monotonicity::NonIncreasing, monotonicity(vec![5, 5, 1]));
+    /// assert_eq!(Monotonicity::NotMonotonic, monotonicity(vec![1, 5, 2]));
+    /// ```
+    pub fn monotonicity(&mut self) -> Monotonicity
+    where
+        I::Item: PartialOrd,
+    {
+        let (mut rises, mut falls, mut flats) = (false, false, false);
+
+        while let Some(cur) = self.next() {
+            if let Some(prev) = self.peek_prev() {
+                match cur.partial_cmp(prev) {
+                    Some(Ordering::Greater) => rises = true,
+                    Some(Ordering::Less) => falls = true,
+                    Some(Ordering::Equal) => flats = true,
+                    None => return Monotonicity::NotMonotonic,
+                }
+            }
+
+            if rises && falls {
+                return Monotonicity::NotMonotonic;
+            }
+        }
+
+        match (rises, falls, flats) {
+            (true, _, false) => Monotonicity::StrictlyIncreasing,
+            (_, true, false) => Monotonicity::StrictlyDecreasing,
+            (true, _, true) | (false, false, true) => Monotonicity::NonDecreasing,
+            (_, true, true) => Monotonicity::NonIncreasing,
+            (false, false, false) => Monotonicity::Trivial,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_descent() {
+        let v = [1, 2, 2, 1, 5];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!(Some(3), it.first_descent());
+        assert_eq!(Some(&2), it.prev());
+        assert_eq!(Some(&&1), it.current());
+        // The rest is non-decreasing
+        assert_eq!(None, it.first_descent());
+    }
+
+    #[test]
+    fn test_first_descent_sorted() {
+        let v = [1, 2, 2, 3, 5];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!(None, it.first_descent());
+        assert_eq!(None, it.next());
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).first_descent());
+    }
+
+    #[test]
+    fn test_ascending_run_lengths() {
+        let v = [1, 3, 2, 4, 5, 1];
+        let lengths: Vec<_> = PrevPeekable::new(v.iter()).ascending_run_lengths().collect();
+
+        assert_eq!(vec![2, 3, 1], lengths);
+    }
+
+    #[test]
+    fn test_ascending_run_lengths_edges() {
+        let lengths =
+            |v: &[i32]| -> Vec<usize> { PrevPeekable::new(v.iter()).ascending_run_lengths().collect() };
+
+        assert_eq!(Vec::<usize>::new(), lengths(&[]));
+        assert_eq!(vec![1], lengths(&[7]));
+        // Equal elements don't break a run
+        assert_eq!(vec![4], lengths(&[1, 1, 2, 2]));
+        assert_eq!(vec![1, 1, 1], lengths(&[3, 2, 1]));
+
+        // The first element read starts a run, even if it's less than the one already returned
+        let mut it = PrevPeekable::new([5, 1, 2, 0].iter().cloned());
+        it.next();
+        assert_eq!(vec![2, 1], it.ascending_run_lengths().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_ascending_run_starts() {
+        let v = [1, 2, 3, 1, 4, 2];
+        let starts: Vec<_> = PrevPeekable::new(v.iter()).ascending_run_starts().collect();
+
+        assert_eq!(vec![&1, &1, &2], starts);
+
+        // The number of starts matches the number of runs
+        let runs = PrevPeekable::new(v.iter()).ascending_run_lengths().count();
+        assert_eq!(runs, starts.len());
+    }
+
+    #[test]
+    fn test_ascending_run_starts_edges() {
+        let starts = |v: &[i32]| -> Vec<i32> {
+            PrevPeekable::new(v.iter().cloned())
+                .ascending_run_starts()
+                .collect()
+        };
+
+        assert_eq!(Vec::<i32>::new(), starts(&[]));
+        assert_eq!(vec![7], starts(&[7]));
+        assert_eq!(vec![1], starts(&[1, 1, 2, 2]));
+        assert_eq!(vec![3, 2, 1], starts(&[3, 2, 1]));
+
+        // The first element yielded always starts a run, even if the iterator was partly consumed
+        let mut it = PrevPeekable::new([5, 6, 1, 2].iter().cloned());
+        it.next();
+        assert_eq!(vec![6, 1], it.ascending_run_starts().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_direction_string() {
+        let v = [1, 2, 2, 1];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!("+=-", it.direction_string());
+        assert_eq!(None, it.next());
+        assert_eq!("", PrevPeekable::new(v[..1].iter()).direction_string());
+        assert_eq!("", PrevPeekable::new(v[..0].iter()).direction_string());
+    }
+
+    #[test]
+    fn test_direction_string_partially_consumed() {
+        // The first transition is from the element that was already returned
+        let mut it = PrevPeekable::new("abca".chars());
+        it.next();
+
+        assert_eq!("++-", it.direction_string());
+    }
+
+    #[test]
+    fn test_monotonicity() {
+        let monotonicity = |v: &[i32]| PrevPeekable::new(v.iter()).monotonicity();
+
+        assert_eq!(Monotonicity::StrictlyIncreasing, monotonicity(&[1, 2, 3]));
+        assert_eq!(Monotonicity::StrictlyDecreasing, monotonicity(&[3, 2, 1]));
+        assert_eq!(Monotonicity::NonDecreasing, monotonicity(&[1, 1, 2]));
+        assert_eq!(Monotonicity::NonIncreasing, monotonicity(&[3, 2, 2]));
+        assert_eq!(Monotonicity::NotMonotonic, monotonicity(&[1, 3, 2]));
+        assert_eq!(Monotonicity::NotMonotonic, monotonicity(&[2, 2, 1, 3]));
+        assert_eq!(Monotonicity::Trivial, monotonicity(&[]));
+        assert_eq!(Monotonicity::Trivial, monotonicity(&[7]));
+        // All equal counts as non-decreasing
+        assert_eq!(Monotonicity::NonDecreasing, monotonicity(&[4, 4]));
+    }
+
+    #[test]
+    fn test_monotonicity_stops_early() {
+        let v = [1.0, 2.0, 1.0, 5.0];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(Monotonicity::NotMonotonic, it.monotonicity());
+        assert_eq!(Some(5.0), it.next());
+
+        let v = [1.0, f64::NAN, 2.0];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+        assert_eq!(Monotonicity::NotMonotonic, it.monotonicity());
+        assert_eq!(Some(2.0), it.next());
+    }
+}
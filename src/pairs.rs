@@ -0,0 +1,481 @@
+//! Adapters over consecutive pairs of elements.
+
+use super::PrevPeekable;
+use std::collections::VecDeque;
+
+/// A change from one value to a different one, yielded by [`transitions_detailed`].
+///
+/// [`transitions_detailed`]: struct.PrevPeekableWith.html#method.transitions_detailed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Transition<T> {
+    /// The index of the element with the new value.
+    pub index: usize,
+    /// The value before the change.
+    pub from: T,
+    /// The value after the change.
+    pub to: T,
+}
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Yields every consecutive pair as `(index, prev, current)`, where `index` is the position of
+    /// `current`. This is handy for error messages along the lines of "between element N-1 and N".
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![5, 6, 7];
+    /// let pairs: Vec<_> = PrevPeekable::new(v.into_iter()).enumerated_pairs().collect();
+    ///
+    /// assert_eq!(vec![(1, 5, 6), (2, 6, 7)], pairs);
+    /// ```
+    pub fn enumerated_pairs(mut self) -> impl Iterator<Item = (usize, I::Item, I::Item)> {
+        let mut index = 0;
+
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+            index += 1;
+
+            if let Some(prev) = self.prev() {
+                return Some((index - 1, prev, cur));
+            }
+        })
+    }
+
+    /// Yields every pair of elements that are `lag` places apart, as `(earlier, later)`. A `lag` of
+    /// 1 gives the same pairs as `(prev, current)`, and larger lags are what's needed for lagged
+    /// correlations. A `lag` of 0 pairs every element with itself, as `(x, x)`. Only the last `lag`
+    /// elements are buffered, and the buffer grows as they're read rather than being allocated up
+    /// front.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 2, 3, 4, 5];
+    /// let pairs: Vec<_> = PrevPeekable::new(v.into_iter()).lagged_pairs(2).collect();
+    ///
+    /// assert_eq!(vec![(1, 3), (2, 4), (3, 5)], pairs);
+    /// ```
+    pub fn lagged_pairs(mut self, lag: usize) -> impl Iterator<Item = (I::Item, I::Item)> {
+        let mut buffer = VecDeque::new();
+
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+            buffer.push_back(cur.clone());
+
+            if buffer.len() > lag {
+                let earlier = buffer
+                    .pop_front()
+                    .expect("the buffer holds more than `lag` elements");
+                return Some((earlier, cur));
+            }
+        })
+    }
+
+    /// Yields every consecutive pair from the end of the iterator towards the start, as
+    /// `(later, earlier)`, which is the order needed to undo a sequence of steps. The last pair
+    /// ends with the current element, if there is one, just like the first pair of other adapters
+    /// starts with it.
+    ///
+    /// The source is read from the back, so nothing is buffered other than elements that
+    /// [`peek_nth`] already buffered.
+    ///
+    /// [`peek_nth`]: struct.PrevPeekableWith.html#method.peek_nth
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let states = vec!["empty", "draft", "review", "published"];
+    /// let undo: Vec<_> = PrevPeekable::new(states.into_iter()).pairs_reversed().collect();
+    ///
+    /// assert_eq!(vec![("published", "review"), ("review", "draft"), ("draft", "empty")], undo);
+    /// ```
+    pub fn pairs_reversed(self) -> impl Iterator<Item = (I::Item, I::Item)>
+    where
+        I: DoubleEndedIterator,
+    {
+        let rest = self.source.buffer.into_iter().chain(self.source.iterator);
+        let mut reversed = PrevPeekable::new(rest.rev().chain(self.current));
+
+        ::std::iter::from_fn(move || loop {
+            let earlier = reversed.next()?;
+
+            if let Some(later) = reversed.prev() {
+                return Some((later, earlier));
+            }
+        })
+    }
+
+    /// Yields a [`Transition`] for every element that differs from the element before it, along
+    /// with its index. Indices count the elements returned by this adapter, starting from 0, so
+    /// `index` is where the new value first appears.
+    ///
+    /// [`Transition`]: struct.Transition.html
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{PrevPeekable, Transition};
+    ///
+    /// let v = vec!["idle", "idle", "busy", "idle"];
+    /// let transitions: Vec<_> = PrevPeekable::new(v.into_iter()).transitions_detailed().collect();
+    ///
+    /// assert_eq!(
+    ///     vec![
+    ///         Transition { index: 2, from: "idle", to: "busy" },
+    ///         Transition { index: 3, from: "busy", to: "idle" },
+    ///     ],
+    ///     transitions
+    /// );
+    /// ```
+    pub fn transitions_detailed(mut self) -> impl Iterator<Item = Transition<I::Item>>
+    where
+        I::Item: PartialEq,
+    {
+        let mut index = 0;
+
+        ::std::iter::from_fn(move || loop {
+            let to = self.next()?;
+            index += 1;
+
+            match self.prev() {
+                Some(from) if from != to => {
+                    return Some(Transition {
+                        index: index - 1,
+                        from,
+                        to,
+                    })
+                }
+                _ => {}
+            }
+        })
+    }
+
+    /// Collects every consecutive `(prev, current)` pair, calling `validate` on each one first. The
+    /// first error returned by `validate` is returned right away, without reading the rest of the
+    /// iterator.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let ascending = |prev: &i32, cur: &i32| if prev < cur { Ok(()) } else { Err(*cur) };
+    ///
+    /// let v = vec![1, 2, 3];
+    /// assert_eq!(Ok(vec![(1, 2), (2, 3)]), PrevPeekable::new(v.into_iter()).try_collect_pairs(ascending));
+    ///
+    /// let v = vec![1, 3, 2, 4];
+    /// assert_eq!(Err(2), PrevPeekable::new(v.into_iter()).try_collect_pairs(ascending));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn try_collect_pairs<E, F>(mut self, mut validate: F) -> Result<Vec<(I::Item, I::Item)>, E>
+    where
+        F: FnMut(&I::Item, &I::Item) -> Result<(), E>,
+    {
+        let mut pairs = Vec::new();
+
+        while let Some(cur) = self.next() {
+            if let Some(prev) = self.prev() {
+                validate(&prev, &cur)?;
+                pairs.push((prev, cur));
+            }
+        }
+
+        Ok(pairs)
+    }
+
+    /// Yields the index of every element that immediately follows an element equal to `value`.
+    /// The first element is never yielded, since there's nothing before it.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// // Find every token that follows a comma
+    /// let tokens = vec!["a", ",", "b", ",", "c"];
+    /// let indices: Vec<_> = PrevPeekable::new(tokens.into_iter()).indices_after(",").collect();
+    ///
+    /// assert_eq!(vec![2, 4], indices);
+    /// ```
+    pub fn indices_after<V>(mut self, value: V) -> impl Iterator<Item = usize>
+    where
+        I::Item: PartialEq<V>,
+    {
+        let mut index = 0;
+
+        ::std::iter::from_fn(move || loop {
+            self.next()?;
+            index += 1;
+
+            if let Some(prev) = self.peek_prev() {
+                if *prev == value {
+                    return Some(index - 1);
+                }
+            }
+        })
+    }
+
+    /// Collects every consecutive `(prev, current)` pair as a directed edge from `prev` to
+    /// `current`, such as to build a graph of the path the elements walk through. An element that
+    /// appears more than once shows up in every edge it's part of.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let path = vec!["home", "search", "product", "search"];
+    /// let edges = PrevPeekable::new(path.into_iter()).to_edges();
+    ///
+    /// assert_eq!(vec![("home", "search"), ("search", "product"), ("product", "search")], edges);
+    /// ```
+    pub fn to_edges(mut self) -> Vec<(I::Item, I::Item)> {
+        let mut edges = Vec::new();
+
+        while let Some(cur) = self.next() {
+            if let Some(prev) = self.prev() {
+                edges.push((prev, cur));
+            }
+        }
+
+        edges
+    }
+
+    /// Like [`to_edges`], but for undirected graphs: every edge is followed by its reverse, from
+    /// `current` to `prev`.
+    ///
+    /// [`to_edges`]: #method.to_edges
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 2, 3];
+    /// let edges = PrevPeekable::new(v.into_iter()).to_edges_undirected();
+    ///
+    /// assert_eq!(vec![(1, 2), (2, 1), (2, 3), (3, 2)], edges);
+    /// ```
+    pub fn to_edges_undirected(mut self) -> Vec<(I::Item, I::Item)> {
+        let mut edges = Vec::new();
+
+        while let Some(cur) = self.next() {
+            if let Some(prev) = self.prev() {
+                edges.push((prev.clone(), cur.clone()));
+                edges.push((cur, prev));
+            }
+        }
+
+        edges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::RecordingIterator;
+
+    #[test]
+    fn test_enumerated_pairs() {
+        let v = [5, 6, 7];
+        let pairs: Vec<_> = PrevPeekable::new(v.iter()).enumerated_pairs().collect();
+
+        assert_eq!(vec![(1, &5, &6), (2, &6, &7)], pairs);
+    }
+
+    #[test]
+    fn test_enumerated_pairs_short() {
+        let v = [5];
+
+        assert_eq!(None, PrevPeekable::new(v.iter()).enumerated_pairs().next());
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).enumerated_pairs().next());
+    }
+
+    #[test]
+    fn test_lagged_pairs() {
+        let v = [1, 2, 3, 4, 5];
+        let pairs: Vec<_> = PrevPeekable::new(v.iter().cloned()).lagged_pairs(2).collect();
+
+        assert_eq!(vec![(1, 3), (2, 4), (3, 5)], pairs);
+
+        let pairs: Vec<_> = PrevPeekable::new(v.iter().cloned()).lagged_pairs(1).collect();
+        let enumerated: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .enumerated_pairs()
+            .map(|(_, prev, cur)| (prev, cur))
+            .collect();
+        assert_eq!(enumerated, pairs);
+    }
+
+    #[test]
+    fn test_lagged_pairs_edge_cases() {
+        let v = [1, 2, 3];
+
+        let pairs: Vec<_> = PrevPeekable::new(v.iter()).lagged_pairs(0).collect();
+        assert_eq!(vec![(&1, &1), (&2, &2), (&3, &3)], pairs);
+        assert_eq!(None, PrevPeekable::new(v.iter()).lagged_pairs(3).next());
+        assert_eq!(Some((&1, &3)), PrevPeekable::new(v.iter()).lagged_pairs(2).next());
+        assert_eq!(None, PrevPeekable::new(v.iter()).lagged_pairs(usize::MAX).next());
+    }
+
+    #[test]
+    fn test_pairs_reversed() {
+        let v = [1, 2, 3];
+        let pairs: Vec<_> = PrevPeekable::new(v.iter().cloned()).pairs_reversed().collect();
+
+        assert_eq!(vec![(3, 2), (2, 1)], pairs);
+        assert_eq!(None, PrevPeekable::new(v[..1].iter()).pairs_reversed().next());
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).pairs_reversed().next());
+    }
+
+    #[test]
+    fn test_pairs_reversed_partially_consumed() {
+        let v = [1, 2, 3, 4, 5];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Some(2), it.next());
+        assert_eq!(Some(&4), it.peek_nth(1));
+        let pairs: Vec<_> = it.pairs_reversed().collect();
+
+        // The current element ends the last pair, and the buffered elements are included
+        assert_eq!(vec![(5, 4), (4, 3), (3, 2)], pairs);
+
+        let mut it = PrevPeekable::new(v.iter().cloned());
+        it.by_ref().for_each(drop);
+        assert_eq!(None, it.pairs_reversed().next());
+    }
+
+    #[test]
+    fn test_transitions_detailed() {
+        let v = [1, 1, 2, 3];
+        let transitions: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .transitions_detailed()
+            .collect();
+
+        assert_eq!(
+            vec![
+                Transition {
+                    index: 2,
+                    from: 1,
+                    to: 2
+                },
+                Transition {
+                    index: 3,
+                    from: 2,
+                    to: 3
+                },
+            ],
+            transitions
+        );
+    }
+
+    #[test]
+    fn test_transitions_detailed_none() {
+        assert_eq!(
+            None,
+            PrevPeekable::new([4, 4, 4].iter()).transitions_detailed().next()
+        );
+        assert_eq!(None, PrevPeekable::new([4].iter()).transitions_detailed().next());
+        assert_eq!(
+            None,
+            PrevPeekable::new([0; 0].iter()).transitions_detailed().next()
+        );
+    }
+
+    #[test]
+    fn test_try_collect_pairs() {
+        let v = [1, 2, 4];
+        let pairs: Result<_, ()> = PrevPeekable::new(v.iter()).try_collect_pairs(|_, _| Ok(()));
+
+        assert_eq!(Ok(vec![(&1, &2), (&2, &4)]), pairs);
+    }
+
+    #[test]
+    fn test_try_collect_pairs_stops_at_first_error() {
+        let v = [1, 2, 1, 0, 5];
+        let source = RecordingIterator::new(v.iter());
+        let recording = source.recording();
+        let pairs = PrevPeekable::new(source).try_collect_pairs(|prev, cur| {
+            if prev < cur {
+                Ok(())
+            } else {
+                Err((**prev, **cur))
+            }
+        });
+
+        assert_eq!(Err((2, 1)), pairs);
+        // Nothing after the failing pair was read
+        recording.assert_yielded(&[&1, &2, &1]);
+    }
+
+    #[test]
+    fn test_try_collect_pairs_short() {
+        let v = [1];
+        let pairs: Result<_, ()> = PrevPeekable::new(v.iter()).try_collect_pairs(|_, _| Err(()));
+
+        assert_eq!(Ok(vec![]), pairs);
+    }
+
+    #[test]
+    fn test_indices_after() {
+        let v = [',', 'a', 'b', ',', 'c'];
+        let indices: Vec<_> = PrevPeekable::new(v.iter().cloned()).indices_after(',').collect();
+
+        assert_eq!(vec![1, 4], indices);
+    }
+
+    #[test]
+    fn test_indices_after_repeated_value() {
+        let v = [0, 0, 0, 1];
+        let indices: Vec<_> = PrevPeekable::new(v.iter().cloned()).indices_after(0).collect();
+
+        assert_eq!(vec![1, 2, 3], indices);
+    }
+
+    #[test]
+    fn test_to_edges() {
+        let v = [1, 2, 3];
+
+        assert_eq!(
+            vec![(1, 2), (2, 3)],
+            PrevPeekable::new(v.iter().cloned()).to_edges()
+        );
+        assert!(PrevPeekable::new(v[..1].iter()).to_edges().is_empty());
+    }
+
+    #[test]
+    fn test_to_edges_undirected() {
+        let v = [1, 2, 3];
+        let edges = PrevPeekable::new(v.iter().cloned()).to_edges_undirected();
+
+        assert_eq!(vec![(1, 2), (2, 1), (2, 3), (3, 2)], edges);
+        assert!(PrevPeekable::new(v[..1].iter()).to_edges_undirected().is_empty());
+    }
+}
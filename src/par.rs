@@ -0,0 +1,146 @@
+//! Parallel processing of consecutive pairs, available with the `rayon` feature.
+//!
+//! [`PrevPeekable`] is inherently sequential, and calling `par_bridge()` on it loses ordering and
+//! is slow. When the data lives in a slice the pairs are independent, so these functions expose them
+//! as indexed parallel iterators which keep their ordering and split exactly.
+//!
+//! [`PrevPeekable`]: ../type.PrevPeekable.html
+
+use rayon::prelude::*;
+use std::ops::Sub;
+
+/// Returns a parallel iterator over every consecutive `(prev, current)` pair in `slice`.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// extern crate rayon;
+/// # extern crate prev_iter;
+/// use prev_iter::par::par_pairwise;
+/// use rayon::prelude::*;
+///
+/// let v = [1, 2, 3];
+/// let pairs: Vec<_> = par_pairwise(&v).collect();
+///
+/// assert_eq!(vec![(&1, &2), (&2, &3)], pairs);
+/// ```
+pub fn par_pairwise<T>(slice: &[T]) -> impl IndexedParallelIterator<Item = (&T, &T)>
+where
+    T: Sync,
+{
+    slice.par_windows(2).map(|w| (&w[0], &w[1]))
+}
+
+/// Returns a parallel iterator over `current - prev` for every consecutive pair in `slice`.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// extern crate rayon;
+/// # extern crate prev_iter;
+/// use prev_iter::par::par_deltas;
+/// use rayon::prelude::*;
+///
+/// let v = [1, 4, 9, 16];
+/// let deltas: Vec<_> = par_deltas(&v).collect();
+///
+/// assert_eq!(vec![3, 5, 7], deltas);
+/// ```
+pub fn par_deltas<T>(slice: &[T]) -> impl IndexedParallelIterator<Item = T> + '_
+where
+    T: Clone + Sub<Output = T> + Send + Sync,
+{
+    slice.par_windows(2).map(|w| w[1].clone() - w[0].clone())
+}
+
+/// Collects every consecutive `(prev, current)` pair in `slice` in parallel, cloning the elements.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use prev_iter::par::collect_pairs_par;
+///
+/// let v = [1, 2, 3];
+///
+/// assert_eq!(vec![(1, 2), (2, 3)], collect_pairs_par(&v));
+/// ```
+pub fn collect_pairs_par<T>(slice: &[T]) -> Vec<(T, T)>
+where
+    T: Clone + Send + Sync,
+{
+    par_pairwise(slice)
+        .map(|(prev, cur)| (prev.clone(), cur.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PrevPeekable;
+
+    /// Generates a deterministic pseudo random vector with a linear congruential generator.
+    fn random_vec(len: usize) -> Vec<i64> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+                (state >> 33) as i64 - (1 << 30)
+            })
+            .collect()
+    }
+
+    fn sequential_pairs(v: &[i64]) -> Vec<(i64, i64)> {
+        let mut it = PrevPeekable::new(v.iter().cloned());
+        let mut pairs = Vec::new();
+
+        while let Some(cur) = it.next() {
+            if let Some(prev) = it.prev() {
+                pairs.push((prev, cur));
+            }
+        }
+
+        pairs
+    }
+
+    #[test]
+    fn test_par_pairwise_matches_sequential() {
+        let v = random_vec(100_000);
+        let pairs: Vec<_> = par_pairwise(&v).map(|(a, b)| (*a, *b)).collect();
+
+        assert_eq!(sequential_pairs(&v), pairs);
+    }
+
+    #[test]
+    fn test_par_deltas_matches_sequential() {
+        let v = random_vec(100_000);
+        let deltas: Vec<_> = par_deltas(&v).collect();
+        let expected: Vec<_> = sequential_pairs(&v)
+            .into_iter()
+            .map(|(prev, cur)| cur - prev)
+            .collect();
+
+        assert_eq!(expected, deltas);
+    }
+
+    #[test]
+    fn test_collect_pairs_par_matches_sequential() {
+        let v = random_vec(100_000);
+
+        assert_eq!(sequential_pairs(&v), collect_pairs_par(&v));
+    }
+
+    #[test]
+    fn test_par_pairwise_short() {
+        let empty: [i64; 0] = [];
+        assert_eq!(0, par_pairwise(&empty).len());
+        assert_eq!(0, par_pairwise(&[1]).len());
+        assert_eq!(1, par_pairwise(&[1, 2]).len());
+    }
+}
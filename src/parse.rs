@@ -0,0 +1,338 @@
+//! Helpers for writing parsers on top of `PrevPeekable`.
+
+use super::PrevPeekable;
+use std::fmt::Debug;
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Consumes and returns the next element if it satisfies `pred`. Otherwise nothing is consumed,
+    /// and an error starting with `msg` is returned which describes what was found instead along
+    /// with the element before it, for context.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new("a;b".chars());
+    ///
+    /// assert_eq!(Ok('a'), it.expect(|c| c.is_alphabetic(), "expected a letter"));
+    /// assert_eq!(Ok(';'), it.expect(|&c| c == ';', "expected ';'"));
+    /// assert_eq!(
+    ///     Err("expected ';': found 'b' after ';'".to_string()),
+    ///     it.expect(|&c| c == ';', "expected ';'")
+    /// );
+    /// ```
+    pub fn expect<F>(&mut self, mut pred: F, msg: &str) -> Result<I::Item, String>
+    where
+        F: FnMut(&I::Item) -> bool,
+        I::Item: Debug,
+    {
+        let matches = match self.peek() {
+            Some(next) => pred(next),
+            None => false,
+        };
+
+        if matches {
+            return Ok(self.next().expect("peek() returned an element"));
+        }
+
+        let found = match self.source.peek() {
+            Some(next) => format!("{:?}", next),
+            None => "end of input".to_string(),
+        };
+        Err(match self.last_returned() {
+            Some(before) => format!("{}: found {} after {:?}", msg, found, before),
+            None => format!("{}: found {} at the start of input", msg, found),
+        })
+    }
+
+    /// Advances the iterator once and returns the element it returned along with a clone of the
+    /// element after it, so a parser can branch on both the token it just took and the one coming
+    /// up.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new("<=".chars());
+    ///
+    /// assert_eq!((Some('<'), Some('=')), it.next_and_peek());
+    /// assert_eq!((Some('='), None), it.next_and_peek());
+    /// assert_eq!((None, None), it.next_and_peek());
+    /// ```
+    pub fn next_and_peek(&mut self) -> (Option<I::Item>, Option<I::Item>) {
+        let cur = self.next();
+
+        (cur, self.peek().cloned())
+    }
+
+    /// Consumes and returns the next two elements if `pred(first, second)` returns `true`.
+    /// Otherwise, or if there are fewer than two elements left, nothing is consumed, so a parser can
+    /// try a rule that spans two tokens and fall back to another one if it doesn't match.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new("<=<".chars());
+    ///
+    /// assert_eq!(Some(('<', '=')), it.next_if_pair(|&a, &b| a == '<' && b == '='));
+    /// // Only one element is left
+    /// assert_eq!(None, it.next_if_pair(|_, _| true));
+    /// assert_eq!(Some('<'), it.next());
+    /// ```
+    pub fn next_if_pair<F>(&mut self, mut pred: F) -> Option<(I::Item, I::Item)>
+    where
+        F: FnMut(&I::Item, &I::Item) -> bool,
+    {
+        self.source.peek_nth(1)?;
+        if !pred(&self.source.buffer[0], &self.source.buffer[1]) {
+            return None;
+        }
+
+        let first = self.next().expect("peek_nth(1) returned an element");
+        let second = self.next().expect("peek_nth(1) returned an element");
+        Some((first, second))
+    }
+
+    /// Consumes elements up to the first one that satisfies `start`, then up to the first one after
+    /// it that satisfies `end`, and returns how many places apart they are. Adjacent elements are 1
+    /// apart. Returns `None` if either isn't found, in which case the rest of the iterator has been
+    /// consumed.
+    ///
+    /// The iterator stops at the element matching `end`, so [`current`] returns it afterwards.
+    ///
+    /// [`current`]: struct.PrevPeekableWith.html#method.current
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let mut it = PrevPeekable::new("f(a, b)".chars());
+    ///
+    /// assert_eq!(Some(5), it.distance_between(|&c| c == '(', |&c| c == ')'));
+    /// assert_eq!(Some('b'), it.prev());
+    /// assert_eq!(Some(&')'), it.current());
+    /// ```
+    pub fn distance_between<F, G>(&mut self, mut start: F, mut end: G) -> Option<usize>
+    where
+        F: FnMut(&I::Item) -> bool,
+        G: FnMut(&I::Item) -> bool,
+    {
+        self.find(|x| start(x))?;
+
+        let mut distance = 0;
+        for cur in self {
+            distance += 1;
+            if end(&cur) {
+                return Some(distance);
+            }
+        }
+
+        None
+    }
+
+    /// Consumes elements up to and including the first one that satisfies `pred`, and returns the
+    /// element right before it. Returns `None` if the first element matches, or if none does, in
+    /// which case the rest of the iterator has been consumed.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let tokens = vec!["let", "x", "=", "?", ";"];
+    /// let mut it = PrevPeekable::new(tokens.into_iter());
+    ///
+    /// // What came right before the first bad token
+    /// assert_eq!(Some("="), it.element_before(|&t| t == "?"));
+    /// assert_eq!(Some(";"), it.next());
+    /// ```
+    pub fn element_before<F>(&mut self, mut pred: F) -> Option<I::Item>
+    where
+        F: FnMut(&I::Item) -> bool,
+    {
+        self.find(|x| pred(x))?;
+
+        self.prev()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expect() {
+        let v = [1, 2, 3];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!(Ok(&1), it.expect(|&&x| x == 1, "expected 1"));
+        assert_eq!(Ok(&2), it.expect(|&&x| x == 2, "expected 2"));
+        assert_eq!(Some(&1), it.prev());
+        assert_eq!(Ok(&3), it.expect(|&&x| x == 3, "expected 3"));
+    }
+
+    #[test]
+    fn test_expect_failure() {
+        let v = [1, 2, 3];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!(
+            Err("expected 5: found 1 at the start of input".to_string()),
+            it.expect(|&&x| x == 5, "expected 5")
+        );
+        // Nothing was consumed
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(
+            Err("expected 5: found 2 after 1".to_string()),
+            it.expect(|&&x| x == 5, "expected 5")
+        );
+        assert_eq!(Some(&&2), it.peek());
+    }
+
+    #[test]
+    fn test_expect_end_of_input() {
+        let v = [1];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!(Ok(&1), it.expect(|_| true, "expected anything"));
+        assert_eq!(
+            Err("expected more: found end of input after 1".to_string()),
+            it.expect(|_| true, "expected more")
+        );
+        assert_eq!(None, it.next());
+        assert_eq!(
+            Err("expected more: found end of input after 1".to_string()),
+            it.expect(|_| true, "expected more")
+        );
+    }
+
+    #[test]
+    fn test_next_and_peek() {
+        let v = [1, 2, 3];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!((Some(1), Some(2)), it.next_and_peek());
+        assert_eq!((Some(2), Some(3)), it.next_and_peek());
+        assert_eq!(Some(1), it.prev());
+        assert_eq!((Some(3), None), it.next_and_peek());
+        assert_eq!((None, None), it.next_and_peek());
+        assert_eq!(Some(3), it.prev());
+    }
+
+    #[test]
+    fn test_next_if_pair() {
+        let v = [1, 2, 4, 8];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(Some((1, 2)), it.next_if_pair(|a, b| a < b));
+        assert_eq!(Some(&2), it.current());
+        assert_eq!(Some(1), it.prev());
+        assert_eq!(Some((4, 8)), it.next_if_pair(|a, b| *b == 2 * *a));
+        assert_eq!(None, it.next());
+    }
+
+    #[test]
+    fn test_next_if_pair_mismatch() {
+        let v = [1, 2, 3];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+        it.next();
+
+        let mut calls = 0;
+        assert_eq!(
+            None,
+            it.next_if_pair(|a, b| {
+                calls += 1;
+                a > b
+            })
+        );
+        assert_eq!(1, calls);
+        // Nothing was consumed
+        assert_eq!(Some(&1), it.current());
+        assert_eq!(None, it.prev());
+        assert_eq!(vec![2, 3], it.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_next_if_pair_single_element() {
+        let mut it = PrevPeekable::new(::std::iter::once('x'));
+
+        assert_eq!(None, it.next_if_pair(|_, _| panic!("there is no pair to test")));
+        assert_eq!(Some(&'x'), it.peek());
+        assert_eq!(Some('x'), it.next());
+        assert_eq!(None, it.next_if_pair(|_, _| true));
+    }
+
+    #[test]
+    fn test_distance_between() {
+        let tokens = ["let", "x", "=", "[", "1", ",", "2", "]", ";"];
+        let mut it = PrevPeekable::new(tokens.iter());
+
+        assert_eq!(Some(4), it.distance_between(|&&t| t == "[", |&&t| t == "]"));
+        assert_eq!(Some(&"2"), it.prev());
+        assert_eq!(Some(&&"]"), it.current());
+        assert_eq!(Some(&";"), it.next());
+    }
+
+    #[test]
+    fn test_distance_between_not_found() {
+        let tokens = ["(", "a", ")"];
+
+        // The start element itself doesn't count as the end
+        assert_eq!(
+            Some(1),
+            PrevPeekable::new(tokens.iter()).distance_between(|_| true, |_| true)
+        );
+        assert_eq!(
+            None,
+            PrevPeekable::new(tokens.iter()).distance_between(|&&t| t == "[", |_| true)
+        );
+
+        let mut it = PrevPeekable::new(tokens.iter());
+        assert_eq!(None, it.distance_between(|&&t| t == "(", |&&t| t == "]"));
+        assert_eq!(None, it.next());
+    }
+
+    #[test]
+    fn test_element_before() {
+        let v = [1, 2, 3, 4];
+        let mut it = PrevPeekable::new(v.iter().cloned());
+
+        assert_eq!(Some(2), it.element_before(|&x| x > 2));
+        // The matching element was consumed
+        assert_eq!(Some(&3), it.current());
+        assert_eq!(Some(4), it.next());
+    }
+
+    #[test]
+    fn test_element_before_none() {
+        let v = [1, 2, 3, 4];
+
+        // The first element matches, so nothing came before it
+        let mut it = PrevPeekable::new(v.iter().cloned());
+        assert_eq!(None, it.element_before(|&x| x == 1));
+        assert_eq!(Some(2), it.next());
+
+        let mut it = PrevPeekable::new(v.iter().cloned());
+        assert_eq!(None, it.element_before(|&x| x > 4));
+        assert_eq!(None, it.next());
+    }
+}
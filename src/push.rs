@@ -0,0 +1,255 @@
+//! A `PrevPeekable` that's fed elements by a producer instead of pulling them from an iterator.
+
+use std::collections::VecDeque;
+
+/// What [`PushPrevPeekable::next`] and [`PushPrevPeekable::peek`] found.
+///
+/// [`PushPrevPeekable::next`]: struct.PushPrevPeekable.html#method.next
+/// [`PushPrevPeekable::peek`]: struct.PushPrevPeekable.html#method.peek
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PushNext<T> {
+    /// The next element.
+    Ready(T),
+    /// Every element pushed so far has been returned, but more can still be pushed.
+    NeedMoreInput,
+    /// Every element has been returned, and the producer has closed the stream.
+    Closed,
+}
+
+impl<T> PushNext<T> {
+    /// Converts to an `Option`, which is `None` both when more input is needed and when the stream
+    /// is closed.
+    pub fn ready(self) -> Option<T> {
+        match self {
+            PushNext::Ready(item) => Some(item),
+            PushNext::NeedMoreInput | PushNext::Closed => None,
+        }
+    }
+}
+
+/// A stream with `prev()`, `peek_prev()`, and `peek()` functions like [`PrevPeekable`], for
+/// elements that arrive through callbacks, such as from an event driven parser or a network
+/// handler, rather than from an iterator.
+///
+/// The producer [`push`]es elements and [`close`]s the stream once there are no more, and the
+/// consumer reads them with [`next`], which returns [`PushNext::NeedMoreInput`] while it's waiting
+/// for more. Waiting doesn't count as reaching the end, so the previous element is the same as it
+/// would be if every element had been available from the start.
+///
+/// [`PrevPeekable`]: type.PrevPeekable.html
+/// [`push`]: #method.push
+/// [`close`]: #method.close
+/// [`next`]: #method.next
+/// [`PushNext::NeedMoreInput`]: enum.PushNext.html#variant.NeedMoreInput
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use prev_iter::{PushNext, PushPrevPeekable};
+///
+/// let mut it = PushPrevPeekable::new();
+///
+/// it.push('a');
+/// assert_eq!(PushNext::Ready('a'), it.next());
+/// assert_eq!(PushNext::NeedMoreInput, it.next());
+///
+/// it.push('b');
+/// it.close();
+/// assert_eq!(PushNext::Ready('b'), it.next());
+/// assert_eq!(Some('a'), it.prev());
+/// assert_eq!(PushNext::Closed, it.next());
+/// assert_eq!(Some('b'), it.prev());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PushPrevPeekable<T>
+where
+    T: Clone,
+{
+    /// Elements that have been pushed but not returned yet
+    queue: VecDeque<T>,
+    /// The element before the one we just returned. Initially it's `None`.
+    prev: Option<T>,
+    /// The current element we just returned.
+    current: Option<T>,
+    /// Whether the producer has closed the stream
+    closed: bool,
+    /// Keeps track of whether the end has been reached or not
+    finished: bool,
+}
+
+impl<T> PushPrevPeekable<T>
+where
+    T: Clone,
+{
+    /// Creates a new, empty `PushPrevPeekable`.
+    pub fn new() -> Self {
+        PushPrevPeekable {
+            queue: VecDeque::new(),
+            prev: None,
+            current: None,
+            closed: false,
+            finished: false,
+        }
+    }
+
+    /// Adds `item` to the end of the stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stream has been closed.
+    pub fn push(&mut self, item: T) {
+        assert!(!self.closed, "can't push to a closed PushPrevPeekable");
+        self.queue.push_back(item);
+    }
+
+    /// Closes the stream, so once the elements pushed so far have been returned, [`next`] returns
+    /// [`PushNext::Closed`] instead of asking for more input.
+    ///
+    /// [`next`]: #method.next
+    /// [`PushNext::Closed`]: enum.PushNext.html#variant.Closed
+    pub fn close(&mut self) {
+        self.closed = true;
+    }
+
+    /// Returns whether the stream has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns the next element if one has been pushed, and otherwise whether more input is needed
+    /// or the stream is closed.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> PushNext<T> {
+        match self.queue.pop_front() {
+            Some(next) => {
+                if let Some(old) = self.current.replace(next.clone()) {
+                    self.prev = Some(old);
+                }
+                PushNext::Ready(next)
+            }
+            None if !self.closed => PushNext::NeedMoreInput,
+            // The first time we reach the end, the last element becomes the previous one
+            None => {
+                if !self.finished {
+                    if let Some(last) = self.current.take() {
+                        self.prev = Some(last);
+                    }
+                    self.finished = true;
+                }
+                PushNext::Closed
+            }
+        }
+    }
+
+    /// Returns a reference to the next element without advancing, and otherwise whether more input
+    /// is needed or the stream is closed.
+    pub fn peek(&self) -> PushNext<&T> {
+        match self.queue.front() {
+            Some(next) => PushNext::Ready(next),
+            None if !self.closed => PushNext::NeedMoreInput,
+            None => PushNext::Closed,
+        }
+    }
+
+    /// Returns the previous element, or `None` if there isn't one yet. Once the stream is closed
+    /// and the end is reached, it returns the last element.
+    pub fn prev(&self) -> Option<T> {
+        self.prev.clone()
+    }
+
+    /// Returns a reference to the previous element, or `None` if there isn't one yet. Once the
+    /// stream is closed and the end is reached, it returns the last element.
+    pub fn peek_prev(&self) -> Option<&T> {
+        self.prev.as_ref()
+    }
+}
+
+impl<T> Default for PushPrevPeekable<T>
+where
+    T: Clone,
+{
+    fn default() -> Self {
+        PushPrevPeekable::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PrevPeekable;
+
+    #[test]
+    fn test_interleaved() {
+        let mut it = PushPrevPeekable::new();
+
+        assert_eq!(PushNext::NeedMoreInput, it.peek());
+        assert_eq!(PushNext::NeedMoreInput, it.next());
+        assert_eq!(None, it.prev());
+
+        it.push(1);
+        it.push(2);
+        assert_eq!(PushNext::Ready(&1), it.peek());
+        assert_eq!(PushNext::Ready(1), it.next());
+        assert_eq!(PushNext::Ready(2), it.next());
+        assert_eq!(Some(1), it.prev());
+
+        // Reading ahead of the producer doesn't end the stream
+        assert_eq!(PushNext::NeedMoreInput, it.next());
+        assert_eq!(PushNext::NeedMoreInput, it.peek());
+        assert_eq!(Some(&1), it.peek_prev());
+
+        it.push(3);
+        assert_eq!(PushNext::Ready(3), it.next());
+        assert_eq!(Some(2), it.prev());
+    }
+
+    #[test]
+    fn test_close() {
+        let mut it = PushPrevPeekable::new();
+
+        it.push('a');
+        it.close();
+        assert!(it.is_closed());
+        assert_eq!(PushNext::Ready('a'), it.next());
+        assert_eq!(PushNext::Closed, it.peek());
+        assert_eq!(PushNext::Closed, it.next());
+        assert_eq!(Some('a'), it.prev());
+        assert_eq!(PushNext::Closed, it.next());
+        assert_eq!(Some('a'), it.prev());
+        assert_eq!(None, it.next().ready());
+    }
+
+    #[test]
+    #[should_panic(expected = "can't push to a closed PushPrevPeekable")]
+    fn test_push_after_close() {
+        let mut it = PushPrevPeekable::new();
+
+        it.close();
+        it.push(1);
+    }
+
+    #[test]
+    fn test_matches_prev_peekable() {
+        let v = [1, 2, 3, 4, 5];
+        let mut pull = PrevPeekable::new(v.iter().cloned());
+        let mut push = PushPrevPeekable::new();
+
+        // Push the elements in uneven chunks and read each one as soon as it's there
+        for chunk in v.chunks(2) {
+            for &x in chunk {
+                push.push(x);
+            }
+            while let PushNext::Ready(x) = push.next() {
+                assert_eq!(pull.next(), Some(x));
+                assert_eq!(pull.prev(), push.prev());
+            }
+        }
+        push.close();
+
+        assert_eq!(PushNext::Closed, push.next());
+        assert_eq!(None, pull.next());
+        assert_eq!(pull.prev(), push.prev());
+    }
+}
@@ -0,0 +1,56 @@
+//! Adapters over streams of `Result`s, such as fallible sources.
+
+use super::PrevPeekable;
+
+impl<I, T, E> PrevPeekable<I>
+where
+    I: Iterator<Item = Result<T, E>>,
+    T: Clone,
+    E: Clone,
+{
+    /// Peeks at the next element, returning a reference to the value inside if it's `Ok` and the
+    /// error if it's `Err`. Like [`peek`], nothing is consumed, so the error is still returned by
+    /// `next()` and can be propagated from there. Returns `Ok(None)` if there are no elements left.
+    ///
+    /// [`peek`]: struct.PrevPeekableWith.html#method.peek
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let tokens = vec![Ok('a'), Err("unexpected end of line")];
+    /// let mut it = PrevPeekable::new(tokens.into_iter());
+    ///
+    /// assert_eq!(Ok(Some(&'a')), it.try_peek());
+    /// assert_eq!(Some(Ok('a')), it.next());
+    /// assert_eq!(Err(&"unexpected end of line"), it.try_peek());
+    /// assert_eq!(Some(Err("unexpected end of line")), it.next());
+    /// assert_eq!(Ok(None), it.try_peek());
+    /// ```
+    pub fn try_peek(&mut self) -> Result<Option<&T>, &E> {
+        self.peek().map(Result::as_ref).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_peek() {
+        let v: Vec<Result<i32, &str>> = vec![Ok(1), Err("e")];
+        let mut it = PrevPeekable::new(v.into_iter());
+
+        assert_eq!(Ok(Some(&1)), it.try_peek());
+        // Peeking again doesn't consume anything
+        assert_eq!(Ok(Some(&1)), it.try_peek());
+        assert_eq!(Some(Ok(1)), it.next());
+        assert_eq!(Err(&"e"), it.try_peek());
+        assert_eq!(Some(Err("e")), it.next());
+        assert_eq!(Some(Ok(1)), it.prev());
+        assert_eq!(Ok(None), it.try_peek());
+    }
+}
@@ -0,0 +1,1144 @@
+//! Adapters that operate on runs of consecutive equal elements.
+
+use super::PrevPeekable;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Collapses each run of consecutive equal elements into a `(value, first_index, last_index)`
+    /// tuple, where the indices are the positions of the run's first and last elements in the
+    /// original stream. This is useful for mapping collapsed runs back to source positions.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![9, 9, 9, 4, 4];
+    /// let spans: Vec<_> = PrevPeekable::new(v.into_iter()).run_spans().collect();
+    ///
+    /// assert_eq!(vec![(9, 0, 2), (4, 3, 4)], spans);
+    /// ```
+    pub fn run_spans(self) -> impl Iterator<Item = (I::Item, usize, usize)>
+    where
+        I::Item: PartialEq,
+    {
+        self.spans_by(|a, b| a == b)
+    }
+
+    /// Collapses each maximal run of elements where every element and the one after it satisfy
+    /// `same(prev, next)` into a `(first, first_index, last_index)` tuple, where `first` is the
+    /// run's first element and the indices are the positions of its first and last elements. This
+    /// is [`run_spans`] for any notion of belonging together, such as having the same sign.
+    ///
+    /// Only consecutive elements are compared, so with a comparison that isn't transitive a run
+    /// can drift away from its first element.
+    ///
+    /// [`run_spans`]: #method.run_spans
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let words = vec!["apple", "avocado", "banana", "blueberry", "cherry"];
+    /// let spans: Vec<_> = PrevPeekable::new(words.into_iter())
+    ///     .spans_by(|a, b| a.chars().next() == b.chars().next())
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![("apple", 0, 1), ("banana", 2, 3), ("cherry", 4, 4)], spans);
+    /// ```
+    pub fn spans_by<F>(mut self, mut same: F) -> impl Iterator<Item = (I::Item, usize, usize)>
+    where
+        F: FnMut(&I::Item, &I::Item) -> bool,
+    {
+        let mut index = 0;
+
+        ::std::iter::from_fn(move || {
+            let value = self.next()?;
+            let first = index;
+
+            while let Some(next) = self.source.peek() {
+                let prev = self.current.as_ref().expect("an element was just returned");
+                if !same(prev, next) {
+                    break;
+                }
+                self.next();
+                index += 1;
+            }
+
+            let last = index;
+            index += 1;
+
+            Some((value, first, last))
+        })
+    }
+
+    /// Consumes the iterator and returns the value and length of the longest run of consecutive
+    /// equal elements, or `None` if the iterator is empty. If several runs are equally long, the
+    /// first one wins.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 1, 2, 2, 2, 1];
+    /// let mut it = PrevPeekable::new(v.into_iter());
+    ///
+    /// assert_eq!(Some((2, 3)), it.longest_run());
+    /// ```
+    pub fn longest_run(&mut self) -> Option<(I::Item, usize)>
+    where
+        I::Item: PartialEq,
+    {
+        let mut longest: Option<(I::Item, usize)> = None;
+        let mut len = 0;
+
+        while let Some(cur) = self.next() {
+            len = match self.peek_prev() {
+                Some(prev) if *prev == cur => len + 1,
+                _ => 1,
+            };
+
+            let is_longer = match longest {
+                Some((_, longest_len)) => len > longest_len,
+                None => true,
+            };
+
+            if is_longer {
+                longest = Some((cur, len));
+            }
+        }
+
+        longest
+    }
+
+    /// Removes consecutive repeated elements, keeping the first element of each run.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 1, 2, 3, 3, 1];
+    /// let deduped: Vec<_> = PrevPeekable::new(v.into_iter()).dedup().collect();
+    ///
+    /// assert_eq!(vec![1, 2, 3, 1], deduped);
+    /// ```
+    pub fn dedup(self) -> impl Iterator<Item = I::Item>
+    where
+        I::Item: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+
+    /// Removes consecutive elements for which `eq(kept, cur)` returns `true`, keeping the first
+    /// element of each run. `kept` is the element that was last yielded, like with
+    /// [`Vec::dedup_by`].
+    ///
+    /// [`Vec::dedup_by`]: https://doc.rust-lang.org/nightly/std/vec/struct.Vec.html#method.dedup_by
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![(1, 'a'), (1, 'b'), (2, 'c')];
+    /// let deduped: Vec<_> = PrevPeekable::new(v.into_iter())
+    ///     .dedup_by(|a, b| a.0 == b.0)
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![(1, 'a'), (2, 'c')], deduped);
+    /// ```
+    pub fn dedup_by<F>(mut self, mut eq: F) -> impl Iterator<Item = I::Item>
+    where
+        F: FnMut(&I::Item, &I::Item) -> bool,
+    {
+        ::std::iter::from_fn(move || {
+            let kept = self.next()?;
+
+            loop {
+                match self.source.peek() {
+                    Some(next) if eq(&kept, next) => {}
+                    _ => return Some(kept),
+                }
+
+                self.next();
+            }
+        })
+    }
+
+    /// Yields only the elements that differ from the element right before them, along with the
+    /// first element.
+    ///
+    /// With plain equality this yields the same elements as [`dedup`]. See
+    /// [`changed_from_prev_by`] for how they differ with a custom comparison.
+    ///
+    /// [`dedup`]: #method.dedup
+    /// [`changed_from_prev_by`]: #method.changed_from_prev_by
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 1, 2, 1];
+    /// let changed: Vec<_> = PrevPeekable::new(v.into_iter()).changed_from_prev().collect();
+    ///
+    /// assert_eq!(vec![1, 2, 1], changed);
+    /// ```
+    pub fn changed_from_prev(self) -> impl Iterator<Item = I::Item>
+    where
+        I::Item: PartialEq,
+    {
+        self.changed_from_prev_by(|a, b| a == b)
+    }
+
+    /// Yields the first element, and every element for which `eq(prev, cur)` returns `false`.
+    /// `prev` is the element right before `cur` in the input, whether or not it was yielded.
+    ///
+    /// This is the difference from [`dedup_by`], which compares each element to the last one it
+    /// yielded. With a comparison that isn't transitive, such as "within 1 of", a slow drift is
+    /// dropped entirely here, since every element is close to its predecessor, while `dedup_by`
+    /// yields an element whenever the drift from the last yielded one gets too large.
+    ///
+    /// [`dedup_by`]: #method.dedup_by
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1i32, 2, 3, 4, 8];
+    /// let changed: Vec<_> = PrevPeekable::new(v.into_iter())
+    ///     .changed_from_prev_by(|prev, cur| (prev - cur).abs() <= 1)
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![1, 8], changed);
+    /// ```
+    pub fn changed_from_prev_by<F>(mut self, mut eq: F) -> impl Iterator<Item = I::Item>
+    where
+        F: FnMut(&I::Item, &I::Item) -> bool,
+    {
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+
+            match self.peek_prev() {
+                Some(prev) if eq(prev, &cur) => {}
+                _ => return Some(cur),
+            }
+        })
+    }
+
+    /// Removes consecutive repeated elements, keeping the last element of each run.
+    ///
+    /// This is useful for "latest status wins" streams, where the last element of a run carries the
+    /// freshest data. See [`dedup_by_keep_last`] for an example.
+    ///
+    /// [`dedup_by_keep_last`]: #method.dedup_by_keep_last
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 1, 2, 3, 3, 1];
+    /// let deduped: Vec<_> = PrevPeekable::new(v.into_iter()).dedup_keep_last().collect();
+    ///
+    /// assert_eq!(vec![1, 2, 3, 1], deduped);
+    /// ```
+    pub fn dedup_keep_last(self) -> impl Iterator<Item = I::Item>
+    where
+        I::Item: PartialEq,
+    {
+        self.dedup_by_keep_last(|a, b| a == b)
+    }
+
+    /// Removes consecutive elements for which `eq(prev, cur)` returns `true`, keeping the last
+    /// element of each run. `prev` is the element right before `cur`.
+    ///
+    /// The most recent element of a run is held back until the run ends, or until the iterator is
+    /// exhausted, so each element is yielded one step later than with [`dedup_by`].
+    ///
+    /// [`dedup_by`]: #method.dedup_by
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let statuses = vec![("db", "starting"), ("db", "up"), ("web", "starting"), ("web", "up")];
+    /// let latest: Vec<_> = PrevPeekable::new(statuses.into_iter())
+    ///     .dedup_by_keep_last(|a, b| a.0 == b.0)
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![("db", "up"), ("web", "up")], latest);
+    /// ```
+    pub fn dedup_by_keep_last<F>(mut self, mut eq: F) -> impl Iterator<Item = I::Item>
+    where
+        F: FnMut(&I::Item, &I::Item) -> bool,
+    {
+        ::std::iter::from_fn(move || {
+            let mut last = self.next()?;
+
+            loop {
+                match self.source.peek() {
+                    Some(next) if eq(&last, next) => {}
+                    _ => return Some(last),
+                }
+
+                last = self.next().expect("peek() returned an element");
+            }
+        })
+    }
+
+    /// Yields each element along with its 0-based position in its run of consecutive equal
+    /// elements. The position goes back to 0 whenever an element differs from the previous one,
+    /// which is handy for logic like "this is the third space in a row".
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec!['a', ' ', ' ', 'b'];
+    /// let positions: Vec<_> = PrevPeekable::new(v.into_iter()).with_run_position().collect();
+    ///
+    /// assert_eq!(vec![('a', 0), (' ', 0), (' ', 1), ('b', 0)], positions);
+    /// ```
+    pub fn with_run_position(mut self) -> impl Iterator<Item = (I::Item, usize)>
+    where
+        I::Item: PartialEq,
+    {
+        let mut position = 0;
+
+        ::std::iter::from_fn(move || {
+            let cur = self.next()?;
+            position = match self.peek_prev() {
+                Some(prev) if *prev == cur => position + 1,
+                _ => 0,
+            };
+
+            Some((cur, position))
+        })
+    }
+
+    /// Yields each element along with the 0-based index of its run of consecutive equal elements.
+    /// The index goes up by one whenever an element differs from the previous one, so every
+    /// element of a run gets the same index, without buffering the run.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec!['a', 'a', 'b', 'a'];
+    /// let ids: Vec<_> = PrevPeekable::new(v.into_iter()).with_run_id().collect();
+    ///
+    /// assert_eq!(vec![(0, 'a'), (0, 'a'), (1, 'b'), (2, 'a')], ids);
+    /// ```
+    pub fn with_run_id(mut self) -> impl Iterator<Item = (usize, I::Item)>
+    where
+        I::Item: PartialEq,
+    {
+        let mut id = None;
+
+        ::std::iter::from_fn(move || {
+            let cur = self.next()?;
+            let cur_id = match (id, self.peek_prev()) {
+                (None, _) => 0,
+                (Some(id), Some(prev)) if *prev == cur => id,
+                (Some(id), _) => id + 1,
+            };
+            id = Some(cur_id);
+
+            Some((cur_id, cur))
+        })
+    }
+
+    /// Like [`dedup`], but yields each kept element along with how many times it occurred in a row.
+    ///
+    /// [`dedup`]: #method.dedup
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec!['a', 'a', 'b', 'a'];
+    /// let counted: Vec<_> = PrevPeekable::new(v.into_iter()).dedup_with_count().collect();
+    ///
+    /// assert_eq!(vec![('a', 2), ('b', 1), ('a', 1)], counted);
+    /// ```
+    pub fn dedup_with_count(mut self) -> impl Iterator<Item = (I::Item, usize)>
+    where
+        I::Item: PartialEq,
+    {
+        ::std::iter::from_fn(move || {
+            let value = self.next()?;
+            let mut count = 1;
+
+            while self.peek() == Some(&value) {
+                self.next();
+                count += 1;
+            }
+
+            Some((value, count))
+        })
+    }
+
+    /// Yields the range of indices covered by each run of consecutive equal elements, along with
+    /// the run's value. The ranges are contiguous and together cover every index, so they can be
+    /// used to subslice the collection the elements came from instead of copying the runs out.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = [9, 9, 9, 4, 4];
+    /// let ranges: Vec<_> = PrevPeekable::new(v.iter()).group_ranges().collect();
+    ///
+    /// assert_eq!(vec![(0..3, &9), (3..5, &4)], ranges);
+    /// assert_eq!([4, 4], v[ranges[1].0.clone()]);
+    /// ```
+    pub fn group_ranges(self) -> impl Iterator<Item = (Range<usize>, I::Item)>
+    where
+        I::Item: PartialEq,
+    {
+        self.run_spans()
+            .map(|(value, first, last)| (first..last + 1, value))
+    }
+
+    /// Like [`group_ranges`], but groups consecutive elements with the same `key` rather than
+    /// equal elements, and yields the key of each group.
+    ///
+    /// [`group_ranges`]: #method.group_ranges
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let words = ["apple", "avocado", "banana", "blueberry", "cherry"];
+    /// let ranges: Vec<_> = PrevPeekable::new(words.iter())
+    ///     .group_ranges_by_key(|word| word.chars().next())
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![(0..2, Some('a')), (2..4, Some('b')), (4..5, Some('c'))], ranges);
+    /// ```
+    pub fn group_ranges_by_key<K, F>(mut self, mut key: F) -> impl Iterator<Item = (Range<usize>, K)>
+    where
+        K: PartialEq,
+        F: FnMut(&I::Item) -> K,
+    {
+        let mut index = 0;
+
+        ::std::iter::from_fn(move || {
+            let group = key(&self.next()?);
+            let start = index;
+            index += 1;
+
+            while let Some(next) = self.peek() {
+                if key(next) != group {
+                    break;
+                }
+
+                self.next();
+                index += 1;
+            }
+
+            Some((start..index, group))
+        })
+    }
+
+    /// Yields every element along with whether it's equal to the element before it. Unlike
+    /// [`dedup`], nothing is dropped, so the caller can decide what to do with repeats.
+    ///
+    /// [`dedup`]: #method.dedup
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec!['a', 'a', 'b', 'a'];
+    /// let annotated: Vec<_> = PrevPeekable::new(v.into_iter()).annotate_repeats().collect();
+    ///
+    /// assert_eq!(vec![('a', false), ('a', true), ('b', false), ('a', false)], annotated);
+    /// ```
+    pub fn annotate_repeats(mut self) -> impl Iterator<Item = (I::Item, bool)>
+    where
+        I::Item: PartialEq,
+    {
+        ::std::iter::from_fn(move || {
+            let cur = self.next()?;
+            let repeat = self.peek_prev() == Some(&cur);
+
+            Some((cur, repeat))
+        })
+    }
+
+    /// Yields every element along with whether it's equal to the element before it in the input,
+    /// with the flag first. The first element is never a duplicate.
+    ///
+    /// This carries the same information as [`annotate_repeats`], in the `(flag, element)` order
+    /// of [`flag_duplicates_by`], so later `map` or `filter` stages can branch on it.
+    ///
+    /// [`annotate_repeats`]: #method.annotate_repeats
+    /// [`flag_duplicates_by`]: #method.flag_duplicates_by
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 1, 2, 2, 2, 1];
+    /// let flagged: Vec<_> = PrevPeekable::new(v.into_iter()).flag_duplicates().collect();
+    ///
+    /// assert_eq!(vec![(false, 1), (true, 1), (false, 2), (true, 2), (true, 2), (false, 1)], flagged);
+    /// ```
+    pub fn flag_duplicates(self) -> impl Iterator<Item = (bool, I::Item)>
+    where
+        I::Item: PartialEq,
+    {
+        self.flag_duplicates_by(|a, b| a == b)
+    }
+
+    /// Yields every element along with whether `eq(prev, cur)` returns `true`, where `prev` is the
+    /// element right before `cur` in the input. The first element is never a duplicate.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let words = vec!["Hello", "hello", "world", "WORLD", "hello"];
+    /// let fresh: Vec<_> = PrevPeekable::new(words.into_iter())
+    ///     .flag_duplicates_by(|a, b| a.eq_ignore_ascii_case(b))
+    ///     .filter(|&(duplicate, _)| !duplicate)
+    ///     .map(|(_, word)| word)
+    ///     .collect();
+    ///
+    /// assert_eq!(vec!["Hello", "world", "hello"], fresh);
+    /// ```
+    pub fn flag_duplicates_by<F>(mut self, mut eq: F) -> impl Iterator<Item = (bool, I::Item)>
+    where
+        F: FnMut(&I::Item, &I::Item) -> bool,
+    {
+        ::std::iter::from_fn(move || {
+            let cur = self.next()?;
+            let duplicate = self.peek_prev().is_some_and(|prev| eq(prev, &cur));
+
+            Some((duplicate, cur))
+        })
+    }
+
+    /// Yields the first element of each run of consecutive equal elements and skips the rest, so
+    /// each run is represented by the element that started it. This keeps the same elements as
+    /// [`dedup`], under a name that says which element of a run is kept.
+    ///
+    /// [`dedup`]: #method.dedup
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![2, 2, 5, 5, 5, 2];
+    /// let firsts: Vec<_> = PrevPeekable::new(v.into_iter()).first_of_each_run().collect();
+    ///
+    /// assert_eq!(vec![2, 5, 2], firsts);
+    /// ```
+    pub fn first_of_each_run(self) -> impl Iterator<Item = I::Item>
+    where
+        I::Item: PartialEq,
+    {
+        self.first_of_each_run_indexed().map(|(_, first)| first)
+    }
+
+    /// Like [`first_of_each_run`], but also yields the index where each run starts.
+    ///
+    /// [`first_of_each_run`]: #method.first_of_each_run
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec!['a', 'a', 'b', 'a'];
+    /// let firsts: Vec<_> = PrevPeekable::new(v.into_iter()).first_of_each_run_indexed().collect();
+    ///
+    /// assert_eq!(vec![(0, 'a'), (2, 'b'), (3, 'a')], firsts);
+    /// ```
+    pub fn first_of_each_run_indexed(mut self) -> impl Iterator<Item = (usize, I::Item)>
+    where
+        I::Item: PartialEq,
+    {
+        let mut index = 0;
+
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+            index += 1;
+
+            if self.peek_prev() != Some(&cur) {
+                return Some((index - 1, cur));
+            }
+        })
+    }
+
+    /// Yields only the elements of runs of at least `min_len` consecutive equal elements, and drops
+    /// shorter runs entirely. A run of exactly `min_len` elements is kept.
+    ///
+    /// The first `min_len` elements of each run are held back until it's clear the run is long
+    /// enough, and the rest of a long run are yielded as they're read.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 1, 1, 2, 3, 3, 3];
+    /// let long: Vec<_> = PrevPeekable::new(v.into_iter()).filter_runs_longer_than(3).collect();
+    ///
+    /// assert_eq!(vec![1, 1, 1, 3, 3, 3], long);
+    /// ```
+    pub fn filter_runs_longer_than(mut self, min_len: usize) -> impl Iterator<Item = I::Item>
+    where
+        I::Item: PartialEq,
+    {
+        let mut held = VecDeque::new();
+        let mut run_len = 0;
+
+        ::std::iter::from_fn(move || loop {
+            // Once the run is long enough, the elements held back are let through
+            if run_len >= min_len {
+                if let Some(item) = held.pop_front() {
+                    return Some(item);
+                }
+            }
+
+            let cur = match self.next() {
+                Some(cur) => cur,
+                None => {
+                    // A run that's still held back at the end is too short
+                    held.clear();
+                    return None;
+                }
+            };
+            if self.peek_prev() == Some(&cur) {
+                run_len += 1;
+            } else {
+                // The run that was held back ended too soon
+                held.clear();
+                run_len = 1;
+            }
+
+            if run_len > min_len {
+                return Some(cur);
+            }
+            held.push_back(cur);
+        })
+    }
+
+    /// Yields `(value, start_index, length)` for every run of at least `min_len` consecutive equal
+    /// elements, where `start_index` is the position of the run's first element. Shorter runs are
+    /// skipped. This is like [`run_spans`], but only for the runs that are long enough to matter,
+    /// such as a sensor that's stuck on one reading.
+    ///
+    /// [`run_spans`]: #method.run_spans
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 2, 2, 2, 3, 3];
+    /// let plateaus: Vec<_> = PrevPeekable::new(v.into_iter()).plateaus(3).collect();
+    ///
+    /// assert_eq!(vec![(2, 1, 3)], plateaus);
+    /// ```
+    pub fn plateaus(self, min_len: usize) -> impl Iterator<Item = (I::Item, usize, usize)>
+    where
+        I::Item: PartialEq,
+    {
+        self.run_spans().filter_map(move |(value, first, last)| {
+            let len = last - first + 1;
+            if len >= min_len {
+                Some((value, first, len))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::RecordingIterator;
+
+    #[test]
+    fn test_run_spans() {
+        let v = [9, 9, 9, 4, 4];
+        let spans: Vec<_> = PrevPeekable::new(v.iter()).run_spans().collect();
+
+        assert_eq!(vec![(&9, 0, 2), (&4, 3, 4)], spans);
+    }
+
+    #[test]
+    fn test_run_spans_single_elements() {
+        let v = [1, 2, 1];
+        let spans: Vec<_> = PrevPeekable::new(v.iter()).run_spans().collect();
+
+        assert_eq!(vec![(&1, 0, 0), (&2, 1, 1), (&1, 2, 2)], spans);
+    }
+
+    #[test]
+    fn test_run_spans_empty() {
+        let v: [i32; 0] = [];
+        let mut spans = PrevPeekable::new(v.iter()).run_spans();
+
+        assert_eq!(None, spans.next());
+        assert_eq!(None, spans.next());
+    }
+
+    #[test]
+    fn test_spans_by() {
+        let v = [1i32, 2, -1, -3, 4];
+        let spans: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .spans_by(|a, b| a.signum() == b.signum())
+            .collect();
+
+        assert_eq!(vec![(1, 0, 1), (-1, 2, 3), (4, 4, 4)], spans);
+        assert_eq!(
+            None,
+            PrevPeekable::new(v[..0].iter()).spans_by(|_, _| true).next()
+        );
+    }
+
+    #[test]
+    fn test_spans_by_compares_neighbors() {
+        // Each element is within 1 of the one before it, even though the run drifts by more
+        let v = [1i32, 2, 3, 4, 10, 11];
+        let spans: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .spans_by(|a, b| (a - b).abs() <= 1)
+            .collect();
+
+        assert_eq!(vec![(1, 0, 3), (10, 4, 5)], spans);
+        let spans: Vec<_> = PrevPeekable::new(v.iter()).spans_by(|a, b| a == b).collect();
+        assert_eq!(PrevPeekable::new(v.iter()).run_spans().collect::<Vec<_>>(), spans);
+    }
+
+    #[test]
+    fn test_longest_run() {
+        let v = [1, 1, 2, 2, 2, 1];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!(Some((&2, 3)), it.longest_run());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(&1), it.prev());
+    }
+
+    #[test]
+    fn test_longest_run_tie_keeps_first() {
+        let v = [3, 3, 1, 4, 4];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!(Some((&3, 2)), it.longest_run());
+    }
+
+    #[test]
+    fn test_longest_run_empty() {
+        let v: [i32; 0] = [];
+        let mut it = PrevPeekable::new(v.iter());
+
+        assert_eq!(None, it.longest_run());
+    }
+
+    #[test]
+    fn test_dedup() {
+        let v = [1, 1, 2, 3, 3, 3, 1];
+        let deduped: Vec<_> = PrevPeekable::new(v.iter()).dedup().collect();
+
+        assert_eq!(vec![&1, &2, &3, &1], deduped);
+    }
+
+    #[test]
+    fn test_dedup_by_compares_to_kept() {
+        // Each element is within 1 of its predecessor, but not of the kept element
+        let v = [1i32, 2, 3, 4];
+        let deduped: Vec<_> = PrevPeekable::new(v.iter())
+            .dedup_by(|a, b| (*a - *b).abs() <= 1)
+            .collect();
+
+        assert_eq!(vec![&1, &3], deduped);
+    }
+
+    #[test]
+    fn test_changed_from_prev() {
+        let v = [1, 1, 2, 1];
+        let changed: Vec<_> = PrevPeekable::new(v.iter().cloned()).changed_from_prev().collect();
+
+        assert_eq!(vec![1, 2, 1], changed);
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).changed_from_prev().next());
+    }
+
+    #[test]
+    fn test_changed_from_prev_by_compares_to_prev() {
+        // Each element is within 1 of its predecessor, so only the first one changed, unlike with
+        // dedup_by()
+        let v = [1i32, 2, 3, 4];
+        let changed: Vec<_> = PrevPeekable::new(v.iter())
+            .changed_from_prev_by(|a, b| (*a - *b).abs() <= 1)
+            .collect();
+
+        assert_eq!(vec![&1], changed);
+    }
+
+    #[test]
+    fn test_dedup_keep_last() {
+        let v = [(1, 'a'), (1, 'b'), (2, 'c'), (3, 'd'), (3, 'e'), (3, 'f')];
+        let deduped: Vec<_> = PrevPeekable::new(v.iter())
+            .dedup_by_keep_last(|a, b| a.0 == b.0)
+            .collect();
+
+        // The trailing run is emitted once the iterator is exhausted
+        assert_eq!(vec![&(1, 'b'), &(2, 'c'), &(3, 'f')], deduped);
+    }
+
+    #[test]
+    fn test_dedup_keep_last_single_run() {
+        let v = [(1, 'a'), (1, 'b')];
+        let mut deduped = PrevPeekable::new(v.iter()).dedup_by_keep_last(|a, b| a.0 == b.0);
+
+        assert_eq!(Some(&(1, 'b')), deduped.next());
+        assert_eq!(None, deduped.next());
+
+        let v = [1, 1, 1];
+        let deduped: Vec<_> = PrevPeekable::new(v.iter()).dedup_keep_last().collect();
+        assert_eq!(vec![&1], deduped);
+    }
+
+    #[test]
+    fn test_dedup_empty() {
+        let v: [i32; 0] = [];
+
+        assert_eq!(None, PrevPeekable::new(v.iter()).dedup().next());
+        assert_eq!(None, PrevPeekable::new(v.iter()).dedup_keep_last().next());
+    }
+
+    #[test]
+    fn test_with_run_position() {
+        let v = [1, 1, 1, 2, 1];
+        let positions: Vec<_> = PrevPeekable::new(v.iter().cloned()).with_run_position().collect();
+
+        assert_eq!(vec![(1, 0), (1, 1), (1, 2), (2, 0), (1, 0)], positions);
+    }
+
+    #[test]
+    fn test_with_run_position_empty() {
+        let v: [i32; 0] = [];
+
+        assert_eq!(None, PrevPeekable::new(v.iter()).with_run_position().next());
+    }
+
+    #[test]
+    fn test_with_run_id() {
+        let v = [5, 5, 6, 5];
+        let ids: Vec<_> = PrevPeekable::new(v.iter().cloned()).with_run_id().collect();
+
+        assert_eq!(vec![(0, 5), (0, 5), (1, 6), (2, 5)], ids);
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).with_run_id().next());
+    }
+
+    #[test]
+    fn test_with_run_id_partially_consumed() {
+        // The first element yielded starts run 0, even if it continues a run it was in before
+        let mut it = PrevPeekable::new([1, 1, 1, 2].iter().cloned());
+        it.next();
+        let ids: Vec<_> = it.with_run_id().collect();
+
+        assert_eq!(vec![(0, 1), (0, 1), (1, 2)], ids);
+    }
+
+    #[test]
+    fn test_dedup_with_count() {
+        let v = [1, 1, 1, 2, 3, 3];
+        let mut counted = PrevPeekable::new(v.iter().cloned()).dedup_with_count();
+
+        assert_eq!(Some((1, 3)), counted.next());
+        assert_eq!(Some((2, 1)), counted.next());
+        // The trailing run is emitted once the iterator is exhausted
+        assert_eq!(Some((3, 2)), counted.next());
+        assert_eq!(None, counted.next());
+    }
+
+    #[test]
+    fn test_dedup_with_count_empty() {
+        let v: [i32; 0] = [];
+
+        assert_eq!(None, PrevPeekable::new(v.iter()).dedup_with_count().next());
+    }
+
+    #[test]
+    fn test_group_ranges_reconstruct() {
+        let v = [3, 3, 1, 2, 2, 2, 3];
+        let ranges: Vec<_> = PrevPeekable::new(v.iter()).group_ranges().collect();
+
+        assert_eq!(vec![(0..2, &3), (2..3, &1), (3..6, &2), (6..7, &3)], ranges);
+        for (range, value) in &ranges {
+            assert!(v[range.clone()].iter().all(|x| x == *value));
+        }
+        let rebuilt: Vec<_> = ranges
+            .into_iter()
+            .flat_map(|(range, _)| v[range].to_vec())
+            .collect();
+        assert_eq!(v.to_vec(), rebuilt);
+    }
+
+    #[test]
+    fn test_group_ranges_by_key() {
+        let v = [1, 3, 5, 2, 4, 7];
+        let ranges: Vec<_> = PrevPeekable::new(v.iter())
+            .group_ranges_by_key(|&&x| x % 2)
+            .collect();
+
+        assert_eq!(vec![(0..3, 1), (3..5, 0), (5..6, 1)], ranges);
+        let rebuilt: Vec<_> = ranges
+            .into_iter()
+            .flat_map(|(range, _)| v[range].to_vec())
+            .collect();
+        assert_eq!(v.to_vec(), rebuilt);
+    }
+
+    #[test]
+    fn test_group_ranges_empty() {
+        let v: [i32; 0] = [];
+
+        assert_eq!(None, PrevPeekable::new(v.iter()).group_ranges().next());
+        assert_eq!(
+            None,
+            PrevPeekable::new(v.iter()).group_ranges_by_key(|&&x| x).next()
+        );
+    }
+
+    #[test]
+    fn test_annotate_repeats() {
+        let v = [1, 1, 2];
+        let annotated: Vec<_> = PrevPeekable::new(v.iter()).annotate_repeats().collect();
+
+        assert_eq!(vec![(&1, false), (&1, true), (&2, false)], annotated);
+    }
+
+    #[test]
+    fn test_annotate_repeats_matches_dedup() {
+        let v = [3, 3, 3, 1, 3, 2, 2];
+        let kept: Vec<_> = PrevPeekable::new(v.iter())
+            .annotate_repeats()
+            .filter(|&(_, repeat)| !repeat)
+            .map(|(x, _)| x)
+            .collect();
+
+        assert_eq!(PrevPeekable::new(v.iter()).dedup().collect::<Vec<_>>(), kept);
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).annotate_repeats().next());
+    }
+
+    #[test]
+    fn test_flag_duplicates() {
+        let v = [7, 7, 7, 3, 3, 7, 1, 1, 1, 1, 2];
+        let flags: Vec<_> = PrevPeekable::new(v.iter())
+            .flag_duplicates()
+            .map(|(duplicate, _)| duplicate)
+            .collect();
+
+        assert_eq!(
+            vec![false, true, true, false, true, false, false, true, true, true, false],
+            flags
+        );
+        let items: Vec<_> = PrevPeekable::new(v.iter())
+            .flag_duplicates()
+            .map(|(_, x)| *x)
+            .collect();
+        assert_eq!(v.to_vec(), items);
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).flag_duplicates().next());
+    }
+
+    #[test]
+    fn test_flag_duplicates_by() {
+        // Compared to the previous input element, so a slow drift is all duplicates
+        let v = [1i32, 2, 3, 4, 8, 9, 20];
+        let flagged: Vec<_> = PrevPeekable::new(v.iter())
+            .flag_duplicates_by(|prev, cur| (*prev - *cur).abs() <= 1)
+            .collect();
+
+        assert_eq!(
+            vec![
+                (false, &1),
+                (true, &2),
+                (true, &3),
+                (true, &4),
+                (false, &8),
+                (true, &9),
+                (false, &20)
+            ],
+            flagged
+        );
+    }
+
+    #[test]
+    fn test_first_of_each_run() {
+        let v = [2, 2, 5, 5, 5, 2];
+        let firsts: Vec<_> = PrevPeekable::new(v.iter()).first_of_each_run().collect();
+
+        assert_eq!(vec![&2, &5, &2], firsts);
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).first_of_each_run().next());
+    }
+
+    #[test]
+    fn test_first_of_each_run_indexed() {
+        let v = [2, 2, 5, 5, 5, 2];
+        let firsts: Vec<_> = PrevPeekable::new(v.iter()).first_of_each_run_indexed().collect();
+
+        assert_eq!(vec![(0, &2), (2, &5), (5, &2)], firsts);
+        for (index, first) in firsts {
+            assert_eq!(first, &v[index]);
+        }
+    }
+
+    #[test]
+    fn test_filter_runs_longer_than() {
+        let v = [1, 1, 1, 2, 3, 3, 3];
+        let long: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .filter_runs_longer_than(3)
+            .collect();
+
+        assert_eq!(vec![1, 1, 1, 3, 3, 3], long);
+    }
+
+    #[test]
+    fn test_filter_runs_longer_than_short_runs() {
+        let v = ['a', 'a', 'b', 'b', 'b', 'b', 'a', 'c', 'c'];
+        let long: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .filter_runs_longer_than(2)
+            .collect();
+        assert_eq!(vec!['a', 'a', 'b', 'b', 'b', 'b', 'c', 'c'], long);
+
+        // The trailing run is too short
+        let long: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .filter_runs_longer_than(3)
+            .collect();
+        assert_eq!(vec!['b'; 4], long);
+
+        let long: Vec<_> = PrevPeekable::new(v.iter().cloned())
+            .filter_runs_longer_than(5)
+            .collect();
+        assert!(long.is_empty());
+    }
+
+    #[test]
+    fn test_filter_runs_longer_than_everything() {
+        let v = [1, 2, 2, 3];
+
+        for &min_len in &[0, 1] {
+            let long: Vec<_> = PrevPeekable::new(v.iter().cloned())
+                .filter_runs_longer_than(min_len)
+                .collect();
+            assert_eq!(v.to_vec(), long);
+        }
+        assert_eq!(
+            None,
+            PrevPeekable::new(v[..0].iter()).filter_runs_longer_than(2).next()
+        );
+        // Nothing is allocated up front for runs that may never be that long
+        assert_eq!(
+            None,
+            PrevPeekable::new(v.iter())
+                .filter_runs_longer_than(usize::MAX)
+                .next()
+        );
+    }
+
+    #[test]
+    fn test_filter_runs_longer_than_streams_long_runs() {
+        let source = RecordingIterator::new([7, 7, 7, 7, 7].iter().cloned());
+        let recording = source.recording();
+        let mut long = PrevPeekable::new(source).filter_runs_longer_than(2);
+
+        assert_eq!(Some(7), long.next());
+        recording.assert_next_calls(2);
+        assert_eq!(Some(7), long.next());
+        assert_eq!(Some(7), long.next());
+        // Past the first two, the elements aren't held back
+        recording.assert_next_calls(3);
+    }
+
+    #[test]
+    fn test_plateaus() {
+        let v = [1, 2, 2, 2, 3, 3];
+        let plateaus: Vec<_> = PrevPeekable::new(v.iter().cloned()).plateaus(3).collect();
+
+        assert_eq!(vec![(2, 1, 3)], plateaus);
+
+        let plateaus: Vec<_> = PrevPeekable::new(v.iter().cloned()).plateaus(2).collect();
+        assert_eq!(vec![(2, 1, 3), (3, 4, 2)], plateaus);
+        // Every run is at least one element long
+        let plateaus: Vec<_> = PrevPeekable::new(v.iter().cloned()).plateaus(0).collect();
+        assert_eq!(vec![(1, 0, 1), (2, 1, 3), (3, 4, 2)], plateaus);
+    }
+
+    #[test]
+    fn test_plateaus_none_long_enough() {
+        let v = [4, 4, 5, 5];
+
+        assert_eq!(None, PrevPeekable::new(v.iter()).plateaus(3).next());
+        assert_eq!(None, PrevPeekable::new(v[..0].iter()).plateaus(1).next());
+    }
+}
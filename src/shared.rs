@@ -0,0 +1,84 @@
+//! A `PrevPeekable` that shares its elements through `Rc` instead of cloning them.
+
+use super::PrevPeekable;
+use std::iter::Map;
+use std::rc::Rc;
+
+/// A `PrevPeekable` whose elements are wrapped in [`Rc`]s, so keeping track of the previous and
+/// current elements only clones pointers.
+///
+/// This type is created by [`new_shared`].
+///
+/// [`Rc`]: https://doc.rust-lang.org/nightly/std/rc/struct.Rc.html
+/// [`new_shared`]: struct.PrevPeekableWith.html#method.new_shared
+pub type SharedPrevPeekable<I> =
+    PrevPeekable<Map<I, fn(<I as ::std::iter::Iterator>::Item) -> Rc<<I as ::std::iter::Iterator>::Item>>>;
+
+impl<I> SharedPrevPeekable<I>
+where
+    I: Iterator,
+{
+    /// Creates a new `PrevPeekable` that wraps every element of `iterator` in an [`Rc`]. The
+    /// elements don't have to implement `Clone`, and are never cloned: `next()`, `prev()` and the
+    /// other accessors return clones of the `Rc`, which point to the same element. This is much
+    /// cheaper for large elements, which a plain `PrevPeekable` clones into `current` and `prev`.
+    ///
+    /// [`Rc`]: https://doc.rust-lang.org/nightly/std/rc/struct.Rc.html
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    /// use std::rc::Rc;
+    ///
+    /// let pages = vec![vec![0u8; 4096], vec![1u8; 4096]];
+    /// let mut it = PrevPeekable::new_shared(pages.into_iter());
+    ///
+    /// let first = it.next().unwrap();
+    /// it.next();
+    /// assert!(Rc::ptr_eq(&first, &it.prev().unwrap()));
+    /// ```
+    pub fn new_shared(iterator: I) -> Self {
+        PrevPeekable::new(iterator.map(Rc::new as fn(I::Item) -> Rc<I::Item>))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::CloneCounter;
+
+    #[test]
+    fn test_new_shared() {
+        let v = CloneCounter::wrap_all(1..5);
+        let counter = v[0].sibling(0);
+        let mut it = PrevPeekable::new_shared(v.into_iter());
+
+        assert_eq!(&1, it.next().unwrap().value());
+        assert_eq!(&2, it.peek().unwrap().value());
+        assert_eq!(&2, it.next().unwrap().value());
+        assert_eq!(&1, it.prev().unwrap().value());
+        assert_eq!(&2, it.current().unwrap().value());
+        assert_eq!(&1, it.peek_prev().unwrap().value());
+        assert_eq!(2, it.by_ref().count());
+        assert_eq!(None, it.next());
+        assert_eq!(&4, it.prev().unwrap().value());
+
+        // None of the payloads were cloned
+        counter.assert_clones(0);
+    }
+
+    #[test]
+    fn test_new_shared_same_element() {
+        let mut it = PrevPeekable::new_shared("ab".chars());
+
+        let a = it.next().unwrap();
+        assert!(Rc::ptr_eq(&a, it.current().unwrap()));
+        it.next();
+        assert!(Rc::ptr_eq(&a, &it.prev().unwrap()));
+        // Only the caller and the previous slot hold the element
+        assert_eq!(2, Rc::strong_count(&a));
+    }
+}
@@ -0,0 +1,153 @@
+//! Prev-aware helpers that operate on slices in place.
+//!
+//! These complement [`PrevPeekable`] for data that already lives in a slice, where wrapping it in an
+//! iterator just to touch adjacent pairs is overkill, and where mutation through an iterator isn't
+//! possible at all.
+//!
+//! [`PrevPeekable`]: ../type.PrevPeekable.html
+
+/// Calls `f` on every element after the first, giving it read-only access to its predecessor while
+/// the element itself is mutated. Elements are visited front to back, so the predecessor has already
+/// been visited (and possibly modified) by the time it's handed to `f`.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use prev_iter::slice::for_each_pair_mut;
+///
+/// let mut v = [1, 2, 3, 4];
+///
+/// // Cumulative sum in place
+/// for_each_pair_mut(&mut v, |prev, cur| *cur += *prev);
+///
+/// assert_eq!([1, 3, 6, 10], v);
+/// ```
+pub fn for_each_pair_mut<T, F>(slice: &mut [T], mut f: F)
+where
+    F: FnMut(&T, &mut T),
+{
+    for i in 1..slice.len() {
+        let (head, tail) = slice.split_at_mut(i);
+        f(&head[i - 1], &mut tail[0]);
+    }
+}
+
+/// Replaces every element with the result of calling `f` on its original predecessor and itself.
+/// The predecessor is `None` for the first element.
+///
+/// Unlike [`for_each_pair_mut`], `f` always sees the predecessor as it was *before* it was
+/// replaced, just like [`PrevPeekable::prev`] reports the previous element of the source. This
+/// requires cloning each element once to remember it.
+///
+/// [`for_each_pair_mut`]: fn.for_each_pair_mut.html
+/// [`PrevPeekable::prev`]: ../struct.PrevPeekableWith.html#method.prev
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use prev_iter::slice::map_in_place_with_prev;
+///
+/// let mut v = [1, 4, 9, 16];
+///
+/// // Replace each element with its difference from the original previous element
+/// map_in_place_with_prev(&mut v, |prev, cur| cur - prev.unwrap_or(&0));
+///
+/// assert_eq!([1, 3, 5, 7], v);
+/// ```
+pub fn map_in_place_with_prev<T, F>(slice: &mut [T], mut f: F)
+where
+    T: Clone,
+    F: FnMut(Option<&T>, &T) -> T,
+{
+    let mut prev: Option<T> = None;
+
+    for item in slice.iter_mut() {
+        let mapped = f(prev.as_ref(), item);
+        prev = Some(::std::mem::replace(item, mapped));
+    }
+}
+
+/// Removes consecutive elements for which `eq(prev, cur)` returns `true`, keeping the first element
+/// of each run. `prev` is the element that was kept before `cur`. Returns how many elements were
+/// removed.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use prev_iter::slice::dedup_in_place_by_prev;
+///
+/// let mut v = vec![1, 1, 2, 3, 3];
+/// let removed = dedup_in_place_by_prev(&mut v, |prev, cur| prev == cur);
+///
+/// assert_eq!(2, removed);
+/// assert_eq!(vec![1, 2, 3], v);
+/// ```
+pub fn dedup_in_place_by_prev<T, F>(vec: &mut Vec<T>, mut eq: F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = vec.len();
+    // Vec::dedup_by passes the candidate for removal first and the kept element second
+    vec.dedup_by(|cur, prev| eq(prev, cur));
+
+    len - vec.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_each_pair_mut_cumulative_sum() {
+        let mut v = [1, 2, 3, 4, 5];
+        for_each_pair_mut(&mut v, |prev, cur| *cur += *prev);
+
+        assert_eq!([1, 3, 6, 10, 15], v);
+    }
+
+    #[test]
+    fn test_for_each_pair_mut_short() {
+        let mut empty: [i32; 0] = [];
+        for_each_pair_mut(&mut empty, |_, _| panic!("no pairs in an empty slice"));
+
+        let mut single = [1];
+        for_each_pair_mut(&mut single, |_, _| panic!("no pairs in a single element slice"));
+        assert_eq!([1], single);
+    }
+
+    #[test]
+    fn test_map_in_place_with_prev() {
+        let mut v = [1, 2, 3];
+        let mut seen = Vec::new();
+        map_in_place_with_prev(&mut v, |prev, cur| {
+            seen.push(prev.cloned());
+            cur * 10
+        });
+
+        assert_eq!([10, 20, 30], v);
+        assert_eq!(vec![None, Some(1), Some(2)], seen);
+    }
+
+    #[test]
+    fn test_dedup_in_place_by_prev() {
+        let mut v = vec![1, 1, 2, 3, 3, 2, 4, 4, 4];
+        let removed = dedup_in_place_by_prev(&mut v, |prev, cur| prev == cur);
+
+        assert_eq!(4, removed);
+        assert_eq!(vec![1, 2, 3, 2, 4], v);
+    }
+
+    #[test]
+    fn test_dedup_in_place_by_prev_no_duplicates() {
+        let mut v = vec![1, 2, 3];
+
+        assert_eq!(0, dedup_in_place_by_prev(&mut v, |prev, cur| prev == cur));
+        assert_eq!(vec![1, 2, 3], v);
+    }
+}
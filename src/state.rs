@@ -0,0 +1,186 @@
+//! The tracking state embedded in iterators that derive `PrevIterator`.
+
+/// The previous, current and peeked elements of an iterator that derives `PrevIterator`, which
+/// gives the iterator the same `prev()`, `peek_prev()`, `current()` and `peek()` methods as
+/// [`PrevPeekable`] without wrapping it.
+///
+/// The iterator keeps a `PrevState` in a field marked with `#[prev_state]`, and names the method
+/// that produces its elements with `#[prev_iterator(next = "...")]`. The derived `Iterator`
+/// implementation calls that method and records what it returned, so elements are tracked however
+/// the iterator is advanced, including by `for` loops and adapters.
+///
+/// [`PrevPeekable`]: type.PrevPeekable.html
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use prev_iter::{PrevIterator, PrevState};
+///
+/// #[derive(PrevIterator)]
+/// #[prev_iterator(next = "step")]
+/// struct Countdown {
+///     left: u32,
+///     #[prev_state]
+///     state: PrevState<u32>,
+/// }
+///
+/// impl Countdown {
+///     fn step(&mut self) -> Option<u32> {
+///         self.left = self.left.checked_sub(1)?;
+///         Some(self.left)
+///     }
+/// }
+///
+/// let mut it = Countdown { left: 3, state: PrevState::new() };
+///
+/// assert_eq!(Some(2), it.next());
+/// assert_eq!(Some(&1), it.peek());
+/// assert_eq!(vec![1, 0], it.by_ref().collect::<Vec<_>>());
+/// assert_eq!(Some(0), it.prev());
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrevState<T>
+where
+    T: Clone,
+{
+    /// The element before the one we just returned. Initially it's `None`.
+    prev: Option<T>,
+    /// The current element we just returned.
+    current: Option<T>,
+    /// What the iterator returned when it was peeked at, if it hasn't been advanced since
+    peeked: Option<Option<T>>,
+    /// Keeps track of whether the iterator has reached the end or not
+    finished: bool,
+}
+
+impl<T> PrevState<T>
+where
+    T: Clone,
+{
+    /// Creates a new `PrevState`, with nothing returned yet.
+    pub fn new() -> Self {
+        PrevState {
+            prev: None,
+            current: None,
+            peeked: None,
+            finished: false,
+        }
+    }
+
+    /// Returns the previous element, or `None` if there isn't one yet. When the end is reached, it
+    /// returns the last element.
+    pub fn prev(&self) -> Option<T> {
+        self.prev.clone()
+    }
+
+    /// Returns a reference to the previous element, or `None` if there isn't one yet. When the end
+    /// is reached, it returns the last element.
+    pub fn peek_prev(&self) -> Option<&T> {
+        self.prev.as_ref()
+    }
+
+    /// Returns a reference to the element returned last, or `None` before the first element has
+    /// been returned and once the end has been reached.
+    pub fn current(&self) -> Option<&T> {
+        self.current.as_ref()
+    }
+
+    /// Returns whether the iterator has been peeked at since it was last advanced.
+    #[doc(hidden)]
+    pub fn is_peeked(&self) -> bool {
+        self.peeked.is_some()
+    }
+
+    /// Holds on to what the iterator returned when it was peeked at.
+    #[doc(hidden)]
+    pub fn set_peeked(&mut self, next: Option<T>) {
+        self.peeked = Some(next);
+    }
+
+    /// Returns a reference to what the iterator returned when it was peeked at.
+    #[doc(hidden)]
+    pub fn peeked(&self) -> Option<&T> {
+        self.peeked.as_ref().and_then(Option::as_ref)
+    }
+
+    /// Hands back what the iterator returned when it was peeked at, if it was.
+    #[doc(hidden)]
+    pub fn take_peeked(&mut self) -> Option<Option<T>> {
+        self.peeked.take()
+    }
+
+    /// Records that the iterator returned `next`, and returns it.
+    #[doc(hidden)]
+    pub fn record(&mut self, next: Option<T>) -> Option<T> {
+        match next {
+            Some(next) => {
+                if let Some(old) = self.current.replace(next.clone()) {
+                    self.prev = Some(old);
+                }
+                Some(next)
+            }
+            // The first time we reach the end, the last element becomes the previous one
+            None => {
+                if !self.finished {
+                    if let Some(last) = self.current.take() {
+                        self.prev = Some(last);
+                    }
+                    self.finished = true;
+                }
+                None
+            }
+        }
+    }
+}
+
+impl<T> Default for PrevState<T>
+where
+    T: Clone,
+{
+    fn default() -> Self {
+        PrevState::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut state = PrevState::new();
+
+        assert_eq!(Some(1), state.record(Some(1)));
+        assert_eq!(None, state.prev());
+        assert_eq!(Some(&1), state.current());
+        assert_eq!(Some(2), state.record(Some(2)));
+        assert_eq!(Some(&1), state.peek_prev());
+        assert_eq!(None, state.record(None));
+        assert_eq!(Some(2), state.prev());
+        assert_eq!(None, state.current());
+        assert_eq!(None, state.record(None));
+        assert_eq!(Some(2), state.prev());
+    }
+
+    #[test]
+    fn test_peeked() {
+        let mut state = PrevState::new();
+
+        assert!(!state.is_peeked());
+        state.set_peeked(Some('a'));
+        assert!(state.is_peeked());
+        assert_eq!(Some(&'a'), state.peeked());
+        assert_eq!(Some(Some('a')), state.take_peeked());
+        assert_eq!(None, state.take_peeked());
+
+        // Peeking at the end is remembered too
+        state.set_peeked(None);
+        assert!(state.is_peeked());
+        assert_eq!(None, state.peeked());
+    }
+}
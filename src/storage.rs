@@ -0,0 +1,212 @@
+//! Strategies for holding on to the previous element.
+
+use std::rc::Rc;
+
+/// Decides how a [`PrevPeekableWith`] holds on to the previous element.
+///
+/// Every time the iterator advances, the element that is no longer current is handed to the
+/// storage, and [`get`] returns whatever the storage kept from the most recent one.
+///
+/// [`PrevPeekableWith`]: struct.PrevPeekableWith.html
+/// [`get`]: trait.PrevStorage.html#tymethod.get
+pub trait PrevStorage<T> {
+    /// What the storage keeps from the previous element.
+    type Stored;
+
+    /// Stores the new previous element, replacing the old one.
+    fn store(&mut self, item: &T);
+
+    /// Returns what was kept from the previous element, or `None` if nothing was stored yet.
+    fn get(&self) -> Option<&Self::Stored>;
+
+    /// Stores the new previous element when the iterator no longer needs it. Implementations that
+    /// keep the element itself can override this to avoid a clone; by default it calls [`store`].
+    ///
+    /// [`store`]: trait.PrevStorage.html#tymethod.store
+    fn store_owned(&mut self, item: T) {
+        self.store(&item);
+    }
+}
+
+/// Keeps a clone of the previous element. This is the storage used by [`PrevPeekable`].
+///
+/// [`PrevPeekable`]: type.PrevPeekable.html
+#[derive(Debug, Clone)]
+pub struct CloneStorage<T> {
+    pub(crate) prev: Option<T>,
+}
+
+impl<T> Default for CloneStorage<T> {
+    fn default() -> Self {
+        CloneStorage { prev: None }
+    }
+}
+
+impl<T> PrevStorage<T> for CloneStorage<T>
+where
+    T: Clone,
+{
+    type Stored = T;
+
+    fn store(&mut self, item: &T) {
+        self.prev = Some(item.clone());
+    }
+
+    fn get(&self) -> Option<&T> {
+        self.prev.as_ref()
+    }
+
+    fn store_owned(&mut self, item: T) {
+        self.prev = Some(item);
+    }
+}
+
+/// Keeps the previous element behind an [`Rc`], so that `prev()` only clones the pointer instead
+/// of the element itself.
+///
+/// [`Rc`]: https://doc.rust-lang.org/nightly/std/rc/struct.Rc.html
+#[derive(Debug, Clone)]
+pub struct RcStorage<T> {
+    prev: Option<Rc<T>>,
+}
+
+impl<T> Default for RcStorage<T> {
+    fn default() -> Self {
+        RcStorage { prev: None }
+    }
+}
+
+impl<T> PrevStorage<T> for RcStorage<T>
+where
+    T: Clone,
+{
+    type Stored = Rc<T>;
+
+    fn store(&mut self, item: &T) {
+        self.prev = Some(Rc::new(item.clone()));
+    }
+
+    fn get(&self) -> Option<&Rc<T>> {
+        self.prev.as_ref()
+    }
+
+    fn store_owned(&mut self, item: T) {
+        self.prev = Some(Rc::new(item));
+    }
+}
+
+/// Keeps only a key computed from the previous element, such as a projection or a hash. This is
+/// useful when the elements are expensive to clone but only a small part of them is needed.
+#[derive(Debug, Clone)]
+pub struct KeyStorage<F, K> {
+    key: F,
+    prev: Option<K>,
+}
+
+impl<F, K> KeyStorage<F, K> {
+    /// Creates a new `KeyStorage` which keeps `key(prev)` instead of the previous element.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{KeyStorage, PrevPeekableWith};
+    ///
+    /// let words = vec!["apple", "banana", "cherry"];
+    /// let mut it = PrevPeekableWith::with_storage(words.into_iter(), KeyStorage::new(|w: &&str| w.len()));
+    ///
+    /// assert_eq!(Some("apple"), it.next());
+    /// assert_eq!(Some("banana"), it.next());
+    /// assert_eq!(Some(5), it.prev());
+    /// ```
+    pub fn new(key: F) -> Self {
+        KeyStorage { key, prev: None }
+    }
+}
+
+impl<T, F, K> PrevStorage<T> for KeyStorage<F, K>
+where
+    F: FnMut(&T) -> K,
+{
+    type Stored = K;
+
+    fn store(&mut self, item: &T) {
+        self.prev = Some((self.key)(item));
+    }
+
+    fn get(&self) -> Option<&K> {
+        self.prev.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::CloneCounter;
+    use PrevPeekableWith;
+
+    #[test]
+    fn test_clone_storage() {
+        let v = [1, 2];
+        let mut it = PrevPeekableWith::with_storage(v.iter(), CloneStorage::default());
+
+        assert_eq!(None, it.prev());
+        assert_eq!(Some(&1), it.next());
+        assert_eq!(None, it.prev());
+        assert_eq!(Some(&2), it.next());
+        assert_eq!(Some(&1), it.prev());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(&2), it.prev());
+    }
+
+    #[test]
+    fn test_clone_storage_store() {
+        let mut storage = CloneStorage::default();
+        assert_eq!(None, storage.get());
+
+        storage.store(&1);
+        assert_eq!(Some(&1), storage.get());
+
+        storage.store_owned(2);
+        assert_eq!(Some(&2), storage.get());
+    }
+
+    #[test]
+    fn test_rc_storage() {
+        let v = CloneCounter::wrap_all(1..4);
+        let counter = v[0].sibling(0);
+        let mut it = PrevPeekableWith::with_storage(v.into_iter(), RcStorage::default());
+
+        it.next();
+        it.next();
+        // Advancing only clones the element being returned
+        counter.assert_clones(2);
+
+        let first = it.prev().unwrap();
+        let again = it.prev().unwrap();
+        assert_eq!(&1, first.value());
+        assert!(Rc::ptr_eq(&first, &again));
+        // Asking for the previous element doesn't clone it
+        counter.assert_clones(2);
+
+        it.next();
+        assert_eq!(&2, it.prev().unwrap().value());
+    }
+
+    #[test]
+    fn test_key_storage() {
+        let v = ["a", "bb", "ccc"];
+        let mut it = PrevPeekableWith::with_storage(v.iter(), KeyStorage::new(|s: &&&str| s.len()));
+
+        assert_eq!(None, it.prev());
+        assert_eq!(Some(&"a"), it.next());
+        assert_eq!(None, it.prev());
+        assert_eq!(Some(&"bb"), it.next());
+        assert_eq!(Some(1), it.prev());
+        assert_eq!(Some(&"ccc"), it.next());
+        assert_eq!(Some(&2), it.peek_prev());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(3), it.prev());
+    }
+}
@@ -0,0 +1,639 @@
+//! A `PrevPeekable` for async streams, with the `futures` feature.
+
+use futures_core::Stream;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A [`Stream`] with `prev()` and `peek_prev()` functions like [`PrevPeekable`], which keeps
+/// track of the element before the one it just returned.
+///
+/// The inner stream has to be `Unpin`, so `!Unpin` streams, such as ones made by `async` blocks,
+/// need to be pinned with `Box::pin` first. The adapters returned by the methods are `Stream`s
+/// themselves, and keep at most the previous element.
+///
+/// [`Stream`]: https://docs.rs/futures-core/0.3/futures_core/stream/trait.Stream.html
+/// [`PrevPeekable`]: type.PrevPeekable.html
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// extern crate futures_executor;
+/// extern crate futures_util;
+/// # extern crate prev_iter;
+///
+/// use futures_executor::block_on;
+/// use futures_util::{stream, StreamExt};
+/// use prev_iter::PrevPeekableStream;
+///
+/// let mut it = PrevPeekableStream::new(stream::iter(vec![1, 2, 3]));
+///
+/// assert_eq!(Some(1), block_on(it.next()));
+/// assert_eq!(Some(2), block_on(it.next()));
+/// assert_eq!(Some(1), it.prev());
+/// ```
+pub struct PrevPeekableStream<S>
+where
+    S: Stream,
+    S::Item: Clone,
+{
+    stream: S,
+    /// The element before the one we just returned. Initially it's `None`.
+    prev: Option<S::Item>,
+    /// The current element we just returned.
+    current: Option<S::Item>,
+    /// Keeps track of whether the end has been reached or not
+    finished: bool,
+}
+
+impl<S> PrevPeekableStream<S>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+{
+    /// Creates a new `PrevPeekableStream` over `stream`.
+    pub fn new(stream: S) -> Self {
+        PrevPeekableStream {
+            stream,
+            prev: None,
+            current: None,
+            finished: false,
+        }
+    }
+
+    /// Returns the previous element, or `None` if there isn't one yet. When the end is reached, it
+    /// returns the last element.
+    pub fn prev(&self) -> Option<S::Item> {
+        self.prev.clone()
+    }
+
+    /// Returns a reference to the previous element, or `None` if there isn't one yet. When the end
+    /// is reached, it returns the last element.
+    pub fn peek_prev(&self) -> Option<&S::Item> {
+        self.prev.as_ref()
+    }
+
+    /// Returns a reference to the element that was returned last, or `None` before the first
+    /// element and once the end has been reached.
+    pub fn current(&self) -> Option<&S::Item> {
+        self.current.as_ref()
+    }
+
+    /// Maps every element with `f`, which is also passed the element before it, or `None` for the
+    /// first element.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// extern crate futures_executor;
+    /// extern crate futures_util;
+    /// # extern crate prev_iter;
+    ///
+    /// use futures_executor::block_on;
+    /// use futures_util::{stream, StreamExt};
+    /// use prev_iter::PrevPeekableStream;
+    ///
+    /// let squares = stream::iter(vec![1, 4, 9]);
+    /// let deltas = PrevPeekableStream::new(squares).map_with_prev(|prev, cur| cur - prev.unwrap_or(&0));
+    ///
+    /// assert_eq!(vec![1, 3, 5], block_on(deltas.collect::<Vec<_>>()));
+    /// ```
+    pub fn map_with_prev<B, F>(self, f: F) -> MapWithPrev<S, F>
+    where
+        F: FnMut(Option<&S::Item>, S::Item) -> B,
+    {
+        MapWithPrev { stream: self, f }
+    }
+
+    /// Yields every element together with the element before it, or `None` for the first element.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// extern crate futures_executor;
+    /// extern crate futures_util;
+    /// # extern crate prev_iter;
+    ///
+    /// use futures_executor::block_on;
+    /// use futures_util::{stream, StreamExt};
+    /// use prev_iter::PrevPeekableStream;
+    ///
+    /// let it = PrevPeekableStream::new(stream::iter(vec!['a', 'b']));
+    ///
+    /// assert_eq!(vec![(None, 'a'), (Some('a'), 'b')], block_on(it.with_prev().collect::<Vec<_>>()));
+    /// ```
+    pub fn with_prev(self) -> WithPrev<S> {
+        WithPrev { stream: self }
+    }
+
+    /// Removes consecutive repeated elements, keeping the first element of each run, like
+    /// `PrevPeekable::dedup()`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// extern crate futures_executor;
+    /// extern crate futures_util;
+    /// # extern crate prev_iter;
+    ///
+    /// use futures_executor::block_on;
+    /// use futures_util::{stream, StreamExt};
+    /// use prev_iter::PrevPeekableStream;
+    ///
+    /// let it = PrevPeekableStream::new(stream::iter(vec![1, 1, 2, 1]));
+    ///
+    /// assert_eq!(vec![1, 2, 1], block_on(it.dedup().collect::<Vec<_>>()));
+    /// ```
+    pub fn dedup(self) -> Dedup<S>
+    where
+        S::Item: PartialEq,
+    {
+        Dedup { stream: self }
+    }
+
+    /// Yields `(prev, cur)` for every consecutive pair of elements that aren't equal, i.e. every
+    /// time the value changes.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// extern crate futures_executor;
+    /// extern crate futures_util;
+    /// # extern crate prev_iter;
+    ///
+    /// use futures_executor::block_on;
+    /// use futures_util::{stream, StreamExt};
+    /// use prev_iter::PrevPeekableStream;
+    ///
+    /// let it = PrevPeekableStream::new(stream::iter(vec![0, 0, 1, 1, 0]));
+    ///
+    /// assert_eq!(vec![(0, 1), (1, 0)], block_on(it.transitions().collect::<Vec<_>>()));
+    /// ```
+    pub fn transitions(self) -> Transitions<S>
+    where
+        S::Item: PartialEq,
+    {
+        Transitions { stream: self }
+    }
+}
+
+// The elements are never pinned, so they don't need to be `Unpin`
+impl<S> Unpin for PrevPeekableStream<S>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+{
+}
+
+impl<S> Stream for PrevPeekableStream<S>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+{
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<S::Item>> {
+        let this = &mut *self;
+        if this.finished {
+            return Poll::Ready(None);
+        }
+
+        // Nothing changes until the inner stream is ready, and it wakes the task when it is
+        let next = match Pin::new(&mut this.stream).poll_next(cx) {
+            Poll::Ready(next) => next,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        // When the end is reached, the last element becomes the previous one
+        if let Some(old) = ::std::mem::replace(&mut this.current, next.clone()) {
+            this.prev = Some(old);
+        }
+        this.finished = next.is_none();
+
+        Poll::Ready(next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.finished {
+            (0, Some(0))
+        } else {
+            self.stream.size_hint()
+        }
+    }
+}
+
+impl<S> fmt::Debug for PrevPeekableStream<S>
+where
+    S: Stream + fmt::Debug,
+    S::Item: Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PrevPeekableStream")
+            .field("stream", &self.stream)
+            .field("prev", &self.prev)
+            .field("current", &self.current)
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+/// A stream that maps the elements of a `PrevPeekableStream` with the element before each one.
+///
+/// This `struct` is created by [`map_with_prev`].
+///
+/// [`map_with_prev`]: struct.PrevPeekableStream.html#method.map_with_prev
+pub struct MapWithPrev<S, F>
+where
+    S: Stream,
+    S::Item: Clone,
+{
+    stream: PrevPeekableStream<S>,
+    f: F,
+}
+
+// The closure is never pinned either
+impl<S, F> Unpin for MapWithPrev<S, F>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+{
+}
+
+impl<B, S, F> Stream for MapWithPrev<S, F>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+    F: FnMut(Option<&S::Item>, S::Item) -> B,
+{
+    type Item = B;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<B>> {
+        let this = &mut *self;
+
+        match Pin::new(&mut this.stream).poll_next(cx) {
+            Poll::Ready(Some(cur)) => Poll::Ready(Some((this.f)(this.stream.peek_prev(), cur))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+impl<S, F> fmt::Debug for MapWithPrev<S, F>
+where
+    S: Stream + fmt::Debug,
+    S::Item: Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MapWithPrev")
+            .field("stream", &self.stream)
+            .finish()
+    }
+}
+
+/// A stream that yields each element of a `PrevPeekableStream` with the element before it.
+///
+/// This `struct` is created by [`with_prev`].
+///
+/// [`with_prev`]: struct.PrevPeekableStream.html#method.with_prev
+pub struct WithPrev<S>
+where
+    S: Stream,
+    S::Item: Clone,
+{
+    stream: PrevPeekableStream<S>,
+}
+
+impl<S> fmt::Debug for WithPrev<S>
+where
+    S: Stream + fmt::Debug,
+    S::Item: Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WithPrev").field("stream", &self.stream).finish()
+    }
+}
+
+impl<S> Stream for WithPrev<S>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+{
+    type Item = (Option<S::Item>, S::Item);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let stream = &mut self.stream;
+
+        match Pin::new(&mut *stream).poll_next(cx) {
+            Poll::Ready(Some(cur)) => Poll::Ready(Some((stream.prev(), cur))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+/// A stream that removes consecutive repeated elements of a `PrevPeekableStream`.
+///
+/// This `struct` is created by [`dedup`].
+///
+/// [`dedup`]: struct.PrevPeekableStream.html#method.dedup
+pub struct Dedup<S>
+where
+    S: Stream,
+    S::Item: Clone,
+{
+    stream: PrevPeekableStream<S>,
+}
+
+impl<S> fmt::Debug for Dedup<S>
+where
+    S: Stream + fmt::Debug,
+    S::Item: Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Dedup").field("stream", &self.stream).finish()
+    }
+}
+
+impl<S> Stream for Dedup<S>
+where
+    S: Stream + Unpin,
+    S::Item: Clone + PartialEq,
+{
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<S::Item>> {
+        let stream = &mut self.stream;
+
+        // Whether an element is a repeat only depends on the one before it, which the inner
+        // stream keeps. So nothing is held back when it's pending, and polling again carries on
+        // where this left off.
+        loop {
+            match Pin::new(&mut *stream).poll_next(cx) {
+                Poll::Ready(Some(cur)) => {
+                    if stream.peek_prev() != Some(&cur) {
+                        return Poll::Ready(Some(cur));
+                    }
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.stream.size_hint();
+        (lower.min(1), upper)
+    }
+}
+
+/// A stream that yields every change of value in a `PrevPeekableStream`.
+///
+/// This `struct` is created by [`transitions`].
+///
+/// [`transitions`]: struct.PrevPeekableStream.html#method.transitions
+pub struct Transitions<S>
+where
+    S: Stream,
+    S::Item: Clone,
+{
+    stream: PrevPeekableStream<S>,
+}
+
+impl<S> fmt::Debug for Transitions<S>
+where
+    S: Stream + fmt::Debug,
+    S::Item: Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Transitions")
+            .field("stream", &self.stream)
+            .finish()
+    }
+}
+
+impl<S> Stream for Transitions<S>
+where
+    S: Stream + Unpin,
+    S::Item: Clone + PartialEq,
+{
+    type Item = (S::Item, S::Item);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let stream = &mut self.stream;
+
+        loop {
+            match Pin::new(&mut *stream).poll_next(cx) {
+                Poll::Ready(Some(cur)) => match stream.prev() {
+                    Some(prev) if prev != cur => return Poll::Ready(Some((prev, cur))),
+                    _ => {}
+                },
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, upper) = self.stream.size_hint();
+        (0, upper.map(|upper| upper.saturating_sub(1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Wake, Waker};
+
+    /// A waker that counts how many times it was woken.
+    struct CountingWake(AtomicUsize);
+
+    impl Wake for CountingWake {
+        fn wake(self: Arc<Self>) {
+            self.wake_by_ref();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// A stream that returns the scripted results in order, where `None` means `Pending`.
+    /// Like a well behaved stream, it wakes the task when it returns `Pending`.
+    struct Scripted<T>(VecDeque<Option<T>>);
+
+    fn scripted<T>(script: Vec<Option<T>>) -> Scripted<T> {
+        Scripted(script.into_iter().collect())
+    }
+
+    impl<T> Unpin for Scripted<T> {}
+
+    impl<T> Stream for Scripted<T> {
+        type Item = T;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<T>> {
+            match self.0.pop_front() {
+                Some(Some(item)) => Poll::Ready(Some(item)),
+                Some(None) => {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+                None => Poll::Ready(None),
+            }
+        }
+    }
+
+    /// Polls `stream` until it ends, and returns every result along with how many times the task
+    /// was woken.
+    fn poll_to_end<S: Stream + Unpin>(mut stream: S) -> (Vec<Poll<Option<S::Item>>>, usize) {
+        let wake = Arc::new(CountingWake(AtomicUsize::new(0)));
+        let waker = Waker::from(wake.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut polls = Vec::new();
+
+        loop {
+            let poll = Pin::new(&mut stream).poll_next(&mut cx);
+            let done = matches!(poll, Poll::Ready(None));
+            polls.push(poll);
+            if done {
+                return (polls, wake.0.load(Ordering::SeqCst));
+            }
+        }
+    }
+
+    #[test]
+    fn test_prev_peekable_stream() {
+        let mut stream = PrevPeekableStream::new(scripted(vec![Some(1), None, Some(2)]));
+        let waker = Waker::from(Arc::new(CountingWake(AtomicUsize::new(0))));
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(Poll::Ready(Some(1)), Pin::new(&mut stream).poll_next(&mut cx));
+        assert_eq!(None, stream.prev());
+        // Waiting doesn't change the previous or current element
+        assert_eq!(Poll::Pending, Pin::new(&mut stream).poll_next(&mut cx));
+        assert_eq!(None, stream.prev());
+        assert_eq!(Some(&1), stream.current());
+        assert_eq!(Poll::Ready(Some(2)), Pin::new(&mut stream).poll_next(&mut cx));
+        assert_eq!(Some(1), stream.prev());
+        assert_eq!(Poll::Ready(None), Pin::new(&mut stream).poll_next(&mut cx));
+        assert_eq!(Some(&2), stream.peek_prev());
+        assert_eq!(None, stream.current());
+        assert_eq!(Poll::Ready(None), Pin::new(&mut stream).poll_next(&mut cx));
+        assert_eq!(Some(2), stream.prev());
+    }
+
+    #[test]
+    fn test_map_with_prev() {
+        let stream = PrevPeekableStream::new(scripted(vec![Some(1), None, Some(4), Some(9)]));
+        let (polls, wakes) = poll_to_end(stream.map_with_prev(|prev, cur| cur - prev.unwrap_or(&0)));
+
+        assert_eq!(
+            vec![
+                Poll::Ready(Some(1)),
+                Poll::Pending,
+                Poll::Ready(Some(3)),
+                Poll::Ready(Some(5)),
+                Poll::Ready(None),
+            ],
+            polls
+        );
+        assert_eq!(1, wakes);
+    }
+
+    #[test]
+    fn test_with_prev() {
+        let stream = PrevPeekableStream::new(scripted(vec![None, Some('a'), Some('b'), None, Some('c')]));
+        let (polls, wakes) = poll_to_end(stream.with_prev());
+
+        assert_eq!(
+            vec![
+                Poll::Pending,
+                Poll::Ready(Some((None, 'a'))),
+                Poll::Ready(Some((Some('a'), 'b'))),
+                Poll::Pending,
+                Poll::Ready(Some((Some('b'), 'c'))),
+                Poll::Ready(None),
+            ],
+            polls
+        );
+        assert_eq!(2, wakes);
+    }
+
+    #[test]
+    fn test_dedup_pending_mid_run() {
+        // The inner stream is pending between repeats of the same element
+        let script = vec![
+            Some(1),
+            None,
+            Some(1),
+            Some(1),
+            None,
+            Some(2),
+            Some(2),
+            None,
+            Some(1),
+        ];
+        let (polls, wakes) = poll_to_end(PrevPeekableStream::new(scripted(script)).dedup());
+
+        assert_eq!(
+            vec![
+                Poll::Ready(Some(1)),
+                Poll::Pending,
+                Poll::Pending,
+                Poll::Ready(Some(2)),
+                Poll::Pending,
+                Poll::Ready(Some(1)),
+                Poll::Ready(None),
+            ],
+            polls
+        );
+        assert_eq!(3, wakes);
+    }
+
+    #[test]
+    fn test_transitions() {
+        let script = vec![Some(0), Some(0), None, Some(1), Some(1), None, Some(1), Some(0)];
+        let (polls, wakes) = poll_to_end(PrevPeekableStream::new(scripted(script)).transitions());
+
+        assert_eq!(
+            vec![
+                Poll::Pending,
+                Poll::Ready(Some((0, 1))),
+                Poll::Pending,
+                Poll::Ready(Some((1, 0))),
+                Poll::Ready(None),
+            ],
+            polls
+        );
+        assert_eq!(2, wakes);
+    }
+
+    #[test]
+    fn test_adapters_empty() {
+        let (polls, _) = poll_to_end(PrevPeekableStream::new(scripted::<u8>(vec![None])).dedup());
+        assert_eq!(vec![Poll::Pending, Poll::Ready(None)], polls);
+
+        let (polls, _) = poll_to_end(PrevPeekableStream::new(scripted::<u8>(vec![])).transitions());
+        assert_eq!(vec![Poll::Ready(None)], polls);
+    }
+}
@@ -0,0 +1,242 @@
+//! A thread-safe `PrevPeekable` for sharing one source between several consumers.
+
+use super::PrevPeekable;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+
+/// A [`PrevPeekable`] that can be shared between threads, for when several workers pull elements
+/// from one source and want to know which element was handed out before.
+///
+/// The iterator and its previous element live behind a [`Mutex`], so every method takes `&self`.
+/// [`Iterator`] is implemented for `&SyncPrevPeekable`, which lets it be shared by reference.
+///
+/// [`PrevPeekable`]: type.PrevPeekable.html
+/// [`Mutex`]: https://doc.rust-lang.org/nightly/std/sync/struct.Mutex.html
+/// [`Iterator`]: https://doc.rust-lang.org/nightly/core/iter/iterator/trait.Iterator.html
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use prev_iter::SyncPrevPeekable;
+/// use std::thread;
+///
+/// let jobs = SyncPrevPeekable::new(0..100);
+///
+/// let handled: usize = thread::scope(|s| {
+///     let workers: Vec<_> = (0..4).map(|_| s.spawn(|| (&jobs).count())).collect();
+///     workers.into_iter().map(|w| w.join().unwrap()).sum()
+/// });
+///
+/// assert_eq!(100, handled);
+/// assert_eq!(Some(99), jobs.prev());
+/// ```
+pub struct SyncPrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    inner: Mutex<PrevPeekable<I>>,
+}
+
+impl<I> SyncPrevPeekable<I>
+where
+    I: Iterator + Send,
+    <I as ::std::iter::Iterator>::Item: Send + Clone,
+{
+    /// Creates a new `SyncPrevPeekable`. It takes an [`Iterator`] whose `Item` implements [`Clone`].
+    ///
+    /// [`Iterator`]: https://doc.rust-lang.org/nightly/core/iter/iterator/trait.Iterator.html
+    /// [`Clone`]: https://doc.rust-lang.org/nightly/core/clone/trait.Clone.html
+    pub fn new(iterator: I) -> Self {
+        SyncPrevPeekable {
+            inner: Mutex::new(PrevPeekable::new(iterator)),
+        }
+    }
+
+    /// Returns the next element, which no other thread will receive.
+    pub fn next(&self) -> Option<I::Item> {
+        self.lock().next()
+    }
+
+    /// Returns a clone of the element that was handed out last, to any thread. Once the end has
+    /// been reached, it will always return the last element.
+    ///
+    /// Other threads may take elements at any time, so by the time this returns the element might
+    /// not be the last one anymore. Use [`next_with_prev`] to get an element together with the one
+    /// handed out right before it.
+    ///
+    /// [`next_with_prev`]: #method.next_with_prev
+    pub fn prev(&self) -> Option<I::Item> {
+        self.lock().last_returned().cloned()
+    }
+
+    /// Returns the next element together with the element that was handed out right before it, to
+    /// any thread. Both are read under the same lock, so no other element can be handed out in
+    /// between.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::SyncPrevPeekable;
+    ///
+    /// let it = SyncPrevPeekable::new(vec![1, 2].into_iter());
+    ///
+    /// assert_eq!(Some((None, 1)), it.next_with_prev());
+    /// assert_eq!(Some((Some(1), 2)), it.next_with_prev());
+    /// assert_eq!(None, it.next_with_prev());
+    /// ```
+    pub fn next_with_prev(&self) -> Option<(Option<I::Item>, I::Item)> {
+        let mut inner = self.lock();
+        let prev = inner.last_returned().cloned();
+
+        inner.next().map(|next| (prev, next))
+    }
+
+    /// Consumes and returns the next element if `pred` returns `true` for it. Checking and
+    /// consuming happen under the same lock, so no other thread can take the element in between.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::SyncPrevPeekable;
+    ///
+    /// let it = SyncPrevPeekable::new(vec![1, 2].into_iter());
+    ///
+    /// assert_eq!(None, it.next_if(|&x| x == 2));
+    /// assert_eq!(Some(1), it.next_if(|&x| x == 1));
+    /// ```
+    pub fn next_if<F>(&self, pred: F) -> Option<I::Item>
+    where
+        F: FnOnce(&I::Item) -> bool,
+    {
+        let mut inner = self.lock();
+        let matches = match inner.peek() {
+            Some(next) => pred(next),
+            None => false,
+        };
+
+        if matches {
+            inner.next()
+        } else {
+            None
+        }
+    }
+
+    /// Consumes the `SyncPrevPeekable` and returns the underlying `PrevPeekable`.
+    pub fn into_inner(self) -> PrevPeekable<I> {
+        match self.inner.into_inner() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PrevPeekable<I>> {
+        // A panic in another thread can only have happened inside the wrapped iterator, after
+        // which the state is as consistent as it'll get
+        match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl<I> fmt::Debug for SyncPrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+    PrevPeekable<I>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SyncPrevPeekable")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<I> Iterator for &SyncPrevPeekable<I>
+where
+    I: Iterator + Send,
+    <I as ::std::iter::Iterator>::Item: Send + Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        SyncPrevPeekable::next(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::thread;
+
+    #[test]
+    fn test_sync_prev_peekable() {
+        let it = SyncPrevPeekable::new(vec![1, 2, 3].into_iter());
+
+        assert_eq!(None, it.prev());
+        assert_eq!(Some(1), it.next());
+        assert_eq!(Some(1), it.prev());
+        assert_eq!(None, it.next_if(|&x| x == 5));
+        assert_eq!(Some((Some(1), 2)), it.next_with_prev());
+        assert_eq!(Some(3), Iterator::next(&mut &it));
+        assert_eq!(None, it.next());
+        assert_eq!(Some(3), it.prev());
+        assert_eq!(None, it.next());
+        assert_eq!(Some(3), it.prev());
+    }
+
+    #[test]
+    fn test_sync_prev_peekable_threads() {
+        const N: usize = 10_000;
+        let it = SyncPrevPeekable::new(0..N);
+
+        let results: Vec<_> = thread::scope(|s| {
+            let workers: Vec<_> = (0..4)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut delivered = Vec::new();
+
+                        while let Some((prev, next)) = it.next_with_prev() {
+                            // The source counts up, so the element before `next` is always one less
+                            assert_eq!(next.checked_sub(1), prev);
+                            delivered.push(next);
+                            delivered.extend(it.next_if(|x| x % 3 == 0));
+                        }
+
+                        delivered
+                    })
+                })
+                .collect();
+
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+
+        let mut delivered = HashSet::new();
+        for items in &results {
+            for &item in items {
+                assert!(delivered.insert(item), "{} was delivered twice", item);
+            }
+        }
+        assert_eq!(N, delivered.len());
+        assert_eq!(Some(N - 1), it.prev());
+    }
+
+    #[test]
+    fn test_into_inner() {
+        let it = SyncPrevPeekable::new(vec![1, 2, 3].into_iter());
+        it.next();
+        it.next();
+
+        let mut inner = it.into_inner();
+        assert_eq!(Some(1), inner.prev());
+        assert_eq!(Some(3), inner.next());
+    }
+}
@@ -0,0 +1,323 @@
+//! Instrumented iterators and elements for testing adapters built on `PrevPeekable`.
+//!
+//! [`RecordingIterator`] counts how often the source was advanced and records what it yielded, and
+//! [`CloneCounter`] counts how often elements were cloned. Both keep their counts behind shared
+//! handles, so they can still be checked after being moved into an adapter.
+//!
+//! This module is only available with the `test-util` feature.
+//!
+//! [`RecordingIterator`]: struct.RecordingIterator.html
+//! [`CloneCounter`]: struct.CloneCounter.html
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::rc::Rc;
+
+/// What a [`RecordingIterator`] has seen so far.
+///
+/// [`RecordingIterator`]: struct.RecordingIterator.html
+#[derive(Debug)]
+struct Log<T> {
+    next_calls: usize,
+    yielded: Vec<T>,
+}
+
+/// An iterator that wraps another one and records how many times `next()` was called and which
+/// elements it yielded. Use [`recording`] to get a handle for checking the record after the iterator
+/// has been handed to an adapter.
+///
+/// [`recording`]: #method.recording
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use prev_iter::test_util::RecordingIterator;
+/// use prev_iter::PrevPeekable;
+///
+/// let source = RecordingIterator::new(1..10);
+/// let recording = source.recording();
+/// let mut it = PrevPeekable::new(source);
+///
+/// it.next();
+/// it.next();
+///
+/// // The source was only read as far as it had to be
+/// recording.assert_next_calls(2);
+/// recording.assert_yielded(&[1, 2]);
+/// ```
+#[derive(Debug)]
+pub struct RecordingIterator<I>
+where
+    I: Iterator,
+{
+    iterator: I,
+    log: Rc<RefCell<Log<I::Item>>>,
+}
+
+impl<I> RecordingIterator<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Creates a new `RecordingIterator` that wraps `iterator`.
+    pub fn new(iterator: I) -> Self {
+        RecordingIterator {
+            iterator,
+            log: Rc::new(RefCell::new(Log {
+                next_calls: 0,
+                yielded: Vec::new(),
+            })),
+        }
+    }
+
+    /// Returns a handle to what this iterator records, which stays up to date as it's advanced.
+    pub fn recording(&self) -> Recording<I::Item> {
+        Recording {
+            log: Rc::clone(&self.log),
+        }
+    }
+}
+
+impl<I> Iterator for RecordingIterator<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let next = self.iterator.next();
+        let mut log = self.log.borrow_mut();
+
+        log.next_calls += 1;
+        if let Some(ref item) = next {
+            log.yielded.push(item.clone());
+        }
+
+        next
+    }
+}
+
+/// A handle to what a [`RecordingIterator`] has recorded.
+///
+/// This `struct` is created by [`RecordingIterator::recording`].
+///
+/// [`RecordingIterator`]: struct.RecordingIterator.html
+/// [`RecordingIterator::recording`]: struct.RecordingIterator.html#method.recording
+#[derive(Debug, Clone)]
+pub struct Recording<T> {
+    log: Rc<RefCell<Log<T>>>,
+}
+
+impl<T> Recording<T> {
+    /// Returns how many times `next()` was called, including calls that returned `None`.
+    pub fn next_calls(&self) -> usize {
+        self.log.borrow().next_calls
+    }
+
+    /// Returns the elements yielded so far, in order.
+    pub fn yielded(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.log.borrow().yielded.clone()
+    }
+
+    /// Panics unless `next()` was called exactly `expected` times.
+    pub fn assert_next_calls(&self, expected: usize) {
+        let next_calls = self.next_calls();
+        assert!(
+            next_calls == expected,
+            "expected the source to be advanced {} times, but it was advanced {} times",
+            expected,
+            next_calls
+        );
+    }
+
+    /// Panics unless the elements yielded so far are exactly `expected`.
+    pub fn assert_yielded(&self, expected: &[T])
+    where
+        T: PartialEq + fmt::Debug,
+    {
+        let log = self.log.borrow();
+        assert!(
+            log.yielded[..] == *expected,
+            "expected the source to yield {:?}, but it yielded {:?}",
+            expected,
+            log.yielded
+        );
+    }
+}
+
+/// An element that counts how many times it, or any element sharing its counter, has been cloned.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use prev_iter::test_util::CloneCounter;
+/// use prev_iter::PrevPeekable;
+///
+/// let items = CloneCounter::wrap_all(vec![1, 2, 3]);
+/// let counter = items[0].sibling(0);
+/// let mut it = PrevPeekable::new(items.into_iter());
+///
+/// it.next();
+/// it.next();
+///
+/// // Each call to next() clones the element it returns
+/// counter.assert_clones(2);
+/// ```
+pub struct CloneCounter<T> {
+    value: T,
+    clones: Rc<Cell<usize>>,
+}
+
+impl<T> CloneCounter<T> {
+    /// Creates a new `CloneCounter` with a counter of its own.
+    pub fn new(value: T) -> Self {
+        CloneCounter {
+            value,
+            clones: Rc::new(Cell::new(0)),
+        }
+    }
+
+    /// Wraps every element of `values` in a `CloneCounter`, all sharing one counter.
+    pub fn wrap_all<V>(values: V) -> Vec<Self>
+    where
+        V: IntoIterator<Item = T>,
+    {
+        let clones = Rc::new(Cell::new(0));
+
+        values
+            .into_iter()
+            .map(|value| CloneCounter {
+                value,
+                clones: Rc::clone(&clones),
+            })
+            .collect()
+    }
+
+    /// Creates a new `CloneCounter` that shares this one's counter. This isn't counted as a clone.
+    pub fn sibling(&self, value: T) -> Self {
+        CloneCounter {
+            value,
+            clones: Rc::clone(&self.clones),
+        }
+    }
+
+    /// Returns a reference to the wrapped value.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Consumes the `CloneCounter` and returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Returns how many clones have been made of the elements sharing this counter.
+    pub fn clones(&self) -> usize {
+        self.clones.get()
+    }
+
+    /// Panics unless exactly `expected` clones have been made of the elements sharing this counter.
+    pub fn assert_clones(&self, expected: usize) {
+        let clones = self.clones();
+        assert!(
+            clones == expected,
+            "expected {} clones, but {} were made",
+            expected,
+            clones
+        );
+    }
+}
+
+impl<T> Clone for CloneCounter<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        self.clones.set(self.clones.get() + 1);
+        self.sibling(self.value.clone())
+    }
+}
+
+impl<T> PartialEq for CloneCounter<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T> fmt::Debug for CloneCounter<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CloneCounter")
+            .field("value", &self.value)
+            .field("clones", &self.clones.get())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PrevPeekable;
+
+    #[test]
+    fn test_recording_iterator() {
+        let source = RecordingIterator::new(vec![1, 2].into_iter());
+        let recording = source.recording();
+        let mut it = PrevPeekable::new(source);
+
+        assert_eq!(Some(&1), it.peek());
+        recording.assert_next_calls(1);
+        recording.assert_yielded(&[1]);
+
+        assert_eq!(3, it.by_ref().sum::<i32>());
+        assert_eq!(vec![1, 2], recording.yielded());
+        // The source is asked once more to find out that it's empty
+        recording.assert_next_calls(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected the source to be advanced 1 times, but it was advanced 0 times")]
+    fn test_recording_iterator_assert_next_calls() {
+        let source = RecordingIterator::new(0..1);
+
+        source.recording().assert_next_calls(1);
+    }
+
+    #[test]
+    fn test_clone_counter() {
+        let items = CloneCounter::wrap_all(vec!['a', 'b']);
+        let copy = items[1].clone();
+
+        assert_eq!(&'b', copy.value());
+        assert_eq!(items[1], copy);
+        items[0].assert_clones(1);
+
+        let unshared = CloneCounter::new('c');
+        let _ = unshared.clone();
+        unshared.assert_clones(1);
+        items[0].assert_clones(1);
+        assert_eq!('c', unshared.into_inner());
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 0 clones, but 1 were made")]
+    fn test_clone_counter_assert_clones() {
+        let item = CloneCounter::new(1);
+        let _ = item.clone();
+
+        item.assert_clones(0);
+    }
+}
@@ -0,0 +1,157 @@
+//! Adapters for streams of text.
+
+use super::PrevPeekable;
+
+/// How the indentation of a line changed relative to the previous non-blank line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndentChange {
+    /// The line is indented further, by this many columns.
+    Indent(usize),
+    /// The line is indented less, by this many columns.
+    Dedent(usize),
+    /// The line has the same indentation.
+    Same,
+}
+
+/// Returns the width of `line`'s leading whitespace, or `None` if the line is blank. Tabs advance to
+/// the next multiple of `tab_width`.
+fn indent_width(line: &str, tab_width: usize) -> Option<usize> {
+    let mut width = 0;
+
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' if tab_width > 0 => width += tab_width - width % tab_width,
+            '\t' => {}
+            '\r' | '\n' => return None,
+            _ => return Some(width),
+        }
+    }
+
+    None
+}
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Pairs every line with how its indentation changed relative to the previous non-blank line.
+    /// This is the key quantity when parsing indentation sensitive formats.
+    ///
+    /// Indentation is measured from leading spaces and tabs, where a tab advances to the next
+    /// multiple of `tab_width` columns (a `tab_width` of 0 ignores tabs). Blank lines are always
+    /// [`Same`], and they don't change the indentation the following lines are compared against. The
+    /// first line is [`Same`] as well.
+    ///
+    /// [`Same`]: enum.IndentChange.html#variant.Same
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{IndentChange, PrevPeekable};
+    ///
+    /// let doc = "a:\n  b: 1\n  c:\n\n    d: 2\ne: 3";
+    /// let changes: Vec<_> = PrevPeekable::new(doc.lines())
+    ///     .with_indent_delta(4)
+    ///     .map(|(change, _)| change)
+    ///     .collect();
+    ///
+    /// assert_eq!(
+    ///     vec![
+    ///         IndentChange::Same,
+    ///         IndentChange::Indent(2),
+    ///         IndentChange::Same,
+    ///         IndentChange::Same,
+    ///         IndentChange::Indent(2),
+    ///         IndentChange::Dedent(4),
+    ///     ],
+    ///     changes
+    /// );
+    /// ```
+    pub fn with_indent_delta(mut self, tab_width: usize) -> impl Iterator<Item = (IndentChange, I::Item)>
+    where
+        I::Item: AsRef<str>,
+    {
+        let mut reference: Option<usize> = None;
+
+        ::std::iter::from_fn(move || {
+            let line = self.next()?;
+            let width = match indent_width(line.as_ref(), tab_width) {
+                Some(width) => width,
+                None => return Some((IndentChange::Same, line)),
+            };
+
+            let change = match reference {
+                Some(prev) if width > prev => IndentChange::Indent(width - prev),
+                Some(prev) if width < prev => IndentChange::Dedent(prev - width),
+                _ => IndentChange::Same,
+            };
+            reference = Some(width);
+
+            Some((change, line))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_indent_delta() {
+        let doc = [
+            "root:", "  child:", "    leaf", "", "   ", "\tleaf", "  child", "root",
+        ];
+        let changes: Vec<_> = PrevPeekable::new(doc.iter()).with_indent_delta(4).collect();
+
+        assert_eq!(
+            vec![
+                (IndentChange::Same, &"root:"),
+                (IndentChange::Indent(2), &"  child:"),
+                (IndentChange::Indent(2), &"    leaf"),
+                (IndentChange::Same, &""),
+                (IndentChange::Same, &"   "),
+                (IndentChange::Same, &"\tleaf"),
+                (IndentChange::Dedent(2), &"  child"),
+                (IndentChange::Dedent(2), &"root"),
+            ],
+            changes
+        );
+    }
+
+    #[test]
+    fn test_with_indent_delta_tab_stops() {
+        // A tab after two spaces only advances to the next tab stop
+        let doc = vec!["a".to_string(), "  \tb".to_string(), "        c".to_string()];
+        let changes: Vec<_> = PrevPeekable::new(doc.into_iter())
+            .with_indent_delta(4)
+            .map(|(change, _)| change)
+            .collect();
+
+        assert_eq!(
+            vec![
+                IndentChange::Same,
+                IndentChange::Indent(4),
+                IndentChange::Indent(4)
+            ],
+            changes
+        );
+    }
+
+    #[test]
+    fn test_with_indent_delta_leading_blank_lines() {
+        let doc = ["", "  a", "b"];
+        let changes: Vec<_> = PrevPeekable::new(doc.iter())
+            .with_indent_delta(8)
+            .map(|(change, _)| change)
+            .collect();
+
+        assert_eq!(
+            vec![IndentChange::Same, IndentChange::Same, IndentChange::Dedent(2)],
+            changes
+        );
+    }
+}
@@ -0,0 +1,295 @@
+//! Decoding UTF-8 from a `PrevPeekable` over bytes.
+
+use super::PrevPeekable;
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Why a [`Utf8Error`] happened.
+///
+/// [`Utf8Error`]: struct.Utf8Error.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Utf8ErrorKind {
+    /// A byte that can't appear where it was found, such as a lone continuation byte or the lead
+    /// byte of a sequence that's overlong or cut short by a byte that doesn't continue it.
+    Invalid,
+    /// The input ended in the middle of a multi-byte character.
+    Incomplete,
+}
+
+/// The error yielded by [`chars_from_bytes`] when the bytes aren't valid UTF-8.
+///
+/// [`chars_from_bytes`]: struct.PrevPeekableWith.html#method.chars_from_bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Utf8Error {
+    /// What went wrong.
+    pub kind: Utf8ErrorKind,
+    /// The offset of the offending byte in the input, or the length of the input if it ended too
+    /// early. When a sequence is cut short by a byte that doesn't continue it, the offending byte
+    /// is the one that started the sequence, and the byte that cut it short is decoded next.
+    pub offset: usize,
+    /// The offending byte, or `None` if the input ended too early.
+    pub byte: Option<u8>,
+    /// The byte before the offending one, or `None` if there wasn't one.
+    pub prev: Option<u8>,
+}
+
+impl fmt::Display for Utf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.byte {
+            Some(byte) => write!(f, "invalid UTF-8 byte {:#04x} at offset {}", byte, self.offset)?,
+            None => write!(f, "incomplete UTF-8 character at offset {}", self.offset)?,
+        }
+        match self.prev {
+            Some(prev) => write!(f, " after {:#04x}", prev),
+            None => write!(f, " at the start of input"),
+        }
+    }
+}
+
+impl Error for Utf8Error {}
+
+/// An iterator that decodes UTF-8 from an iterator of bytes, yielding a [`Utf8Error`] for every
+/// invalid or incomplete sequence.
+///
+/// This `struct` is created by [`chars_from_bytes`], wrapped in a `PrevPeekable`.
+///
+/// [`Utf8Error`]: struct.Utf8Error.html
+/// [`chars_from_bytes`]: struct.PrevPeekableWith.html#method.chars_from_bytes
+#[derive(Debug)]
+pub struct Utf8Chars<I>
+where
+    I: Iterator<Item = u8>,
+{
+    bytes: PrevPeekable<I>,
+    /// How many bytes have been consumed so far
+    offset: usize,
+}
+
+impl<I> Iterator for Utf8Chars<I>
+where
+    I: Iterator<Item = u8>,
+{
+    type Item = Result<char, Utf8Error>;
+
+    fn next(&mut self) -> Option<Result<char, Utf8Error>> {
+        let lead = self.bytes.next()?;
+        // Where the sequence started, and the byte before it
+        let (start, before) = (self.offset, self.bytes.prev());
+        self.offset += 1;
+
+        // The length of the sequence, and the range of its second byte, which rules out overlong
+        // encodings, surrogates and values above U+10FFFF
+        let (len, second): (usize, RangeInclusive<u8>) = match lead {
+            0x00..=0x7f => return Some(Ok(char::from(lead))),
+            0xc2..=0xdf => (2, 0x80..=0xbf),
+            0xe0 => (3, 0xa0..=0xbf),
+            0xe1..=0xec | 0xee..=0xef => (3, 0x80..=0xbf),
+            0xed => (3, 0x80..=0x9f),
+            0xf0 => (4, 0x90..=0xbf),
+            0xf1..=0xf3 => (4, 0x80..=0xbf),
+            0xf4 => (4, 0x80..=0x8f),
+            _ => {
+                return Some(Err(Utf8Error {
+                    kind: Utf8ErrorKind::Invalid,
+                    offset: start,
+                    byte: Some(lead),
+                    prev: before,
+                }))
+            }
+        };
+
+        let mut code = u32::from(lead & (0x7f >> len));
+        for i in 1..len {
+            let valid = if i == 1 { second.clone() } else { 0x80..=0xbf };
+
+            // A byte that doesn't continue the sequence is left alone, since it may start the next one
+            match self.bytes.peek().cloned() {
+                Some(byte) if valid.contains(&byte) => {
+                    self.bytes.next();
+                    self.offset += 1;
+                    code = (code << 6) | u32::from(byte & 0x3f);
+                }
+                // The byte will be blamed itself if it can't start a character either, so blame
+                // the sequence it cut short by its lead byte
+                Some(_) => {
+                    return Some(Err(Utf8Error {
+                        kind: Utf8ErrorKind::Invalid,
+                        offset: start,
+                        byte: Some(lead),
+                        prev: before,
+                    }));
+                }
+                None => {
+                    return Some(Err(Utf8Error {
+                        kind: Utf8ErrorKind::Incomplete,
+                        offset: self.offset,
+                        byte: None,
+                        prev: self.bytes.current().cloned(),
+                    }));
+                }
+            }
+        }
+
+        Some(Ok(
+            char::from_u32(code).expect("the byte ranges only allow valid characters")
+        ))
+    }
+}
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator<Item = u8>,
+{
+    /// Decodes the bytes as UTF-8, yielding a `char` for every character and a [`Utf8Error`] for
+    /// every invalid or incomplete sequence. The result is a `PrevPeekable` itself, so a lexer can
+    /// work on the characters with lookbehind and peeking.
+    ///
+    /// After an error, decoding continues with the next byte that could start a character, so one
+    /// error is reported for each bad sequence.
+    ///
+    /// [`Utf8Error`]: struct.Utf8Error.html
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::{PrevPeekable, Utf8Error, Utf8ErrorKind};
+    ///
+    /// let bytes = vec![b'h', 0xc3, 0xa9, 0xff, b'!'];
+    /// let mut chars = PrevPeekable::new(bytes.into_iter()).chars_from_bytes();
+    ///
+    /// assert_eq!(Some(Ok('h')), chars.next());
+    /// assert_eq!(Some(&Ok('é')), chars.peek());
+    /// assert_eq!(Some(Ok('é')), chars.next());
+    /// assert_eq!(
+    ///     Some(Err(Utf8Error {
+    ///         kind: Utf8ErrorKind::Invalid,
+    ///         offset: 3,
+    ///         byte: Some(0xff),
+    ///         prev: Some(0xa9),
+    ///     })),
+    ///     chars.next()
+    /// );
+    /// assert_eq!(Some(Ok('!')), chars.next());
+    /// ```
+    pub fn chars_from_bytes(self) -> PrevPeekable<Utf8Chars<I>> {
+        PrevPeekable::new(Utf8Chars {
+            bytes: self,
+            offset: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Vec<Result<char, Utf8Error>> {
+        PrevPeekable::new(bytes.iter().copied())
+            .chars_from_bytes()
+            .collect()
+    }
+
+    fn invalid(offset: usize, byte: u8, prev: Option<u8>) -> Result<char, Utf8Error> {
+        Err(Utf8Error {
+            kind: Utf8ErrorKind::Invalid,
+            offset,
+            byte: Some(byte),
+            prev,
+        })
+    }
+
+    #[test]
+    fn test_chars_from_bytes() {
+        let s = "a\u{e9}\u{20ac}\u{1f600}z";
+        let chars: Vec<_> = decode(s.as_bytes());
+
+        assert_eq!(s.chars().map(Ok).collect::<Vec<_>>(), chars);
+    }
+
+    #[test]
+    fn test_chars_from_bytes_prev() {
+        let mut chars = PrevPeekable::new("a\u{20ac}b".bytes()).chars_from_bytes();
+
+        assert_eq!(Some(Ok('a')), chars.next());
+        assert_eq!(Some(Ok('\u{20ac}')), chars.next());
+        assert_eq!(Some(Ok('b')), chars.next());
+        assert_eq!(Some(Ok('\u{20ac}')), chars.prev());
+    }
+
+    #[test]
+    fn test_chars_from_bytes_lone_continuation() {
+        assert_eq!(
+            vec![
+                Ok('a'),
+                invalid(1, 0x80, Some(b'a')),
+                invalid(2, 0xbf, Some(0x80)),
+                Ok('b')
+            ],
+            decode(b"a\x80\xbfb")
+        );
+    }
+
+    #[test]
+    fn test_chars_from_bytes_overlong() {
+        // '/' as two bytes: 0xc0 can only start an overlong encoding
+        assert_eq!(
+            vec![invalid(0, 0xc0, None), invalid(1, 0xaf, Some(0xc0))],
+            decode(b"\xc0\xaf")
+        );
+        // U+0000 as three bytes: ruled out by the second byte, so the sequence is blamed on its
+        // lead byte and the rest are lone continuation bytes
+        assert_eq!(
+            vec![
+                invalid(0, 0xe0, None),
+                invalid(1, 0x80, Some(0xe0)),
+                invalid(2, 0x80, Some(0x80)),
+            ],
+            decode(b"\xe0\x80\x80")
+        );
+    }
+
+    #[test]
+    fn test_chars_from_bytes_interrupted_sequence() {
+        // A three byte character cut short by an ASCII character, which is still decoded and
+        // isn't blamed for the error
+        assert_eq!(
+            vec![Ok('a'), invalid(1, 0xe2, Some(b'a')), Ok('x')],
+            decode(b"a\xe2\x82x")
+        );
+    }
+
+    #[test]
+    fn test_chars_from_bytes_truncated() {
+        let euro = "\u{20ac}".as_bytes();
+
+        for len in 1..euro.len() {
+            assert_eq!(
+                vec![
+                    Ok('a'),
+                    Err(Utf8Error {
+                        kind: Utf8ErrorKind::Incomplete,
+                        offset: len + 1,
+                        byte: None,
+                        prev: Some(euro[len - 1]),
+                    }),
+                ],
+                decode(&[&b"a"[..], &euro[..len]].concat())
+            );
+        }
+    }
+
+    #[test]
+    fn test_utf8_error_display() {
+        assert_eq!(
+            "incomplete UTF-8 character at offset 2 after 0x82",
+            decode(b"\xe2\x82")[0].unwrap_err().to_string()
+        );
+        assert_eq!(
+            "invalid UTF-8 byte 0xff at offset 0 at the start of input",
+            decode(b"\xff")[0].unwrap_err().to_string()
+        );
+    }
+}
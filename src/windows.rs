@@ -0,0 +1,319 @@
+//! Adapters over sliding windows of consecutive elements.
+
+use super::PrevPeekable;
+use std::collections::VecDeque;
+
+impl<I> PrevPeekable<I>
+where
+    I: Iterator,
+    <I as ::std::iter::Iterator>::Item: ::std::clone::Clone,
+{
+    /// Folds every window of `n` consecutive elements with `step`, starting from a fresh `init()`
+    /// each time, and yields the results. Like [`slice::windows`], the first result is yielded once
+    /// `n` elements have been read, and nothing is yielded if there are fewer than `n`.
+    ///
+    /// Only the last `n` elements are kept, and no window is allocated to fold over.
+    ///
+    /// [`slice::windows`]: https://doc.rust-lang.org/nightly/std/primitive.slice.html#method.windows
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![3, 1, 4, 1, 5];
+    /// let maxima: Vec<_> = PrevPeekable::new(v.into_iter())
+    ///     .windowed_fold(3, || 0, |max, &x| if x > max { x } else { max })
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![4, 4, 5], maxima);
+    /// ```
+    pub fn windowed_fold<B, F, G>(mut self, n: usize, init: F, mut step: G) -> impl Iterator<Item = B>
+    where
+        F: Fn() -> B,
+        G: FnMut(B, &I::Item) -> B,
+    {
+        assert!(n != 0, "window size must be non-zero");
+        let mut window = VecDeque::new();
+
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+
+            if window.len() == n {
+                window.pop_front();
+            }
+            window.push_back(cur);
+
+            if window.len() == n {
+                return Some(window.iter().fold(init(), &mut step));
+            }
+        })
+    }
+
+    /// Like [`windowed_fold`], but for operations that can be undone, such as sums and counts.
+    /// Instead of folding each window from scratch, the fold is updated as the window slides:
+    /// `add` is called with each element as it enters the window, and `remove` with each element as
+    /// it leaves.
+    ///
+    /// [`windowed_fold`]: #method.windowed_fold
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec![1, 2, 3, 4, 5];
+    /// let sums: Vec<_> = PrevPeekable::new(v.into_iter())
+    ///     .windowed_fold_invertible(2, 0, |sum, x| sum + x, |sum, x| sum - x)
+    ///     .collect();
+    ///
+    /// assert_eq!(vec![3, 5, 7, 9], sums);
+    /// ```
+    pub fn windowed_fold_invertible<B, F, G>(
+        mut self,
+        n: usize,
+        init: B,
+        mut add: F,
+        mut remove: G,
+    ) -> impl Iterator<Item = B>
+    where
+        B: Clone,
+        F: FnMut(B, &I::Item) -> B,
+        G: FnMut(B, &I::Item) -> B,
+    {
+        assert!(n != 0, "window size must be non-zero");
+        let mut window = VecDeque::new();
+        let mut acc = Some(init);
+
+        ::std::iter::from_fn(move || loop {
+            let cur = self.next()?;
+            let mut folded = acc.take().expect("the fold is put back after every step");
+
+            if window.len() == n {
+                let oldest = window.pop_front().expect("the window is full");
+                folded = remove(folded, &oldest);
+            }
+            folded = add(folded, &cur);
+            window.push_back(cur);
+            acc = Some(folded);
+
+            if window.len() == n {
+                return acc.clone();
+            }
+        })
+    }
+
+    /// Yields a window of the last `N` elements for every element, as an array with the current
+    /// element last. Unlike [`windowed_fold`], the leading windows aren't skipped while fewer than
+    /// `N` elements have been read: their missing history is filled in with `None` on the left, so
+    /// there are as many windows as elements.
+    ///
+    /// [`windowed_fold`]: #method.windowed_fold
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is 0.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use prev_iter::PrevPeekable;
+    ///
+    /// let v = vec!['a', 'b', 'c', 'd'];
+    /// let windows: Vec<_> = PrevPeekable::new(v.into_iter()).windows_padded::<3>().collect();
+    ///
+    /// assert_eq!(
+    ///     vec![
+    ///         [None, None, Some('a')],
+    ///         [None, Some('a'), Some('b')],
+    ///         [Some('a'), Some('b'), Some('c')],
+    ///         [Some('b'), Some('c'), Some('d')],
+    ///     ],
+    ///     windows
+    /// );
+    /// ```
+    pub fn windows_padded<const N: usize>(mut self) -> impl Iterator<Item = [Option<I::Item>; N]> {
+        assert!(N != 0, "window size must be non-zero");
+        let mut window: [Option<I::Item>; N] = ::std::array::from_fn(|_| None);
+
+        ::std::iter::from_fn(move || {
+            let cur = self.next()?;
+
+            window.rotate_left(1);
+            window[N - 1] = Some(cur);
+
+            Some(window.clone())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Generates a deterministic pseudo random vector with a linear congruential generator.
+    fn random_vec(len: usize) -> Vec<i64> {
+        let mut state: u64 = 0x853c_49e6_748f_ea9b;
+
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+                (state >> 44) as i64 - (1 << 19)
+            })
+            .collect()
+    }
+
+    fn naive_window_sums(v: &[i64], n: usize) -> Vec<i64> {
+        v.windows(n).map(|w| w.iter().sum()).collect()
+    }
+
+    #[test]
+    fn test_windowed_fold_matches_naive() {
+        let v = random_vec(1_000);
+
+        for &n in &[1, 2, 7, 1_000] {
+            let sums: Vec<_> = PrevPeekable::new(v.iter().cloned())
+                .windowed_fold(n, || 0, |sum, x| sum + x)
+                .collect();
+
+            assert_eq!(naive_window_sums(&v, n), sums);
+        }
+    }
+
+    #[test]
+    fn test_windowed_fold_invertible_matches_naive() {
+        let v = random_vec(1_000);
+
+        for &n in &[1, 2, 7, 1_000] {
+            let sums: Vec<_> = PrevPeekable::new(v.iter().cloned())
+                .windowed_fold_invertible(n, 0, |sum, x| sum + x, |sum, x| sum - x)
+                .collect();
+
+            assert_eq!(naive_window_sums(&v, n), sums);
+        }
+    }
+
+    #[test]
+    fn test_windowed_fold_order() {
+        let v = ['a', 'b', 'c', 'd'];
+        let windows: Vec<_> = PrevPeekable::new(v.iter())
+            .windowed_fold(3, String::new, |mut s, c| {
+                s.push(**c);
+                s
+            })
+            .collect();
+
+        assert_eq!(vec!["abc", "bcd"], windows);
+    }
+
+    #[test]
+    fn test_windowed_fold_shorter_than_window() {
+        let v = [1, 2];
+
+        assert_eq!(
+            None,
+            PrevPeekable::new(v.iter().cloned())
+                .windowed_fold(3, || 0, |sum, x| sum + x)
+                .next()
+        );
+        assert_eq!(
+            None,
+            PrevPeekable::new(v.iter().cloned())
+                .windowed_fold_invertible(3, 0, |sum, x| sum + x, |sum, x| sum - x)
+                .next()
+        );
+    }
+
+    #[test]
+    fn test_windowed_fold_huge_window() {
+        // The window grows as elements arrive instead of being allocated up front
+        let v = [1, 2];
+
+        assert_eq!(
+            None,
+            PrevPeekable::new(v.iter().cloned())
+                .windowed_fold(usize::MAX, || 0, |sum, x| sum + x)
+                .next()
+        );
+        assert_eq!(
+            None,
+            PrevPeekable::new(v.iter().cloned())
+                .windowed_fold_invertible(usize::MAX, 0, |sum, x| sum + x, |sum, x| sum - x)
+                .next()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "window size must be non-zero")]
+    fn test_windowed_fold_empty_window() {
+        let v = [1];
+        let _ = PrevPeekable::new(v.iter().cloned()).windowed_fold(0, || 0, |sum, x| sum + x);
+    }
+
+    #[test]
+    fn test_windows_padded() {
+        let windows: Vec<_> = PrevPeekable::new(1..6).windows_padded::<2>().collect();
+
+        assert_eq!(
+            vec![
+                [None, Some(1)],
+                [Some(1), Some(2)],
+                [Some(2), Some(3)],
+                [Some(3), Some(4)],
+                [Some(4), Some(5)],
+            ],
+            windows
+        );
+    }
+
+    #[test]
+    fn test_windows_padded_shorter_than_window() {
+        let windows: Vec<_> = PrevPeekable::new(1..3).windows_padded::<4>().collect();
+        assert_eq!(
+            vec![[None, None, None, Some(1)], [None, None, Some(1), Some(2)]],
+            windows
+        );
+
+        assert_eq!(None, PrevPeekable::new(0..0).windows_padded::<3>().next());
+    }
+
+    #[test]
+    fn test_windows_padded_matches_windows() {
+        // Once the window is full, the padded windows are the same as slice::windows
+        let v = random_vec(100);
+        let padded: Vec<Vec<i64>> = PrevPeekable::new(v.iter().cloned())
+            .windows_padded::<7>()
+            .skip(6)
+            .map(|w| w.iter().map(|x| x.unwrap()).collect())
+            .collect();
+
+        assert_eq!(v.windows(7).map(|w| w.to_vec()).collect::<Vec<_>>(), padded);
+    }
+
+    #[test]
+    fn test_windows_padded_single() {
+        let windows: Vec<_> = PrevPeekable::new("ab".chars()).windows_padded::<1>().collect();
+
+        assert_eq!(vec![[Some('a')], [Some('b')]], windows);
+    }
+
+    #[test]
+    #[should_panic(expected = "window size must be non-zero")]
+    fn test_windows_padded_empty_window() {
+        let _ = PrevPeekable::new(1..3).windows_padded::<0>();
+    }
+}